@@ -0,0 +1,50 @@
+//! `psp-ge-disasm`: print the commands of a raw display list dump
+//!
+//! ```text
+//! psp-ge-disasm <file> [address]
+//! ```
+//!
+//! The file contains little endian command words. With the address the list was located at
+//! (hexadecimal), data embedded in the list is skipped.
+
+use std::process::ExitCode;
+
+use psp_gfx_tools::disasm::{disasm, disasm_at};
+
+const USAGE: &str = "usage: psp-ge-disasm <file> [address]";
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().ok_or(USAGE)?;
+    let address = args
+        .next()
+        .map(|address| {
+            u32::from_str_radix(address.trim_start_matches("0x"), 16)
+                .map_err(|_| format!("invalid address '{address}'"))
+        })
+        .transpose()?;
+
+    let data = std::fs::read(&path).map_err(|err| format!("{path}: {err}"))?;
+    let words: Vec<u32> = data
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+        .collect();
+    let lines = match address {
+        Some(address) => disasm_at(&words, address),
+        None => disasm(&words),
+    };
+    for line in lines {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("psp-ge-disasm: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
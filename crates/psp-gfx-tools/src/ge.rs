@@ -0,0 +1,9 @@
+//! GE command encoder and dump recorder, shared with `psp_gfx::ge`
+
+#[path = "../../psp-gfx/src/ge/dump.rs"]
+pub mod dump;
+#[path = "../../psp-gfx/src/ge/encoder.rs"]
+pub mod encoder;
+pub mod sys;
+
+pub use encoder::*;
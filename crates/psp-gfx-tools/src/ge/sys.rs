@@ -0,0 +1,543 @@
+//! Mirrors of the `psp::sys` types used by the GE [`encoder`](super::encoder),
+//! [`shapes`](crate::shapes), [`quantize`](crate::quantize),
+//! [`screenshot`](crate::screenshot), [`texture`](crate::texture), [`clip`](crate::clip)
+//! and the model [`file`](crate::model::file) parser
+//!
+//! The `psp` crate only builds for the PSP, so the host tools define the same types with
+//! the same values. Only the parts the shared modules and their tests use are mirrored.
+
+use core::ops::BitOr;
+
+/// Vertex format bits, mirrors the `bitflags` type of `psp::sys`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexType(i32);
+
+impl VertexType {
+    pub const TEXTURE_8BIT: Self = Self(1);
+    pub const TEXTURE_16BIT: Self = Self(2);
+    pub const TEXTURE_32BITF: Self = Self(3);
+    pub const COLOR_5650: Self = Self(4 << 2);
+    pub const COLOR_5551: Self = Self(5 << 2);
+    pub const COLOR_4444: Self = Self(6 << 2);
+    pub const COLOR_8888: Self = Self(7 << 2);
+    pub const NORMAL_8BIT: Self = Self(1 << 5);
+    pub const NORMAL_16BIT: Self = Self(2 << 5);
+    pub const NORMAL_32BITF: Self = Self(3 << 5);
+    pub const VERTEX_8BIT: Self = Self(1 << 7);
+    pub const VERTEX_16BIT: Self = Self(2 << 7);
+    pub const VERTEX_32BITF: Self = Self(3 << 7);
+    pub const WEIGHT_8BIT: Self = Self(1 << 9);
+    pub const WEIGHT_16BIT: Self = Self(2 << 9);
+    pub const WEIGHT_32BITF: Self = Self(3 << 9);
+    pub const INDEX_8BIT: Self = Self(1 << 11);
+    pub const INDEX_16BIT: Self = Self(2 << 11);
+    pub const TRANSFORM_3D: Self = Self(0);
+    pub const TRANSFORM_2D: Self = Self(1 << 23);
+
+    /// Bits of all flags, including the weight and morph vertex counts
+    const ALL: i32 = 0x1fff | (7 << 14) | (7 << 18) | (1 << 23);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Convert bits, `None` if a bit doesn't belong to a flag
+    pub const fn from_bits(bits: i32) -> Option<Self> {
+        if bits & !Self::ALL == 0 {
+            Some(Self(bits))
+        } else {
+            None
+        }
+    }
+
+    pub const fn bits(&self) -> i32 {
+        self.0
+    }
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for VertexType {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScePspFVector4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScePspFMatrix4 {
+    pub x: ScePspFVector4,
+    pub y: ScePspFVector4,
+    pub z: ScePspFVector4,
+    pub w: ScePspFVector4,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeCommand {
+    Nop = 0,
+    Vaddr = 0x1,
+    Iaddr = 0x2,
+    Prim = 0x4,
+    Bezier = 0x5,
+    Spline = 0x6,
+    BoundingBox = 0x7,
+    Jump = 0x8,
+    BJump = 0x9,
+    Call = 0xa,
+    Ret = 0xb,
+    End = 0xc,
+    Signal = 0xe,
+    Finish = 0xf,
+    Base = 0x10,
+    VertexType = 0x12,
+    OffsetAddr = 0x13,
+    Origin = 0x14,
+    Region1 = 0x15,
+    Region2 = 0x16,
+    LightingEnable = 0x17,
+    LightEnable0 = 0x18,
+    LightEnable1 = 0x19,
+    LightEnable2 = 0x1a,
+    LightEnable3 = 0x1b,
+    DepthClampEnable = 0x1c,
+    CullFaceEnable = 0x1d,
+    TextureMapEnable = 0x1e,
+    FogEnable = 0x1f,
+    DitherEnable = 0x20,
+    AlphaBlendEnable = 0x21,
+    AlphaTestEnable = 0x22,
+    ZTestEnable = 0x23,
+    StencilTestEnable = 0x24,
+    AntiAliasEnable = 0x25,
+    PatchCullEnable = 0x26,
+    ColorTestEnable = 0x27,
+    LogicOpEnable = 0x28,
+    BoneMatrixNumber = 0x2a,
+    BoneMatrixData = 0x2b,
+    MorphWeight0 = 0x2c,
+    MorphWeight1 = 0x2d,
+    MorphWeight2 = 0x2e,
+    MorphWeight3 = 0x2f,
+    MorphWeight4 = 0x30,
+    MorphWeight5 = 0x31,
+    MorphWeight6 = 0x32,
+    MorphWeight7 = 0x33,
+    PatchDivision = 0x36,
+    PatchPrimitive = 0x37,
+    PatchFacing = 0x38,
+    WorldMatrixNumber = 0x3a,
+    WorldMatrixData = 0x3b,
+    ViewMatrixNumber = 0x3c,
+    ViewMatrixData = 0x3d,
+    ProjMatrixNumber = 0x3e,
+    ProjMatrixData = 0x3f,
+    TGenMatrixNumber = 0x40,
+    TGenMatrixData = 0x41,
+    ViewportXScale = 0x42,
+    ViewportYScale = 0x43,
+    ViewportZScale = 0x44,
+    ViewportXCenter = 0x45,
+    ViewportYCenter = 0x46,
+    ViewportZCenter = 0x47,
+    TexScaleU = 0x48,
+    TexScaleV = 0x49,
+    TexOffsetU = 0x4a,
+    TexOffsetV = 0x4b,
+    OffsetX = 0x4c,
+    OffsetY = 0x4d,
+    ShadeMode = 0x50,
+    ReverseNormal = 0x51,
+    MaterialUpdate = 0x53,
+    MaterialEmissive = 0x54,
+    MaterialAmbient = 0x55,
+    MaterialDiffuse = 0x56,
+    MaterialSpecular = 0x57,
+    MaterialAlpha = 0x58,
+    MaterialSpecularCoef = 0x5b,
+    AmbientColor = 0x5c,
+    AmbientAlpha = 0x5d,
+    LightMode = 0x5e,
+    LightType0 = 0x5f,
+    LightType1 = 0x60,
+    LightType2 = 0x61,
+    LightType3 = 0x62,
+    Light0X = 0x63,
+    Light0Y,
+    Light0Z,
+    Light1X,
+    Light1Y,
+    Light1Z,
+    Light2X,
+    Light2Y,
+    Light2Z,
+    Light3X,
+    Light3Y,
+    Light3Z,
+    Light0DirectionX = 0x6f,
+    Light0DirectionY,
+    Light0DirectionZ,
+    Light1DirectionX,
+    Light1DirectionY,
+    Light1DirectionZ,
+    Light2DirectionX,
+    Light2DirectionY,
+    Light2DirectionZ,
+    Light3DirectionX,
+    Light3DirectionY,
+    Light3DirectionZ,
+    Light0ConstantAtten = 0x7b,
+    Light0LinearAtten,
+    Light0QuadtraticAtten,
+    Light1ConstantAtten,
+    Light1LinearAtten,
+    Light1QuadtraticAtten,
+    Light2ConstantAtten,
+    Light2LinearAtten,
+    Light2QuadtraticAtten,
+    Light3ConstantAtten,
+    Light3LinearAtten,
+    Light3QuadtraticAtten,
+    Light0ExponentAtten = 0x87,
+    Light1ExponentAtten,
+    Light2ExponentAtten,
+    Light3ExponentAtten,
+    Light0CutoffAtten = 0x8b,
+    Light1CutoffAtten,
+    Light2CutoffAtten,
+    Light3CutoffAtten,
+    Light0Ambient = 0x8f,
+    Light0Diffuse,
+    Light0Specular,
+    Light1Ambient,
+    Light1Diffuse,
+    Light1Specular,
+    Light2Ambient,
+    Light2Diffuse,
+    Light2Specular,
+    Light3Ambient,
+    Light3Diffuse,
+    Light3Specular,
+    Cull = 0x9b,
+    FrameBufPtr = 0x9c,
+    FrameBufWidth = 0x9d,
+    ZBufPtr = 0x9e,
+    ZBufWidth = 0x9f,
+    TexAddr0 = 0xa0,
+    TexAddr1,
+    TexAddr2,
+    TexAddr3,
+    TexAddr4,
+    TexAddr5,
+    TexAddr6,
+    TexAddr7,
+    TexBufWidth0 = 0xa8,
+    TexBufWidth1,
+    TexBufWidth2,
+    TexBufWidth3,
+    TexBufWidth4,
+    TexBufWidth5,
+    TexBufWidth6,
+    TexBufWidth7,
+    ClutAddr = 0xb0,
+    ClutAddrUpper = 0xb1,
+    TransferSrc,
+    TransferSrcW,
+    TransferDst,
+    TransferDstW,
+    TexSize0 = 0xb8,
+    TexSize1,
+    TexSize2,
+    TexSize3,
+    TexSize4,
+    TexSize5,
+    TexSize6,
+    TexSize7,
+    TexMapMode = 0xc0,
+    TexShadeLs = 0xc1,
+    TexMode = 0xc2,
+    TexFormat = 0xc3,
+    LoadClut = 0xc4,
+    ClutFormat = 0xc5,
+    TexFilter = 0xc6,
+    TexWrap = 0xc7,
+    TexLevel = 0xc8,
+    TexFunc = 0xc9,
+    TexEnvColor = 0xca,
+    TexFlush = 0xcb,
+    TexSync = 0xcc,
+    Fog1 = 0xcd,
+    Fog2 = 0xce,
+    FogColor = 0xcf,
+    TexLodSlope = 0xd0,
+    FramebufPixFormat = 0xd2,
+    ClearMode = 0xd3,
+    Scissor1 = 0xd4,
+    Scissor2 = 0xd5,
+    MinZ = 0xd6,
+    MaxZ = 0xd7,
+    ColorTest = 0xd8,
+    ColorRef = 0xd9,
+    ColorTestmask = 0xda,
+    AlphaTest = 0xdb,
+    StencilTest = 0xdc,
+    StencilOp = 0xdd,
+    ZTest = 0xde,
+    BlendMode = 0xdf,
+    BlendFixedA = 0xe0,
+    BlendFixedB = 0xe1,
+    Dith0 = 0xe2,
+    Dith1,
+    Dith2,
+    Dith3,
+    LogicOp = 0xe6,
+    ZWriteDisable = 0xe7,
+    MaskRgb = 0xe8,
+    MaskAlpha = 0xe9,
+    TransferStart = 0xea,
+    TransferSrcPos = 0xeb,
+    TransferDstPos = 0xec,
+    TransferSize = 0xee,
+    Vscx = 0xf0,
+    Vscy = 0xf1,
+    Vscz = 0xf2,
+    Vtcs = 0xf3,
+    Vtct = 0xf4,
+    Vtcq = 0xf5,
+    Vcv = 0xf6,
+    Vap = 0xf7,
+    Vfc = 0xf8,
+    Vscv = 0xf9,
+
+    Unknown03 = 0x03,
+    Unknown0D = 0x0d,
+    Unknown11 = 0x11,
+    Unknown29 = 0x29,
+    Unknown34 = 0x34,
+    Unknown35 = 0x35,
+    Unknown39 = 0x39,
+    Unknown4E = 0x4e,
+    Unknown4F = 0x4f,
+    Unknown52 = 0x52,
+    Unknown59 = 0x59,
+    Unknown5A = 0x5a,
+    UnknownB6 = 0xb6,
+    UnknownB7 = 0xb7,
+    UnknownD1 = 0xd1,
+    UnknownED = 0xed,
+    UnknownEF = 0xef,
+    UnknownFA = 0xfa,
+    UnknownFB = 0xfb,
+    UnknownFC = 0xfc,
+    UnknownFD = 0xfd,
+    UnknownFE = 0xfe,
+    NopFF = 0xff,
+}
+
+/// Primitive types
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuPrimitive {
+    /// Single pixel points (1 vertex per primitive)
+    Points = 0,
+    Lines = 1,
+    LineStrip = 2,
+    Triangles = 3,
+    TriangleStrip = 4,
+    TriangleFan = 5,
+    Sprites = 6,
+}
+
+/// Patch primitive types
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatchPrimitive {
+    /// Single pixel points (1 vertex per primitive)
+    Points = 0,
+    LineStrip = 2,
+    TriangleStrip = 4,
+}
+
+/// Spline Mode
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplineMode {
+    FillFill = 0,
+    OpenFill = 1,
+    FillOpen = 2,
+    OpenOpen = 3,
+}
+
+/// Front Face Direction
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontFaceDirection {
+    Clockwise = 0,
+    CounterClockwise = 1,
+}
+
+/// Shading Model
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadingModel {
+    Flat = 0,
+    Smooth = 1,
+}
+
+/// Matrix modes
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixMode {
+    Projection = 0,
+    View = 1,
+    Model = 2,
+    Texture = 3,
+}
+
+/// Framebuffer pixel formats
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayPixelFormat {
+    /// 16-bit RGB 5:6:5
+    Psm5650 = 0,
+    /// 16-bit RGBA 5:5:5:1
+    Psm5551 = 1,
+    /// 16-bit RGBA 4:4:4:4
+    Psm4444 = 2,
+    /// 32-bit RGBA 8:8:8:8
+    Psm8888 = 3,
+}
+
+/// Texture pixel formats
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TexturePixelFormat {
+    /// Hicolor, 16-bit.
+    Psm5650 = 0,
+    Psm5551 = 1,
+    Psm4444 = 2,
+    Psm8888 = 3,
+    PsmT4 = 4,
+    PsmT8 = 5,
+    PsmT16 = 6,
+    PsmT32 = 7,
+    PsmDxt1 = 8,
+    PsmDxt3 = 9,
+    PsmDxt5 = 10,
+}
+
+/// Mipmap Level
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MipmapLevel {
+    None = 0,
+    Level1,
+    Level2,
+    Level3,
+    Level4,
+    Level5,
+    Level6,
+    Level7,
+}
+
+/// Texture Filter
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest = 0,
+    Linear = 1,
+    NearestMipmapNearest = 4,
+    LinearMipmapNearest = 5,
+    NearestMipmapLinear = 6,
+    LinearMipmapLinear = 7,
+}
+
+/// Wrap Mode
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuTexWrapMode {
+    /// The texture repeats after crossing the border
+    Repeat = 0,
+    Clamp = 1,
+}
+
+/// Texture Level Mode
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureLevelMode {
+    Auto = 0,
+    Const = 1,
+    Slope = 2,
+}
+
+/// Test function for stencil test
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StencilFunc {
+    Never = 0,
+    Always,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// Stencil Operations
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StencilOperation {
+    /// Keeps the current value
+    Keep = 0,
+    Zero = 1,
+    Replace = 2,
+    Invert = 3,
+    Incr = 4,
+    Decr = 5,
+}
+
+/// Test function for color test
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorFunc {
+    Never = 0,
+    Always,
+    Equal,
+    NotEqual,
+}
+
+/// Logical operation
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicalOperation {
+    Clear = 0,
+    And = 1,
+    AndReverse = 2,
+    Copy = 3,
+    AndInverted = 4,
+    Noop = 5,
+    Xor = 6,
+    Or = 7,
+    Nor = 8,
+    Equiv = 9,
+    Inverted = 10,
+    OrReverse = 11,
+    CopyInverted = 12,
+    OrInverted = 13,
+    Nand = 14,
+    Set = 15,
+}
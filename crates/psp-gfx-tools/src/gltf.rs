@@ -0,0 +1,401 @@
+//! glTF 2.0 importer (`.gltf` with external or embedded buffers, and `.glb`)
+//!
+//! All primitives of all meshes are merged into one mesh, in mesh space (node transforms
+//! are ignored). Supported attributes are `POSITION`, `NORMAL`, `TEXCOORD_0` and `COLOR_0`,
+//! the base color factor and texture of the metallic-roughness material are imported.
+//! Sparse accessors are not supported.
+
+use crate::{
+    json::{self, Value},
+    scene::{MaterialData, MeshData, SubmeshData},
+};
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const CHUNK_JSON: u32 = 0x4e4f534a;
+const CHUNK_BIN: u32 = 0x004e4942;
+
+const MODE_TRIANGLES: usize = 4;
+const MODE_TRIANGLE_STRIP: usize = 5;
+const MODE_TRIANGLE_FAN: usize = 6;
+
+/// Parse a glTF or GLB file, `load_file` is used to read external buffers relative to the model
+pub fn parse(data: &[u8], load_file: impl Fn(&str) -> Option<Vec<u8>>) -> Result<MeshData, String> {
+    let (text, glb_buffer) = if data.starts_with(GLB_MAGIC) {
+        split_glb(data)?
+    } else {
+        (
+            std::str::from_utf8(data).map_err(|_| "glTF document is not valid UTF-8")?,
+            None,
+        )
+    };
+    let document = json::parse(text)?;
+
+    let mut buffers = Vec::new();
+    for (idx, buffer) in array(&document, "buffers").iter().enumerate() {
+        let data = match buffer.get("uri").and_then(Value::as_str) {
+            Some(uri) => match uri.strip_prefix("data:") {
+                Some(data_uri) => {
+                    let (_, encoded) = data_uri
+                        .split_once(";base64,")
+                        .ok_or("only base64 data URIs are supported")?;
+                    base64_decode(encoded)?
+                }
+                None => load_file(&percent_decode(uri))
+                    .ok_or_else(|| format!("failed to read buffer '{uri}'"))?,
+            },
+            None if idx == 0 => glb_buffer.clone().ok_or("buffer 0 has no data")?,
+            None => return Err(format!("buffer {idx} has no uri")),
+        };
+        buffers.push(data);
+    }
+    let reader = AccessorReader {
+        document: &document,
+        buffers: &buffers,
+    };
+
+    let materials = array(&document, "materials")
+        .iter()
+        .map(|material| read_material(&document, material))
+        .collect::<Vec<_>>();
+
+    let mut result = MeshData::default();
+    for mesh in array(&document, "meshes") {
+        for primitive in mesh
+            .get("primitives")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+        {
+            result.append(read_primitive(&reader, primitive, &materials)?);
+        }
+    }
+    Ok(result)
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value.get(key).and_then(Value::as_array).unwrap_or_default()
+}
+
+/// Split a GLB container into the JSON document and the binary buffer
+fn split_glb(data: &[u8]) -> Result<(&str, Option<Vec<u8>>), String> {
+    let u32_at = |offset: usize| -> Result<u32, String> {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| "truncated GLB file".to_owned())
+    };
+    if u32_at(4)? != 2 {
+        return Err("unsupported GLB version".into());
+    }
+    let length = (u32_at(8)? as usize).min(data.len());
+    let mut offset = 12;
+    let mut json = None;
+    let mut bin = None;
+    while offset + 8 <= length {
+        let chunk_length = u32_at(offset)? as usize;
+        let chunk_type = u32_at(offset + 4)?;
+        let chunk = data
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or("truncated GLB chunk")?;
+        match chunk_type {
+            CHUNK_JSON => {
+                json = Some(std::str::from_utf8(chunk).map_err(|_| "invalid GLB JSON chunk")?)
+            }
+            CHUNK_BIN => bin = Some(chunk.to_vec()),
+            _ => (),
+        }
+        offset += 8 + chunk_length.next_multiple_of(4);
+    }
+    Ok((json.ok_or("GLB file has no JSON chunk")?, bin))
+}
+
+fn read_material(document: &Value, material: &Value) -> MaterialData {
+    let pbr = material.get("pbrMetallicRoughness");
+    let mut color = [1.; 4];
+    if let Some(factor) = pbr
+        .and_then(|pbr| pbr.get("baseColorFactor"))
+        .and_then(Value::as_array)
+    {
+        for (dst, value) in color.iter_mut().zip(factor) {
+            *dst = value.as_f64().unwrap_or(1.) as f32;
+        }
+    }
+    let texture = pbr
+        .and_then(|pbr| pbr.get("baseColorTexture"))
+        .and_then(|texture| texture.get("index"))
+        .and_then(Value::as_usize)
+        .and_then(|idx| array(document, "textures").get(idx))
+        .and_then(|texture| texture.get("source"))
+        .and_then(Value::as_usize)
+        .and_then(|idx| array(document, "images").get(idx))
+        .and_then(|image| image.get("uri"))
+        .and_then(Value::as_str)
+        .filter(|uri| !uri.starts_with("data:"))
+        .map(percent_decode);
+    MaterialData { color, texture }
+}
+
+fn read_primitive(
+    reader: &AccessorReader,
+    primitive: &Value,
+    materials: &[MaterialData],
+) -> Result<MeshData, String> {
+    let attributes = primitive
+        .get("attributes")
+        .ok_or("primitive has no attributes")?;
+    let attribute = |name: &str| attributes.get(name).and_then(Value::as_usize);
+
+    let positions = reader.read_vectors::<3>(
+        attribute("POSITION").ok_or("primitive has no positions")?,
+        0.,
+    )?;
+    let uvs = match attribute("TEXCOORD_0") {
+        Some(accessor) => reader.read_vectors::<2>(accessor, 0.)?,
+        None => Vec::new(),
+    };
+    let normals = match attribute("NORMAL") {
+        Some(accessor) => reader.read_vectors::<3>(accessor, 0.)?,
+        None => Vec::new(),
+    };
+    let colors = match attribute("COLOR_0") {
+        Some(accessor) => reader.read_vectors::<4>(accessor, 1.)?,
+        None => Vec::new(),
+    };
+
+    let elements = match primitive.get("indices").and_then(Value::as_usize) {
+        Some(accessor) => reader
+            .read_scalars(accessor)?
+            .into_iter()
+            .map(|index| index as u32)
+            .collect(),
+        None => (0..positions.len() as u32).collect::<Vec<_>>(),
+    };
+    if elements
+        .iter()
+        .any(|&index| index as usize >= positions.len())
+    {
+        return Err("index out of range".into());
+    }
+    let mode = primitive
+        .get("mode")
+        .and_then(Value::as_usize)
+        .unwrap_or(MODE_TRIANGLES);
+    let indices = triangulate(mode, &elements)?;
+
+    let material = primitive
+        .get("material")
+        .and_then(Value::as_usize)
+        .and_then(|idx| materials.get(idx))
+        .cloned()
+        .unwrap_or_default();
+    Ok(MeshData {
+        positions,
+        uvs,
+        normals,
+        colors,
+        submeshes: vec![SubmeshData {
+            start: 0,
+            count: indices.len(),
+            material: 0,
+        }],
+        indices,
+        materials: vec![material],
+    })
+}
+
+/// Convert strips and fans to a triangle list
+fn triangulate(mode: usize, elements: &[u32]) -> Result<Vec<u32>, String> {
+    let count = elements.len().saturating_sub(2);
+    Ok(match mode {
+        MODE_TRIANGLES => elements[..elements.len() / 3 * 3].to_vec(),
+        MODE_TRIANGLE_STRIP => (0..count)
+            .flat_map(|idx| {
+                if idx % 2 == 0 {
+                    [elements[idx], elements[idx + 1], elements[idx + 2]]
+                } else {
+                    [elements[idx + 1], elements[idx], elements[idx + 2]]
+                }
+            })
+            .collect(),
+        MODE_TRIANGLE_FAN => (0..count)
+            .flat_map(|idx| [elements[0], elements[idx + 1], elements[idx + 2]])
+            .collect(),
+        _ => return Err(format!("unsupported primitive mode {mode}")),
+    })
+}
+
+struct AccessorReader<'a> {
+    document: &'a Value,
+    buffers: &'a [Vec<u8>],
+}
+
+impl AccessorReader<'_> {
+    /// Read an accessor as vectors of `N` floats, normalized integers are converted to `0..=1`
+    /// (or `-1..=1`), missing components are filled with `fill`
+    fn read_vectors<const N: usize>(
+        &self,
+        accessor: usize,
+        fill: f32,
+    ) -> Result<Vec<[f32; N]>, String> {
+        let (values, components) = self.read(accessor)?;
+        Ok(values
+            .chunks(components)
+            .map(|chunk| std::array::from_fn(|idx| chunk.get(idx).copied().unwrap_or(fill)))
+            .collect())
+    }
+
+    /// Read an accessor of scalars without normalization (e.g. indices)
+    fn read_scalars(&self, accessor: usize) -> Result<Vec<f64>, String> {
+        let accessor_value = &array(self.document, "accessors")
+            .get(accessor)
+            .ok_or("accessor out of range")?;
+        let (raw, _) = self.read_raw(accessor_value)?;
+        Ok(raw)
+    }
+
+    fn read(&self, accessor: usize) -> Result<(Vec<f32>, usize), String> {
+        let accessor = array(self.document, "accessors")
+            .get(accessor)
+            .ok_or("accessor out of range")?;
+        let (raw, component_type) = self.read_raw(accessor)?;
+        let normalized = accessor
+            .get("normalized")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let scale = match (normalized, component_type) {
+            (true, 5120) => 127.,
+            (true, 5121) => 255.,
+            (true, 5122) => 32767.,
+            (true, 5123) => 65535.,
+            _ => 1.,
+        };
+        let values = raw
+            .into_iter()
+            .map(|value| ((value / scale) as f32).max(-1.))
+            .collect();
+        Ok((values, component_count(accessor)?))
+    }
+
+    /// Read all components of an accessor, returns the values and the component type
+    fn read_raw(&self, accessor: &Value) -> Result<(Vec<f64>, usize), String> {
+        if accessor.get("sparse").is_some() {
+            return Err("sparse accessors are not supported".into());
+        }
+        let component_type = accessor
+            .get("componentType")
+            .and_then(Value::as_usize)
+            .ok_or("accessor has no component type")?;
+        let count = accessor
+            .get("count")
+            .and_then(Value::as_usize)
+            .ok_or("accessor has no count")?;
+        let components = component_count(accessor)?;
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(format!("invalid component type {component_type}")),
+        };
+        let Some(view) = accessor.get("bufferView").and_then(Value::as_usize) else {
+            // Accessors without a buffer view are all zeros
+            return Ok((vec![0.; count * components], component_type));
+        };
+        let view = array(self.document, "bufferViews")
+            .get(view)
+            .ok_or("buffer view out of range")?;
+        let buffer = view
+            .get("buffer")
+            .and_then(Value::as_usize)
+            .and_then(|idx| self.buffers.get(idx))
+            .ok_or("buffer out of range")?;
+        let view_offset = view
+            .get("byteOffset")
+            .and_then(Value::as_usize)
+            .unwrap_or(0);
+        let offset = accessor
+            .get("byteOffset")
+            .and_then(Value::as_usize)
+            .unwrap_or(0);
+        let element_size = component_size * components;
+        let stride = view
+            .get("byteStride")
+            .and_then(Value::as_usize)
+            .unwrap_or(element_size);
+
+        let mut values = Vec::with_capacity(count * components);
+        for element in 0..count {
+            let start = view_offset + offset + element * stride;
+            let bytes = buffer
+                .get(start..start + element_size)
+                .ok_or("accessor out of buffer bounds")?;
+            for component in bytes.chunks(component_size) {
+                values.push(match component_type {
+                    5120 => component[0] as i8 as f64,
+                    5121 => component[0] as f64,
+                    5122 => i16::from_le_bytes([component[0], component[1]]) as f64,
+                    5123 => u16::from_le_bytes([component[0], component[1]]) as f64,
+                    5125 => u32::from_le_bytes(component.try_into().unwrap()) as f64,
+                    _ => f32::from_le_bytes(component.try_into().unwrap()) as f64,
+                });
+            }
+        }
+        Ok((values, component_type))
+    }
+}
+
+fn component_count(accessor: &Value) -> Result<usize, String> {
+    match accessor.get("type").and_then(Value::as_str) {
+        Some("SCALAR") => Ok(1),
+        Some("VEC2") => Ok(2),
+        Some("VEC3") => Ok(3),
+        Some("VEC4") => Ok(4),
+        other => Err(format!("unsupported accessor type {other:?}")),
+    }
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut output = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for byte in text.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => return Err("invalid base64 data".into()),
+        };
+        bits = ((bits << 6) | value as u32) & 0xffffff;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            output.push((bits >> bit_count) as u8);
+        }
+    }
+    Ok(output)
+}
+
+/// Decode `%XX` escapes in URIs
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                output.push(byte);
+                idx += 3;
+            }
+            None => {
+                output.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
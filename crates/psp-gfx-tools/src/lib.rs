@@ -0,0 +1,43 @@
+//! Host side asset tools for `psp-gfx`
+//!
+//! These run on the development machine and produce files loaded at runtime by the
+//! `psp-gfx` crate, they don't depend on it as it only builds for the PSP.
+
+extern crate alloc;
+
+/// CPU clipping of 3D triangles, shared with `psp_gfx::clip`
+#[path = "../../psp-gfx/src/clip.rs"]
+pub mod clip;
+/// Color types and sRGB conversion, shared with `psp_gfx::color`
+#[path = "../../psp-gfx/src/color.rs"]
+pub mod color;
+/// GE display list disassembler, shared with `psp_gfx::ge::disasm`
+#[path = "../../psp-gfx/src/ge/disasm.rs"]
+pub mod disasm;
+pub mod ge;
+pub mod gltf;
+pub mod json;
+pub mod model;
+pub mod obj;
+/// Fixed point vertex quantization, shared with `psp_gfx::quantize`
+#[path = "../../psp-gfx/src/quantize.rs"]
+pub mod quantize;
+/// Rectangle and point types, shared with `psp_gfx::rect`
+#[path = "../../psp-gfx/src/rect.rs"]
+pub mod rect;
+pub mod scene;
+/// Framebuffer conversion and BMP/PNG encoding, shared with `psp_gfx::screenshot`
+#[path = "../../psp-gfx/src/screenshot.rs"]
+pub mod screenshot;
+/// 2D shape tessellation, shared with `psp_gfx::gfx_ext::shapes`
+#[path = "../../psp-gfx/src/gfx_ext/shapes.rs"]
+pub mod shapes;
+pub mod text;
+pub mod texture;
+/// Block transfer clipping and splitting, shared with `psp_gfx::transfer`
+#[path = "../../psp-gfx/src/transfer.rs"]
+pub mod transfer;
+pub mod vertex;
+/// VRAM allocator, shared with `psp_gfx::vram`
+#[path = "../../psp-gfx/src/vram/heap.rs"]
+pub mod vram;
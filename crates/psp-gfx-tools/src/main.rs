@@ -0,0 +1,90 @@
+//! `psp-model`: convert OBJ and glTF models into the `psp_gfx::model` format
+//!
+//! ```text
+//! psp-model <input> <output> [--position 8|16|f32] [--uv none|8|16|f32]
+//!                            [--normal none|8|16|f32] [--color]
+//! ```
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use psp_gfx_tools::model::{Format, VertexLayout, write_model};
+use psp_gfx_tools::{gltf, obj};
+
+const USAGE: &str = "usage: psp-model <input> <output> [--position 8|16|f32] \
+                     [--uv none|8|16|f32] [--normal none|8|16|f32] [--color]";
+
+fn parse_format(value: Option<String>) -> Result<Option<Format>, String> {
+    match value.as_deref() {
+        Some("none") => Ok(None),
+        Some("8") => Ok(Some(Format::I8)),
+        Some("16") => Ok(Some(Format::I16)),
+        Some("f32") => Ok(Some(Format::F32)),
+        Some(value) => Err(format!("invalid format '{value}'")),
+        None => Err("missing format".to_owned()),
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut paths = Vec::new();
+    let mut layout = VertexLayout {
+        uv: Some(Format::F32),
+        color: false,
+        normal: None,
+        position: Format::F32,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--position" => {
+                layout.position =
+                    parse_format(args.next())?.ok_or("positions can't be disabled")?;
+            }
+            "--uv" => layout.uv = parse_format(args.next())?,
+            "--normal" => layout.normal = parse_format(args.next())?,
+            "--color" => layout.color = true,
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{arg}'\n{USAGE}")),
+            _ => paths.push(arg),
+        }
+    }
+    let [input, output] = <[String; 2]>::try_from(paths).map_err(|_| USAGE.to_owned())?;
+
+    let input = Path::new(&input);
+    let dir = input.parent().unwrap_or(Path::new(""));
+    let data = std::fs::read(input).map_err(|err| format!("{}: {err}", input.display()))?;
+    let extension = input
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let mesh = match extension.as_deref() {
+        Some("obj") => {
+            let text = String::from_utf8(data).map_err(|_| "OBJ file is not valid UTF-8")?;
+            obj::parse(&text, |name| std::fs::read_to_string(dir.join(name)).ok())
+        }
+        Some("gltf" | "glb") => gltf::parse(&data, |name| std::fs::read(dir.join(name)).ok()),
+        _ => Err("unsupported input, expected .obj, .gltf or .glb".to_owned()),
+    }
+    .map_err(|err| format!("{}: {err}", input.display()))?;
+
+    let model = write_model(&mesh, &layout)?;
+    std::fs::write(&output, model).map_err(|err| format!("{output}: {err}"))?;
+    println!(
+        "{output}: {} vertices, {} indices, {} submeshes, vertex type {:#x}",
+        mesh.positions.len(),
+        mesh.indices.len(),
+        mesh.submeshes.len(),
+        layout.vertex_type()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("psp-model: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
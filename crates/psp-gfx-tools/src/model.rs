@@ -0,0 +1,286 @@
+//! Quantization and serialization into the `psp_gfx::model` binary format
+//!
+//! See the `psp_gfx::model` module for the format description.
+
+use crate::{
+    color::ColorF32,
+    quantize::{Fixed, Quantizer, quantize_normal},
+    scene::MeshData,
+};
+
+/// Parser of the model format, shared with `psp_gfx::model`
+#[path = "../../psp-gfx/src/model/file.rs"]
+pub mod file;
+
+use file::{MAGIC, VERSION};
+const PRIMITIVE_TRIANGLES: u32 = 3;
+
+/// Storage format of a vertex attribute
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// 8-bit fixed point (`*_8BIT`)
+    I8,
+    /// 16-bit fixed point (`*_16BIT`)
+    I16,
+    /// 32-bit float (`*_32BITF`)
+    F32,
+}
+
+impl Format {
+    fn size(self) -> usize {
+        match self {
+            Format::I8 => 1,
+            Format::I16 => 2,
+            Format::F32 => 4,
+        }
+    }
+
+    /// Value of the format in the 2-bit vertex type fields
+    fn bits(self) -> u32 {
+        match self {
+            Format::I8 => 1,
+            Format::I16 => 2,
+            Format::F32 => 3,
+        }
+    }
+}
+
+/// Vertex layout of the output, matching a `define_vertex_layout!` with `TRANSFORM_3D`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexLayout {
+    pub uv: Option<Format>,
+    /// Store `COLOR_8888` vertex colors
+    pub color: bool,
+    pub normal: Option<Format>,
+    pub position: Format,
+}
+
+impl VertexLayout {
+    /// Get the vertex type bits (`VertexType`)
+    pub fn vertex_type(&self) -> u32 {
+        let mut bits = self.position.bits() << 7;
+        if let Some(uv) = self.uv {
+            bits |= uv.bits();
+        }
+        if self.color {
+            bits |= 7 << 2;
+        }
+        if let Some(normal) = self.normal {
+            bits |= normal.bits() << 5;
+        }
+        bits
+    }
+
+    /// Get the attributes in vertex order with their byte offsets, and the vertex size
+    ///
+    /// Each attribute is aligned to its component size, the vertex size is a multiple of 4.
+    fn offsets(&self) -> ([usize; 4], usize) {
+        let mut offset = 0usize;
+        let mut place = |size: usize, count: usize| {
+            offset = offset.next_multiple_of(size);
+            let start = offset;
+            offset += size * count;
+            start
+        };
+        let uv = self.uv.map_or(0, |format| place(format.size(), 2));
+        let color = if self.color { place(4, 1) } else { 0 };
+        let normal = self.normal.map_or(0, |format| place(format.size(), 3));
+        let position = place(self.position.size(), 3);
+        ([uv, color, normal, position], offset.next_multiple_of(4))
+    }
+}
+
+/// Scale and offset decoding quantized values (`value * scale + offset`)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform<const N: usize> {
+    pub scale: [f32; N],
+    pub offset: [f32; N],
+}
+
+impl<const N: usize> Transform<N> {
+    const IDENTITY: Self = Self {
+        scale: [1.; N],
+        offset: [0.; N],
+    };
+}
+
+/// Quantize signed values, centered on the bounding box (positions)
+fn quantize_signed<const N: usize>(
+    values: &[[f32; N]],
+    format: Format,
+) -> (Vec<[i32; N]>, Transform<N>) {
+    match format {
+        Format::I8 => quantize::<i8, N>(values),
+        Format::I16 => quantize::<i16, N>(values),
+        Format::F32 => (Vec::new(), Transform::IDENTITY),
+    }
+}
+
+/// Quantize unsigned values, starting at the minimum of the bounding box (texture coordinates)
+fn quantize_unsigned<const N: usize>(
+    values: &[[f32; N]],
+    format: Format,
+) -> (Vec<[i32; N]>, Transform<N>) {
+    match format {
+        Format::I8 => quantize::<u8, N>(values),
+        Format::I16 => quantize::<u16, N>(values),
+        Format::F32 => (Vec::new(), Transform::IDENTITY),
+    }
+}
+
+fn quantize<T: Fixed + Into<i32>, const N: usize>(
+    values: &[[f32; N]],
+) -> (Vec<[i32; N]>, Transform<N>) {
+    let quantizer = Quantizer::<T, N>::from_values(values.iter().copied());
+    let quantized = values
+        .iter()
+        .map(|&value| quantizer.quantize(value).map(Into::into))
+        .collect();
+    let transform = Transform {
+        scale: quantizer.scale(),
+        offset: quantizer.offset(),
+    };
+    (quantized, transform)
+}
+
+fn pack_color([r, g, b, a]: [f32; 4]) -> [u8; 4] {
+    ColorF32::new(r, g, b, a).to_srgb().to_array()
+}
+
+fn write_component(dst: &mut [u8], format: Format, quantized: i32, float: f32) {
+    match format {
+        Format::I8 => dst[0] = quantized as u8,
+        Format::I16 => dst[..2].copy_from_slice(&(quantized as u16).to_le_bytes()),
+        Format::F32 => dst[..4].copy_from_slice(&float.to_le_bytes()),
+    }
+}
+
+/// Serialize a mesh into the model format, quantizing attributes to the layout
+///
+/// Attributes enabled in the layout but missing from the mesh are filled with defaults.
+pub fn write_model(mesh: &MeshData, layout: &VertexLayout) -> Result<Vec<u8>, String> {
+    let vertex_count = mesh.positions.len();
+    if vertex_count > u16::MAX as usize + 1 {
+        return Err(format!(
+            "too many vertices ({vertex_count}), at most 65536 are supported"
+        ));
+    }
+    let uvs = if mesh.uvs.is_empty() {
+        vec![[0.; 2]; vertex_count]
+    } else {
+        mesh.uvs.clone()
+    };
+
+    let (positions, position_transform) = quantize_signed(&mesh.positions, layout.position);
+    let (quantized_uvs, uv_transform) = match layout.uv {
+        Some(format) => quantize_unsigned(&uvs, format),
+        None => (Vec::new(), Transform::IDENTITY),
+    };
+
+    let (offsets, stride) = layout.offsets();
+    let mut vertices = vec![0u8; vertex_count * stride];
+    for (idx, vertex) in vertices.chunks_mut(stride).enumerate() {
+        if let Some(format) = layout.uv {
+            for component in 0..2 {
+                let offset = offsets[0] + component * format.size();
+                let quantized = quantized_uvs.get(idx).map_or(0, |uv| uv[component]);
+                write_component(
+                    &mut vertex[offset..],
+                    format,
+                    quantized,
+                    uvs[idx][component],
+                );
+            }
+        }
+        if layout.color {
+            let color = mesh.colors.get(idx).copied().unwrap_or([1.; 4]);
+            vertex[offsets[1]..offsets[1] + 4].copy_from_slice(&pack_color(color));
+        }
+        if let Some(format) = layout.normal {
+            let normal = mesh.normals.get(idx).copied().unwrap_or([0.; 3]);
+            let quantized: [i32; 3] = match format {
+                Format::I8 => quantize_normal::<i8>(normal).map(Into::into),
+                _ => quantize_normal::<i16>(normal).map(Into::into),
+            };
+            for component in 0..3 {
+                let offset = offsets[2] + component * format.size();
+                let float = normal[component];
+                write_component(&mut vertex[offset..], format, quantized[component], float);
+            }
+        }
+        for component in 0..3 {
+            let offset = offsets[3] + component * layout.position.size();
+            let quantized = positions.get(idx).map_or(0, |position| position[component]);
+            let float = mesh.positions[idx][component];
+            write_component(&mut vertex[offset..], layout.position, quantized, float);
+        }
+    }
+
+    let mut textures: Vec<&str> = Vec::new();
+    let mut out = Vec::new();
+    let u32 = |out: &mut Vec<u8>, value: u32| out.extend_from_slice(&value.to_le_bytes());
+    let f32 = |out: &mut Vec<u8>, value: f32| out.extend_from_slice(&value.to_le_bytes());
+
+    let mut submeshes = Vec::with_capacity(mesh.submeshes.len());
+    for submesh in &mesh.submeshes {
+        let material = mesh
+            .materials
+            .get(submesh.material)
+            .ok_or("submesh material out of range")?;
+        let texture = match &material.texture {
+            Some(name) => match textures.iter().position(|texture| texture == name) {
+                Some(idx) => idx as i32,
+                None => {
+                    textures.push(name);
+                    textures.len() as i32 - 1
+                }
+            },
+            None => -1,
+        };
+        submeshes.push((
+            submesh.start,
+            submesh.count,
+            pack_color(material.color),
+            texture,
+        ));
+    }
+
+    out.extend_from_slice(MAGIC);
+    u32(&mut out, VERSION);
+    u32(&mut out, layout.vertex_type());
+    u32(&mut out, stride as u32);
+    u32(&mut out, PRIMITIVE_TRIANGLES);
+    u32(&mut out, vertex_count as u32);
+    u32(&mut out, mesh.indices.len() as u32);
+    u32(&mut out, submeshes.len() as u32);
+    u32(&mut out, textures.len() as u32);
+    for value in position_transform
+        .scale
+        .into_iter()
+        .chain(position_transform.offset)
+    {
+        f32(&mut out, value);
+    }
+    for value in uv_transform.scale.into_iter().chain(uv_transform.offset) {
+        f32(&mut out, value);
+    }
+    for texture in &textures {
+        let len = u16::try_from(texture.len()).map_err(|_| "texture file name too long")?;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(texture.as_bytes());
+    }
+    for (start, count, color, texture) in submeshes {
+        u32(&mut out, start as u32);
+        u32(&mut out, count as u32);
+        out.extend_from_slice(&color);
+        u32(&mut out, texture as u32);
+    }
+    // The vertex data is padded, not the file offset, which depends on the texture names
+    let padding = vertices.len().next_multiple_of(4) - vertices.len();
+    out.extend_from_slice(&vertices);
+    out.resize(out.len() + padding, 0);
+    for &index in &mesh.indices {
+        out.extend_from_slice(&(index as u16).to_le_bytes());
+    }
+    Ok(out)
+}
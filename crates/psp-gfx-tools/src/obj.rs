@@ -0,0 +1,185 @@
+//! Wavefront OBJ importer
+//!
+//! Supports `v`, `vt`, `vn`, `f` (polygons are triangulated as fans), `usemtl` and `mtllib`
+//! with the `Kd`, `d` and `map_Kd` material statements. Texture coordinates are flipped
+//! vertically, as OBJ uses a bottom-left origin.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::scene::{MaterialData, MeshData, SubmeshData};
+
+/// Parse an OBJ file, `load_file` is used to read material libraries relative to the model
+pub fn parse(text: &str, load_file: impl Fn(&str) -> Option<String>) -> Result<MeshData, String> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut library = BTreeMap::new();
+
+    let mut mesh = MeshData::default();
+    let mut vertex_map = HashMap::new();
+    let mut has_uvs = false;
+    let mut has_normals = false;
+    let mut material_ids = BTreeMap::new();
+    let mut current_material = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {message}", line_idx + 1);
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(tag) = tokens.next() else {
+            continue;
+        };
+        let mut floats = || -> Result<Vec<f32>, String> {
+            tokens
+                .by_ref()
+                .map(|token| token.parse().map_err(|_| error("invalid number")))
+                .collect()
+        };
+        match tag {
+            "v" => {
+                let values = floats()?;
+                if values.len() < 3 {
+                    return Err(error("expected 3 coordinates"));
+                }
+                positions.push([values[0], values[1], values[2]]);
+            }
+            "vt" => {
+                let values = floats()?;
+                let u = values.first().copied().unwrap_or_default();
+                let v = values.get(1).copied().unwrap_or_default();
+                uvs.push([u, 1. - v]);
+            }
+            "vn" => {
+                let values = floats()?;
+                if values.len() < 3 {
+                    return Err(error("expected 3 coordinates"));
+                }
+                normals.push([values[0], values[1], values[2]]);
+            }
+            "mtllib" => {
+                let name = line.trim_start()[tag.len()..].trim();
+                let text =
+                    load_file(name).ok_or_else(|| error("failed to read material library"))?;
+                parse_mtl(&text, &mut library);
+            }
+            "usemtl" => {
+                let name = line.trim_start()[tag.len()..].trim().to_owned();
+                let next_id = material_ids.len();
+                let id = *material_ids.entry(name.clone()).or_insert(next_id);
+                if id == mesh.materials.len() {
+                    mesh.materials
+                        .push(library.get(&name).cloned().unwrap_or_default());
+                }
+                current_material = Some(id);
+            }
+            "f" => {
+                let mut face = Vec::new();
+                for token in tokens.by_ref() {
+                    let mut parts = token.split('/');
+                    let mut index = |len: usize| -> Result<Option<usize>, String> {
+                        match parts.next() {
+                            None | Some("") => Ok(None),
+                            Some(part) => {
+                                let index: isize =
+                                    part.parse().map_err(|_| error("invalid index"))?;
+                                let resolved = if index < 0 {
+                                    len as isize + index
+                                } else {
+                                    index - 1
+                                };
+                                if resolved < 0 || resolved as usize >= len {
+                                    return Err(error("index out of range"));
+                                }
+                                Ok(Some(resolved as usize))
+                            }
+                        }
+                    };
+                    let key = (
+                        index(positions.len())?.ok_or_else(|| error("missing position index"))?,
+                        index(uvs.len())?,
+                        index(normals.len())?,
+                    );
+                    has_uvs |= key.1.is_some();
+                    has_normals |= key.2.is_some();
+                    let vertex = *vertex_map.entry(key).or_insert_with(|| {
+                        mesh.positions.push(positions[key.0]);
+                        mesh.uvs.push(key.1.map_or([0.; 2], |idx| uvs[idx]));
+                        mesh.normals.push(key.2.map_or([0.; 3], |idx| normals[idx]));
+                        mesh.positions.len() as u32 - 1
+                    });
+                    face.push(vertex);
+                }
+                if face.len() < 3 {
+                    return Err(error("face with less than 3 vertices"));
+                }
+
+                let material = match current_material {
+                    Some(material) => material,
+                    None => {
+                        // Faces before the first `usemtl` use a default material
+                        let id = material_ids.len();
+                        material_ids.insert(String::new(), id);
+                        mesh.materials.push(MaterialData::default());
+                        current_material = Some(id);
+                        id
+                    }
+                };
+                match mesh.submeshes.last_mut() {
+                    Some(submesh) if submesh.material == material => (),
+                    _ => mesh.submeshes.push(SubmeshData {
+                        start: mesh.indices.len(),
+                        count: 0,
+                        material,
+                    }),
+                }
+                for idx in 1..(face.len() - 1) {
+                    mesh.indices
+                        .extend_from_slice(&[face[0], face[idx], face[idx + 1]]);
+                }
+                let submesh = mesh.submeshes.last_mut().unwrap();
+                submesh.count = mesh.indices.len() - submesh.start;
+            }
+            _ => (),
+        }
+    }
+
+    if !has_uvs {
+        mesh.uvs.clear();
+    }
+    if !has_normals {
+        mesh.normals.clear();
+    }
+    Ok(mesh)
+}
+
+/// Parse a material library, adding materials to `library`
+fn parse_mtl(text: &str, library: &mut BTreeMap<String, MaterialData>) {
+    let mut current = None;
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((tag, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let rest = rest.trim();
+        if tag == "newmtl" {
+            library.insert(rest.to_owned(), MaterialData::default());
+            current = Some(rest.to_owned());
+            continue;
+        }
+        let Some(material) = current.as_ref().and_then(|name| library.get_mut(name)) else {
+            continue;
+        };
+        let floats: Vec<f32> = rest
+            .split_whitespace()
+            .filter_map(|token| token.parse().ok())
+            .collect();
+        match tag {
+            "Kd" if floats.len() >= 3 => material.color[..3].copy_from_slice(&floats[..3]),
+            "d" if !floats.is_empty() => material.color[3] = floats[0],
+            "Tr" if !floats.is_empty() => material.color[3] = 1. - floats[0],
+            // Options (`-o`, `-s`, ...) are not supported, the file name is the last token
+            "map_Kd" => material.texture = rest.split_whitespace().last().map(str::to_owned),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Format independent mesh data produced by the importers
+
+/// Material of a submesh
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialData {
+    /// Linear RGBA base color
+    pub color: [f32; 4],
+    /// Texture file name, relative to the model file
+    pub texture: Option<String>,
+}
+
+impl Default for MaterialData {
+    fn default() -> Self {
+        Self {
+            color: [1.; 4],
+            texture: None,
+        }
+    }
+}
+
+/// Range of indices drawn with a single material
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmeshData {
+    pub start: usize,
+    pub count: usize,
+    pub material: usize,
+}
+
+/// Indexed triangle list with optional per-vertex attributes
+///
+/// Attribute vectors are either empty or have one entry per position.
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub normals: Vec<[f32; 3]>,
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+    pub submeshes: Vec<SubmeshData>,
+    pub materials: Vec<MaterialData>,
+}
+
+impl MeshData {
+    /// Append the triangles of `other`, keeping its submeshes and materials separate
+    pub fn append(&mut self, other: MeshData) {
+        let base_vertex = self.positions.len();
+        let base_index = self.indices.len();
+        let base_material = self.materials.len();
+        let count = other.positions.len();
+        merge_attribute(&mut self.uvs, other.uvs, base_vertex, count, [0.; 2]);
+        merge_attribute(
+            &mut self.normals,
+            other.normals,
+            base_vertex,
+            count,
+            [0.; 3],
+        );
+        merge_attribute(&mut self.colors, other.colors, base_vertex, count, [1.; 4]);
+        self.positions.extend(other.positions);
+        self.indices
+            .extend(other.indices.iter().map(|index| index + base_vertex as u32));
+        self.submeshes
+            .extend(other.submeshes.into_iter().map(|submesh| SubmeshData {
+                start: submesh.start + base_index,
+                count: submesh.count,
+                material: submesh.material + base_material,
+            }));
+        self.materials.extend(other.materials);
+    }
+}
+
+/// Append an optional attribute, filling in defaults when only one side has it
+fn merge_attribute<T: Copy>(
+    dst: &mut Vec<T>,
+    src: Vec<T>,
+    dst_len: usize,
+    src_len: usize,
+    default: T,
+) {
+    if dst.is_empty() && src.is_empty() {
+        return;
+    }
+    dst.resize(dst_len, default);
+    if src.is_empty() {
+        dst.resize(dst_len + src_len, default);
+    } else {
+        dst.extend(src);
+    }
+}
@@ -0,0 +1,36 @@
+//! Text layout and BMFont descriptor parsing, shared with `psp_gfx::text`
+
+#[path = "../../psp-gfx/src/text/bmfont/file.rs"]
+pub mod bmfont;
+#[path = "../../psp-gfx/src/text/layout.rs"]
+mod layout;
+
+pub use layout::*;
+
+/// Font metrics, mirror of `psp_gfx::text::Font` without the atlas pages
+pub trait Font {
+    /// Distance between two consecutive lines in pixels
+    fn line_height(&self) -> i32;
+
+    /// Get a glyph for the specified character
+    fn glyph(&self, ch: char) -> Option<Glyph>;
+
+    /// Get horizontal adjustment applied between two consecutive characters
+    fn kerning(&self, _left: char, _right: char) -> i32 {
+        0
+    }
+}
+
+impl Font for bmfont::BmFontFile {
+    fn line_height(&self) -> i32 {
+        self.line_height
+    }
+
+    fn glyph(&self, ch: char) -> Option<Glyph> {
+        self.glyph(ch)
+    }
+
+    fn kerning(&self, left: char, right: char) -> i32 {
+        self.kerning(left, right)
+    }
+}
@@ -0,0 +1,12 @@
+//! Texture layout, DXT compression and mip level generation, shared with
+//! `psp_gfx::texture`
+
+#[path = "../../psp-gfx/src/texture/dxt.rs"]
+pub mod dxt;
+#[path = "../../psp-gfx/src/texture/layout.rs"]
+mod layout;
+#[path = "../../psp-gfx/src/texture/mipmap.rs"]
+pub mod mipmap;
+
+pub use layout::*;
+pub use mipmap::MipmapFilter;
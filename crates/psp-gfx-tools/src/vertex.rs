@@ -0,0 +1,9 @@
+//! Mirror of the `psp_gfx::vertex::Vertex` trait used by [`clip`](crate::clip)
+//!
+//! The `define_vertex_layout!` macro is PSP only, tests implement the trait by hand.
+
+use crate::ge::sys::VertexType;
+
+pub trait Vertex {
+    fn vtype() -> VertexType;
+}
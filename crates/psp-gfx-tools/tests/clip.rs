@@ -0,0 +1,214 @@
+//! Triangle clipping shared with `psp_gfx::clip`
+
+use psp_gfx_tools::{
+    clip::{ClipVertex, GuardBand, clip_triangles},
+    ge::sys::{GuPrimitive, ScePspFMatrix4, ScePspFVector4, VertexType},
+    vertex::Vertex,
+};
+
+/// Vertex with a texture coordinate, to check interpolation
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TexturedVertex {
+    u: f32,
+    position: [f32; 3],
+}
+
+impl Vertex for TexturedVertex {
+    fn vtype() -> VertexType {
+        VertexType::TEXTURE_32BITF | VertexType::VERTEX_32BITF | VertexType::TRANSFORM_3D
+    }
+}
+
+impl ClipVertex for TexturedVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            u: self.u + (other.u - self.u) * t,
+            position: core::array::from_fn(|idx| {
+                self.position[idx] + (other.position[idx] - self.position[idx]) * t
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    position: [f32; 3],
+}
+
+impl Vertex for ScreenVertex {
+    fn vtype() -> VertexType {
+        VertexType::VERTEX_32BITF | VertexType::TRANSFORM_2D
+    }
+}
+
+impl ClipVertex for ScreenVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+
+    fn lerp(&self, _other: &Self, _t: f32) -> Self {
+        *self
+    }
+}
+
+fn vertex(u: f32, x: f32, y: f32, z: f32) -> TexturedVertex {
+    TexturedVertex {
+        u,
+        position: [x, y, z],
+    }
+}
+
+/// Clip space is model space, with `w = 1`
+fn identity() -> ScePspFMatrix4 {
+    let column = |x, y, z, w| ScePspFVector4 { x, y, z, w };
+    ScePspFMatrix4 {
+        x: column(1., 0., 0., 0.),
+        y: column(0., 1., 0., 0.),
+        z: column(0., 0., 1., 0.),
+        w: column(0., 0., 0., 1.),
+    }
+}
+
+/// Guard band at `-2..=2` in both directions
+const GUARD_BAND: GuardBand = GuardBand { x: 2., y: 2. };
+
+fn clip(primitive: GuPrimitive, vertices: &[TexturedVertex]) -> Vec<TexturedVertex> {
+    clip_triangles(primitive, vertices, &identity(), GUARD_BAND)
+}
+
+#[test]
+fn fully_inside() {
+    let triangle = [
+        vertex(0., 0., 0., 0.),
+        vertex(1., 1., 0., 0.),
+        vertex(2., 0., 1., 0.),
+    ];
+    assert_eq!(clip(GuPrimitive::Triangles, &triangle), triangle);
+    // On the planes is inside
+    let edge = [
+        vertex(0., -2., -2., -1.),
+        vertex(1., 2., -2., -1.),
+        vertex(2., 2., 2., -1.),
+    ];
+    assert_eq!(clip(GuPrimitive::Triangles, &edge), edge);
+}
+
+#[test]
+fn fully_outside() {
+    // Right of the guard band, behind the near plane, and across a corner
+    for triangle in [
+        [
+            vertex(0., 3., 0., 0.),
+            vertex(1., 4., 0., 0.),
+            vertex(2., 3., 1., 0.),
+        ],
+        [
+            vertex(0., 0., 0., -2.),
+            vertex(1., 1., 0., -2.),
+            vertex(2., 0., 1., -3.),
+        ],
+        [
+            vertex(0., -4., 1., 0.),
+            vertex(1., 1., 6., 0.),
+            vertex(2., -4., 6., 0.),
+        ],
+    ] {
+        assert!(clip(GuPrimitive::Triangles, &triangle).is_empty());
+    }
+}
+
+#[test]
+fn one_vertex_out() {
+    // The second vertex is right of the guard band, the triangle becomes a quad
+    let triangle = [
+        vertex(0., 0., 0., 0.),
+        vertex(4., 4., 0., 0.),
+        vertex(2., 0., 1., 0.),
+    ];
+    let p1 = vertex(2., 2., 0., 0.);
+    let p2 = vertex(3., 2., 0.5, 0.);
+    assert_eq!(
+        clip(GuPrimitive::Triangles, &triangle),
+        [triangle[0], p1, p2, triangle[0], p2, triangle[2]]
+    );
+}
+
+#[test]
+fn two_vertices_out() {
+    // Both vertices are below the guard band, the triangle stays a triangle
+    let triangle = [
+        vertex(0., 0., 0., 0.),
+        vertex(4., 0., -4., 0.),
+        vertex(8., 1., -4., 0.),
+    ];
+    assert_eq!(
+        clip(GuPrimitive::Triangles, &triangle),
+        [
+            triangle[0],
+            vertex(2., 0., -2., 0.),
+            vertex(4., 0.5, -2., 0.)
+        ]
+    );
+}
+
+#[test]
+fn near_plane() {
+    // The second vertex is behind the near plane (z < -w)
+    let triangle = [
+        vertex(0., 0., 0., 0.),
+        vertex(4., 1., 0., -2.),
+        vertex(8., 0., 1., 0.),
+    ];
+    assert_eq!(
+        clip(GuPrimitive::Triangles, &triangle),
+        [
+            triangle[0],
+            vertex(2., 0.5, 0., -1.),
+            vertex(6., 0.5, 0.5, -1.),
+            triangle[0],
+            vertex(6., 0.5, 0.5, -1.),
+            triangle[2]
+        ]
+    );
+}
+
+#[test]
+fn strips_and_fans() {
+    let quad = [
+        vertex(0., 0., 0., 0.),
+        vertex(1., 1., 0., 0.),
+        vertex(2., 0., 1., 0.),
+        vertex(3., 1., 1., 0.),
+    ];
+    let [a, b, c, d] = quad;
+    // Odd triangles of strips are flipped to keep the winding of the first
+    assert_eq!(clip(GuPrimitive::TriangleStrip, &quad), [a, b, c, c, b, d]);
+    assert_eq!(clip(GuPrimitive::TriangleFan, &quad), [a, b, c, a, c, d]);
+    // An incomplete triangle is dropped
+    assert_eq!(clip(GuPrimitive::Triangles, &quad), [a, b, c]);
+}
+
+#[test]
+#[should_panic(expected = "only TRANSFORM_3D vertices")]
+fn screen_space_vertices() {
+    let vertices = [ScreenVertex { position: [0.; 3] }; 3];
+    clip_triangles(GuPrimitive::Triangles, &vertices, &identity(), GUARD_BAND);
+}
+
+#[test]
+#[should_panic(expected = "only triangle primitives")]
+fn lines() {
+    clip(GuPrimitive::Lines, &[vertex(0., 0., 0., 0.); 2]);
+}
+
+#[test]
+fn default_guard_band() {
+    // 2047 pixels from the center of the 480x272 viewport
+    assert_eq!(GuardBand::DEFAULT, GuardBand::for_viewport(480, 272));
+    assert!((GuardBand::DEFAULT.x * 240. - 2047.).abs() < 1e-3);
+    assert!((GuardBand::DEFAULT.y * 136. - 2047.).abs() < 1e-3);
+}
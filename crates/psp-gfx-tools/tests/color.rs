@@ -0,0 +1,280 @@
+//! Color conversions shared with `psp_gfx::color`
+
+use psp_gfx_tools::color::{
+    Color32, Color4444, Color5551, Color5650, ColorF32, linear_to_srgb, srgb_to_linear,
+};
+
+/// Nearest `bits` wide level of an 8-bit value
+fn nearest_level(value: u8, bits: u32) -> u16 {
+    let max = ((1 << bits) - 1) as f32;
+    (value as f32 * max / 255.).round() as u16
+}
+
+/// Bit width and shift of each channel, and conversions of a packed format
+struct Format {
+    channels: [(u32, u32); 4],
+    from_color32: fn(Color32) -> u16,
+    to_color32: fn(u16) -> Color32,
+}
+
+const FORMATS: [Format; 3] = [
+    Format {
+        channels: [(5, 0), (6, 5), (5, 11), (0, 0)],
+        from_color32: |color| Color5650::from_color32(color).to_bits(),
+        to_color32: |bits| Color5650::from_bits(bits).to_color32(),
+    },
+    Format {
+        channels: [(5, 0), (5, 5), (5, 10), (1, 15)],
+        from_color32: |color| Color5551::from_color32(color).to_bits(),
+        to_color32: |bits| Color5551::from_bits(bits).to_color32(),
+    },
+    Format {
+        channels: [(4, 0), (4, 4), (4, 8), (4, 12)],
+        from_color32: |color| Color4444::from_color32(color).to_bits(),
+        to_color32: |bits| Color4444::from_bits(bits).to_color32(),
+    },
+];
+
+#[test]
+fn quantize_rounds_to_nearest() {
+    for (idx, format) in FORMATS.iter().enumerate() {
+        for value in 0..=255u8 {
+            let bits = (format.from_color32)(Color32::new(value, value, value, value));
+            for (width, shift) in format.channels.into_iter().filter(|(width, _)| *width > 0) {
+                let level = (bits >> shift) & ((1 << width) - 1);
+                assert_eq!(
+                    level,
+                    nearest_level(value, width),
+                    "format {idx}, value {value}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn expand_round_trip() {
+    for (idx, format) in FORMATS.iter().enumerate() {
+        let used_bits: u16 = format
+            .channels
+            .iter()
+            .map(|&(width, shift)| ((1 << width) - 1) << shift)
+            .sum();
+        for bits in 0..=u16::MAX {
+            let bits = bits & used_bits;
+            let color = (format.to_color32)(bits);
+            // Expanded levels are spread evenly over 0..=255, and quantize back losslessly
+            for ((width, shift), value) in format.channels.into_iter().zip(color.to_array()) {
+                if width > 0 {
+                    let level = (bits >> shift) & ((1 << width) - 1);
+                    let max = ((1 << width) - 1) as f32;
+                    assert_eq!(value, (level as f32 * 255. / max).round() as u8);
+                }
+            }
+            assert_eq!((format.from_color32)(color), bits, "format {idx}");
+        }
+    }
+}
+
+#[test]
+fn channel_ends() {
+    let white = Color32::new(255, 255, 255, 255);
+    let black = Color32::new(0, 0, 0, 0);
+    for format in &FORMATS {
+        let max: u16 = format
+            .channels
+            .iter()
+            .map(|&(width, shift)| ((1 << width) - 1) << shift)
+            .sum();
+        assert_eq!((format.from_color32)(white), max);
+        assert_eq!((format.from_color32)(black), 0);
+        assert_eq!((format.to_color32)(max), white);
+    }
+    // Formats without alpha are opaque
+    assert_eq!(
+        Color5650::from_bits(0).to_color32(),
+        Color32::new(0, 0, 0, 255)
+    );
+    assert_eq!(Color5551::from_bits(0).to_color32(), black);
+    assert_eq!(Color4444::from_bits(0).to_color32(), black);
+
+    // The first and last steps round towards the ends
+    let red = |value| Color5650::from_color32(Color32::rgb(value, 0, 0)).to_bits();
+    assert_eq!([red(4), red(5), red(250), red(251)], [0, 1, 30, 31]);
+    let alpha = |value| Color5551::from_color32(Color32::new(0, 0, 0, value)).a();
+    assert_eq!([alpha(127), alpha(128)], [0, 255]);
+}
+
+/// Colors with every channel in steps of 17, including 0 and 255
+fn color_grid() -> impl Iterator<Item = Color32> {
+    (0..16u8).flat_map(|r| {
+        (0..16u8).flat_map(move |g| (0..16u8).map(move |b| Color32::rgb(r * 17, g * 17, b * 17)))
+    })
+}
+
+#[test]
+fn hsv_round_trip() {
+    for color in color_grid() {
+        let (h, s, v) = color.to_hsv();
+        assert!((0. ..360.).contains(&h), "{color:?}: {h}");
+        assert!((0. ..=1.).contains(&s) && (0. ..=1.).contains(&v));
+        assert_eq!(Color32::from_hsv(h, s, v), color);
+    }
+}
+
+#[test]
+fn hsl_round_trip() {
+    for color in color_grid() {
+        let (h, s, l) = color.to_hsl();
+        assert!((0. ..360.).contains(&h), "{color:?}: {h}");
+        assert!(
+            (0. ..=1.).contains(&s) && (0. ..=1.).contains(&l),
+            "{color:?}: {s} {l}"
+        );
+        assert_eq!(Color32::from_hsl(h, s, l), color);
+    }
+}
+
+#[test]
+fn hue_wraps_around() {
+    let red = Color32::rgb(255, 0, 0);
+    let green = Color32::rgb(0, 255, 0);
+    let blue = Color32::rgb(0, 0, 255);
+    for (h, color) in [
+        (0., red),
+        (120., green),
+        (240., blue),
+        (360., red),
+        (480., green),
+        (-120., blue),
+        (-360., red),
+    ] {
+        assert_eq!(Color32::from_hsv(h, 1., 1.), color, "{h}");
+        assert_eq!(Color32::from_hsl(h, 1., 0.5), color, "{h}");
+    }
+    // Just below 360 is still red with a little blue
+    assert_eq!(Color32::from_hsv(359.9, 1., 1.), Color32::rgb(255, 0, 0));
+    assert_eq!(Color32::from_hsv(345., 1., 1.), Color32::rgb(255, 0, 64));
+    let (h, _, _) = Color32::rgb(255, 0, 1).to_hsv();
+    assert!(h > 359. && h < 360.);
+
+    // Grays have no hue
+    assert_eq!(Color32::rgb(128, 128, 128).to_hsv(), (0., 0., 128. / 255.));
+    assert_eq!(
+        Color32::from_hsl(200., 0., 0.5),
+        Color32::rgb(128, 128, 128)
+    );
+    // Saturation and value are clamped
+    assert_eq!(Color32::from_hsv(0., 2., 2.), red);
+}
+
+/// Threshold of the linear segment of the sRGB curve, encoded and linear
+const SRGB_THRESHOLD: (f32, f32) = (0.04045, 0.04045 / 12.92);
+
+#[test]
+fn srgb_curve_ends() {
+    assert_eq!(srgb_to_linear(0.), 0.);
+    assert_eq!(linear_to_srgb(0.), 0.);
+    assert!((srgb_to_linear(1.) - 1.).abs() < 1e-6);
+    assert!((linear_to_srgb(1.) - 1.).abs() < 1e-6);
+
+    // Both segments meet at the threshold
+    let (encoded, linear) = SRGB_THRESHOLD;
+    assert!((srgb_to_linear(encoded) - linear).abs() < 1e-6);
+    assert!((srgb_to_linear(encoded + 1e-6) - linear).abs() < 1e-6);
+    assert!((linear_to_srgb(linear) - encoded).abs() < 1e-5);
+    assert!((linear_to_srgb(linear + 1e-7) - encoded).abs() < 1e-5);
+}
+
+#[test]
+fn srgb_round_trip() {
+    let (encoded, linear) = SRGB_THRESHOLD;
+    for value in [0., 1., encoded, linear, 0.5, 0.001] {
+        assert!(
+            (linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5,
+            "{value}"
+        );
+        assert!(
+            (srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5,
+            "{value}"
+        );
+    }
+
+    // Every 8-bit value survives decoding and encoding, alpha is linear
+    for value in 0..=255 {
+        let color = Color32::new(value, value, value, value);
+        let linear = ColorF32::from_srgb(color);
+        assert_eq!(linear.a, value as f32 / 255.);
+        assert_eq!(linear.to_srgb(), color);
+        assert_eq!(
+            ColorF32::from_color32_linear(color).to_color32_linear(),
+            color
+        );
+    }
+    assert_eq!(
+        ColorF32::from_srgb(Color32::new(0, 0, 0, 0)),
+        ColorF32::TRANSPARENT
+    );
+    assert_eq!(
+        ColorF32::from_srgb(Color32::new(255, 255, 255, 255)),
+        ColorF32::WHITE
+    );
+    // Out of range components are clamped when encoding
+    assert_eq!(
+        ColorF32::new(-1., 2., 0.5, 1.5).to_srgb(),
+        Color32::new(0, 255, 188, 255)
+    );
+}
+
+/// Values from 0 to 64 in small steps, then a few very large ones
+fn hdr_values() -> impl Iterator<Item = f32> {
+    (0..=6400)
+        .map(|step| step as f32 / 100.)
+        .chain([1e3, 1e6, f32::MAX])
+}
+
+#[test]
+fn tone_mapping() {
+    for (name, operator) in [
+        (
+            "reinhard",
+            ColorF32::tonemap_reinhard as fn(ColorF32) -> ColorF32,
+        ),
+        ("aces", ColorF32::tonemap_aces),
+    ] {
+        assert_eq!(operator(ColorF32::BLACK), ColorF32::BLACK, "{name}");
+        for stops in -4..=4 {
+            let mut previous = 0.;
+            for value in hdr_values() {
+                let color = ColorF32::new(value, value / 2., 0., 0.25).exposure(stops as f32);
+                let mapped = operator(color);
+                for component in [mapped.r, mapped.g, mapped.b] {
+                    assert!(
+                        (0. ..=1.).contains(&component),
+                        "{name}: {value} at {stops} stops is {component}"
+                    );
+                }
+                // Monotonic in the input, alpha is kept
+                assert!(mapped.r >= previous, "{name}: {value} at {stops} stops");
+                assert!(mapped.g <= mapped.r);
+                assert_eq!(mapped.a, 0.25);
+                previous = mapped.r;
+            }
+        }
+    }
+}
+
+#[test]
+fn exposure() {
+    let color = ColorF32::new(0.25, 0.5, 1., 0.5);
+    assert_eq!(color.exposure(0.), color);
+    assert_eq!(color.exposure(1.), ColorF32::new(0.5, 1., 2., 0.5));
+    assert_eq!(color.exposure(-2.), ColorF32::new(0.0625, 0.125, 0.25, 0.5));
+    // Monotonic in the stops
+    let mut previous = 0.;
+    for stops in -40..=40 {
+        let exposed = color.exposure(stops as f32 / 4.).tonemap_reinhard();
+        assert!(exposed.r > previous);
+        previous = exposed.r;
+    }
+}
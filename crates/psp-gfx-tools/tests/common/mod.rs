@@ -0,0 +1,23 @@
+//! Helpers shared by the display list tests
+//!
+//! Every test file only uses some of them.
+#![allow(dead_code)]
+
+use psp_gfx_tools::ge::Encoder;
+
+/// Encode commands into a `Vec<u32>`
+pub fn encode(f: impl FnOnce(&mut Encoder<Vec<u32>>)) -> Vec<u32> {
+    let mut encoder = Encoder::new(Vec::new());
+    f(&mut encoder);
+    encoder.into_inner()
+}
+
+/// Command word with a raw 24-bit argument
+pub fn word(opcode: u32, argument: u32) -> u32 {
+    (opcode << 24) | (argument & 0xffffff)
+}
+
+/// Command word with a float argument
+pub fn float(opcode: u32, value: f32) -> u32 {
+    word(opcode, value.to_bits() >> 8)
+}
@@ -0,0 +1,222 @@
+//! Display list disassembler shared with `psp_gfx::ge::disasm`
+
+mod common;
+
+use common::{encode, word};
+use psp_gfx_tools::{
+    disasm::{Line, disasm, disasm_at, vertex_type},
+    ge::sys::{GuPrimitive, MatrixMode, ScePspFMatrix4, ScePspFVector4, VertexType},
+};
+
+fn texts(words: &[u32]) -> Vec<String> {
+    disasm(words).map(|line| line.text).collect()
+}
+
+fn matrix() -> ScePspFMatrix4 {
+    let row = |base: f32| ScePspFVector4 {
+        x: base,
+        y: base + 1.,
+        z: base + 2.,
+        w: base + 3.,
+    };
+    ScePspFMatrix4 {
+        x: row(0.),
+        y: row(4.),
+        z: row(8.),
+        w: row(12.),
+    }
+}
+
+#[test]
+fn matrix_grouping() {
+    let words = encode(|ge| {
+        ge.matrix(MatrixMode::View, &matrix())
+            .matrix(MatrixMode::Projection, &matrix());
+    });
+    let lines: Vec<Line> = disasm(&words).collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0].text, "VMS 0");
+    // 4 rows of 3 values, the 4x3 part of the matrix
+    assert_eq!(lines[1].text, "VIEW [0 1 2] [4 5 6] [8 9 10] [12 13 14]");
+    assert_eq!((lines[1].offset, lines[1].len), (4, 12));
+    assert_eq!(lines[2].text, "PMS 0");
+    // The projection matrix has 4 columns
+    assert_eq!(
+        lines[3].text,
+        "PROJ [0 1 2 3] [4 5 6 7] [8 9 10 11] [12 13 14 15]"
+    );
+    assert_eq!((lines[3].offset, lines[3].len), (13 * 4 + 4, 16));
+    assert_eq!(
+        lines.iter().map(|line| line.len).sum::<usize>(),
+        words.len()
+    );
+}
+
+#[test]
+fn matrix_grouping_limits() {
+    let data = |value: f32| word(0x3b, value.to_bits() >> 8);
+    // A full matrix per line, then a partial upload ended by another command
+    let mut words: Vec<u32> = (0..17).map(|idx| data(idx as f32)).collect();
+    words.push(word(0x00, 0));
+    assert_eq!(
+        texts(&words),
+        [
+            "WORLD [0 1 2] [3 4 5] [6 7 8] [9 10 11]",
+            "WORLD [12 13 14] [15 16]",
+            "NOP",
+        ]
+    );
+    // Different matrices aren't merged
+    assert_eq!(
+        texts(&[data(1.), word(0x3d, 2f32.to_bits() >> 8)]),
+        ["WORLD [1]", "VIEW [2]"]
+    );
+}
+
+#[test]
+fn vertex_type_decoding() {
+    let vtype = VertexType::TEXTURE_32BITF
+        | VertexType::COLOR_8888
+        | VertexType::VERTEX_32BITF
+        | VertexType::TRANSFORM_2D;
+    let words = encode(|ge| {
+        ge.vertex_type(vtype);
+    });
+    assert_eq!(
+        texts(&words),
+        ["VTYPE uv=f32 color=8888 normal=none pos=f32 weight=none index=none 2d"]
+    );
+    assert_eq!(
+        vertex_type(
+            (VertexType::TEXTURE_8BIT
+                | VertexType::COLOR_5650
+                | VertexType::NORMAL_16BIT
+                | VertexType::VERTEX_16BIT
+                | VertexType::INDEX_16BIT)
+                .bits() as u32
+        ),
+        "uv=8 color=5650 normal=16 pos=16 weight=none index=u16 3d"
+    );
+    // 8-bit weights with 3 weights per vertex, 2 morph targets
+    let bits = (1 << 9) | (2 << 14) | (1 << 18) | (1 << 11);
+    assert_eq!(
+        vertex_type(bits),
+        "uv=none color=none normal=none pos=none weight=8 index=u8 weights=3 morphs=2 3d"
+    );
+    // The weight count is only shown with weights
+    assert_eq!(vertex_type(2 << 14), vertex_type(0));
+}
+
+#[test]
+fn jump_skips_embedded_data() {
+    let address = 0x0880_0000;
+    let words = [
+        word(0x10, 0x08_0000),
+        // Jump over 2 words of data to the NOP
+        word(0x08, 0x80_0010),
+        0xdead_beef,
+        word(0x0c, 0),
+        word(0x00, 0),
+        word(0x0c, 0),
+    ];
+    let lines: Vec<Line> = disasm_at(&words, address).collect();
+    let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        [
+            "BASE 08000000",
+            "JUMP 08800010 (skipped 2 words)",
+            "NOP",
+            "END"
+        ]
+    );
+    assert_eq!(lines[2].offset, 16);
+
+    // Uncached addresses of the list work as well
+    assert_eq!(disasm_at(&words, address | 0x4000_0000).count(), 4);
+
+    // Without the address the data is decoded and the embedded END stops the list
+    assert_eq!(
+        self::texts(&words),
+        ["BASE 08000000", "JUMP 08800010", "ZTST ge", "END"]
+    );
+}
+
+#[test]
+fn jump_outside_of_the_list() {
+    let address = 0x0880_0000;
+    let words = [
+        word(0x10, 0x08_0000),
+        // Backwards and past the end of the words, nothing is skipped
+        word(0x08, 0x80_0000),
+        word(0x08, 0x80_1000),
+        word(0x0c, 0),
+    ];
+    assert_eq!(
+        disasm_at(&words, address)
+            .map(|line| line.text)
+            .collect::<Vec<_>>(),
+        ["BASE 08000000", "JUMP 08800000", "JUMP 08801000", "END"]
+    );
+}
+
+#[test]
+fn call_follows_embedded_sub_list() {
+    let address = 0x0880_0000;
+    // Sub-list in memory from sceGuGetMemory, called like `GuList` does
+    let words = [
+        word(0x10, 0x08_0000),
+        word(0x08, 0x80_0010),
+        word(0x00, 0),
+        word(0x0b, 0),
+        word(0x10, 0x08_0000),
+        word(0x0a, 0x80_0008),
+        word(0x0c, 0),
+    ];
+    let lines: Vec<Line> = disasm_at(&words, address).collect();
+    let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+    assert_eq!(
+        texts,
+        [
+            "BASE 08000000",
+            "JUMP 08800010 (skipped 2 words)",
+            "BASE 08000000",
+            "CALL 08800008",
+            "NOP",
+            "RET",
+            "END"
+        ]
+    );
+    let offsets: Vec<usize> = lines.iter().map(|line| line.offset).collect();
+    assert_eq!(offsets, [0, 4, 16, 20, 8, 12, 24]);
+
+    // A list calling itself stops following at the nesting limit
+    let words = [word(0x10, 0x08_0000), word(0x0a, 0x80_0004), word(0x0c, 0)];
+    let lines = disasm_at(&words, address).count();
+    assert_eq!(lines, 1 + 9 + 1);
+}
+
+#[test]
+fn end_terminates() {
+    let words = encode(|ge| {
+        ge.prim(GuPrimitive::Sprites, 2);
+    });
+    let mut words = words;
+    words.extend([word(0x0f, 0), word(0x0c, 0), word(0x04, 0), word(0x0c, 0)]);
+    let mut lines = disasm(&words);
+    assert_eq!(lines.next().unwrap().text, "PRIM sprites count=2");
+    assert_eq!(lines.next().unwrap().text, "FINISH");
+    assert_eq!(lines.next().unwrap().text, "END");
+    assert_eq!(lines.next(), None);
+    assert_eq!(lines.next(), None);
+
+    // Lists without END stop at the end of the words
+    assert_eq!(texts(&[word(0x00, 0)]), ["NOP"]);
+    assert!(texts(&[]).is_empty());
+}
+
+#[test]
+fn line_display() {
+    let line = disasm(&[word(0x04, (3 << 16) | 3)]).next().unwrap();
+    assert_eq!(line.to_string(), "000000: 04030003  PRIM triangles count=3");
+}
@@ -0,0 +1,202 @@
+//! GE dump recording shared with `psp_gfx::ge::dump`
+
+mod common;
+
+use common::word;
+use psp_gfx_tools::ge::dump::{CONTEXT_SIZE, record};
+
+const LIST_ADDRESS: u32 = 0x0890_0000;
+const VERTEX_ADDRESS: u32 = 0x0880_0000;
+const INDEX_ADDRESS: u32 = 0x0880_1000;
+
+/// `VERTEX_32BITF | INDEX_8BIT`
+const VERTEX_TYPE: u32 = (3 << 7) | (1 << 11);
+
+fn vertices() -> Vec<u8> {
+    (0..9u32)
+        .flat_map(|idx| (idx as f32).to_le_bytes())
+        .collect()
+}
+
+const INDICES: [u8; 3] = [0, 2, 1];
+
+/// Read the vertex and index memory of the test list
+fn read_memory(address: u32, buf: &mut [u8]) -> bool {
+    let vertices = vertices();
+    let memory: &[u8] = match address {
+        VERTEX_ADDRESS => &vertices,
+        INDEX_ADDRESS => &INDICES,
+        _ => return false,
+    };
+    match memory.get(..buf.len()) {
+        Some(memory) => {
+            buf.copy_from_slice(memory);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Decompress a length prefixed snappy block, which must only contain literals
+fn decompress(data: &mut &[u8]) -> Vec<u8> {
+    let (len, rest) = data.split_at(4);
+    let (mut block, rest) = rest.split_at(u32::from_le_bytes(len.try_into().unwrap()) as usize);
+    *data = rest;
+
+    let mut size = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = block.split_first().unwrap();
+        block = rest;
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    let mut out = Vec::new();
+    while let Some((&tag, rest)) = block.split_first() {
+        assert_eq!(tag & 3, 0, "only literals are written");
+        let (len, rest) = match tag >> 2 {
+            60 => (rest[0] as usize, &rest[1..]),
+            61 => (u16::from_le_bytes([rest[0], rest[1]]) as usize, &rest[2..]),
+            len => (len as usize, rest),
+        };
+        let (literal, rest) = rest.split_at(len + 1);
+        out.extend_from_slice(literal);
+        block = rest;
+    }
+    assert_eq!(out.len(), size);
+    out
+}
+
+/// Parsed dump: command records (type, size, offset) and the push buffer
+fn parse(dump: &[u8]) -> (Vec<(u8, u32, u32)>, Vec<u8>) {
+    assert_eq!(&dump[..8], b"PPSSPPGE");
+    assert_eq!(dump[8..12], 4u32.to_le_bytes());
+    // Game ID and padding
+    assert_eq!(dump[12..24], [0; 12]);
+    let command_count = u32::from_le_bytes(dump[24..28].try_into().unwrap()) as usize;
+    let pushbuf_size = u32::from_le_bytes(dump[28..32].try_into().unwrap()) as usize;
+
+    let mut data = &dump[32..];
+    let commands = decompress(&mut data);
+    let pushbuf = decompress(&mut data);
+    assert!(data.is_empty());
+    assert_eq!(commands.len(), command_count * 9);
+    assert_eq!(pushbuf.len(), pushbuf_size);
+
+    let records = commands
+        .chunks_exact(9)
+        .map(|record| {
+            let size = u32::from_le_bytes(record[1..5].try_into().unwrap());
+            let offset = u32::from_le_bytes(record[5..9].try_into().unwrap());
+            (record[0], size, offset)
+        })
+        .collect();
+    (records, pushbuf)
+}
+
+#[test]
+fn indexed_draw() {
+    let prim = word(0x04, (3 << 16) | 3);
+    let list = [
+        // BASE, VTYPE, VADDR, IADDR
+        word(0x10, (VERTEX_ADDRESS >> 8) & 0xf0000),
+        word(0x12, VERTEX_TYPE),
+        word(0x01, VERTEX_ADDRESS & 0xffffff),
+        word(0x02, INDEX_ADDRESS & 0xffffff),
+        prim,
+        // The same draw again, its memory is stored once
+        word(0x02, INDEX_ADDRESS & 0xffffff),
+        prim,
+        // FINISH, END
+        word(0x0f, 0),
+        word(0x0c, 0),
+        // Not recorded after END
+        word(0x12, 0),
+    ];
+    let mut context = [0; CONTEXT_SIZE];
+    context[0] = 0x1234_5678;
+    context[CONTEXT_SIZE - 1] = 0x9abc_def0;
+    let (records, pushbuf) = parse(&record(&list, LIST_ADDRESS, &context, read_memory));
+
+    assert_eq!(
+        records,
+        [
+            // Init with the context
+            (0, 2048, 0),
+            // Registers up to the draw
+            (1, 4, 2048),
+            // Indices, then the 3 indexed vertices of 12 bytes, aligned to 4 bytes
+            (3, 3, 2052),
+            (2, 36, 2056),
+            // The draw command, then the second draw
+            (1, 4, 2092),
+            (3, 3, 2052),
+            (2, 36, 2056),
+            (1, 4, 2096),
+            // Display buffer
+            (9, 12, 2100),
+        ]
+    );
+
+    let words = |offset: usize, count: usize| -> Vec<u32> {
+        pushbuf[offset..offset + count * 4]
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect()
+    };
+    assert_eq!(words(0, CONTEXT_SIZE), context);
+    assert_eq!(words(2048, 1), [word(0x12, VERTEX_TYPE)]);
+    assert_eq!(pushbuf[2052..2055], INDICES);
+    assert_eq!(pushbuf[2056..2092], vertices());
+    assert_eq!(words(2092, 1), [prim]);
+    assert_eq!(words(2096, 1), [prim]);
+    // Fallback display buffer at the start of VRAM, 512 pixels wide and 32-bit
+    assert_eq!(words(2100, 3), [0x0400_0000, 512, 3]);
+    assert_eq!(pushbuf.len(), 2112);
+}
+
+#[test]
+fn unreadable_memory() {
+    // Draws from invalid addresses only record their commands
+    let list = [
+        word(0x12, VERTEX_TYPE & !(3 << 11)),
+        word(0x01, 0),
+        word(0x04, (3 << 16) | 3),
+        word(0x0c, 0),
+    ];
+    let (records, _) = parse(&record(
+        &list,
+        LIST_ADDRESS,
+        &[0; CONTEXT_SIZE],
+        read_memory,
+    ));
+    let types: Vec<u8> = records.iter().map(|record| record.0).collect();
+    assert_eq!(types, [0, 1, 1, 9]);
+}
+
+#[test]
+fn large_literals() {
+    // 6000 vertices of 12 bytes don't fit into one literal of 65536 bytes
+    let list = [
+        word(0x12, VERTEX_TYPE & !(3 << 11)),
+        word(0x10, (VERTEX_ADDRESS >> 8) & 0xf0000),
+        word(0x01, VERTEX_ADDRESS & 0xffffff),
+        word(0x04, (3 << 16) | 6000),
+        word(0x0c, 0),
+    ];
+    let read_memory = |address, buf: &mut [u8]| {
+        buf.fill(0xab);
+        address == VERTEX_ADDRESS
+    };
+    let (records, pushbuf) = parse(&record(
+        &list,
+        LIST_ADDRESS,
+        &[0; CONTEXT_SIZE],
+        read_memory,
+    ));
+    assert_eq!(records[2], (2, 72000, 2052));
+    assert!(pushbuf[2052..2052 + 72000].iter().all(|&byte| byte == 0xab));
+}
@@ -0,0 +1,167 @@
+//! DXT block compression shared with `psp_gfx::texture`
+
+use psp_gfx_tools::{
+    color::{Color32, Color5650},
+    ge::sys::TexturePixelFormat,
+    texture::dxt::{Block, block_size, decode_block, encode_block},
+};
+
+const FORMATS: [TexturePixelFormat; 3] = [
+    TexturePixelFormat::PsmDxt1,
+    TexturePixelFormat::PsmDxt3,
+    TexturePixelFormat::PsmDxt5,
+];
+
+fn round_trip(format: TexturePixelFormat, pixels: &Block) -> Block {
+    let mut data = [0; 16];
+    encode_block(format, pixels, &mut data[..block_size(format)]);
+    decode_block(format, &data)
+}
+
+/// Largest difference of the color channels
+fn color_error(a: [u8; 4], b: [u8; 4]) -> u8 {
+    (0..3).map(|ch| a[ch].abs_diff(b[ch])).max().unwrap()
+}
+
+#[test]
+fn block_sizes() {
+    assert_eq!(block_size(TexturePixelFormat::PsmDxt1), 8);
+    assert_eq!(block_size(TexturePixelFormat::PsmDxt3), 16);
+    assert_eq!(block_size(TexturePixelFormat::PsmDxt5), 16);
+}
+
+#[test]
+fn solid_round_trip() {
+    let color = [200, 100, 50, 255];
+    let expected = Color5650::from_color32(Color32::new(200, 100, 50, 255))
+        .to_color32()
+        .to_array();
+    for format in FORMATS {
+        assert_eq!(
+            round_trip(format, &[color; 16]),
+            [expected; 16],
+            "{format:?}"
+        );
+    }
+}
+
+#[test]
+fn gradient_round_trip() {
+    let pixels: Block = core::array::from_fn(|idx| {
+        let value = idx as u8 * 17;
+        [value, value, 255 - value, 255]
+    });
+    for format in FORMATS {
+        let decoded = round_trip(format, &pixels);
+        // The ends of the gradient are the endpoints
+        assert_eq!(decoded[0], [0, 0, 255, 255], "{format:?}");
+        assert_eq!(decoded[15], [255, 255, 0, 255], "{format:?}");
+        for (pixel, restored) in pixels.iter().zip(decoded) {
+            // Half the distance between the 4 palette entries
+            assert!(
+                color_error(*pixel, restored) <= 43,
+                "{format:?}: {pixel:?} decoded as {restored:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn block_word_order() {
+    // Solid red: indices first, then both 5650 endpoints
+    let mut data = [0xaa; 8];
+    encode_block(
+        TexturePixelFormat::PsmDxt1,
+        &[[255, 0, 0, 255]; 16],
+        &mut data,
+    );
+    assert_eq!(data, [0, 0, 0, 0, 0x1f, 0, 0x1f, 0]);
+
+    // Pixel 0 uses index 1, the second endpoint (black), the others white
+    let block = decode_block(TexturePixelFormat::PsmDxt1, &[1, 0, 0, 0, 0xff, 0xff, 0, 0]);
+    assert_eq!(block[0], [0, 0, 0, 255]);
+    assert_eq!(block[1..], [[255; 4]; 15]);
+
+    // The color block comes before the alpha block
+    let mut data = [0; 16];
+    data[4..6].copy_from_slice(&0xffffu16.to_le_bytes());
+    data[8..16].copy_from_slice(&0xfedc_ba98_7654_3210u64.to_le_bytes());
+    let block = decode_block(TexturePixelFormat::PsmDxt3, &data);
+    for (idx, pixel) in block.iter().enumerate() {
+        assert_eq!(*pixel, [255, 255, 255, idx as u8 * 17]);
+    }
+
+    // DXT5: 48 bits of alpha indices, then both alpha endpoints
+    let mut data = [0; 16];
+    data[8..14].copy_from_slice(&[0b001, 0, 0, 0, 0, 0]);
+    data[14] = 200;
+    data[15] = 100;
+    let block = decode_block(TexturePixelFormat::PsmDxt5, &data);
+    assert_eq!(block[0][3], 100);
+    assert!(block[1..].iter().all(|pixel| pixel[3] == 200));
+}
+
+#[test]
+fn dxt1_punch_through() {
+    let pixels: Block = core::array::from_fn(|idx| {
+        if idx % 2 == 0 {
+            [0, 0, 255, 255]
+        } else {
+            [255, 0, 0, 100]
+        }
+    });
+    let mut data = [0; 8];
+    encode_block(TexturePixelFormat::PsmDxt1, &pixels, &mut data);
+    // The 3 color mode with a transparent entry is selected by `c0 <= c1`
+    let c0 = u16::from_le_bytes([data[4], data[5]]);
+    let c1 = u16::from_le_bytes([data[6], data[7]]);
+    assert!(c0 <= c1);
+
+    let decoded = decode_block(TexturePixelFormat::PsmDxt1, &data);
+    for (idx, pixel) in decoded.iter().enumerate() {
+        let expected = if idx % 2 == 0 {
+            [0, 0, 255, 255]
+        } else {
+            [0; 4]
+        };
+        assert_eq!(*pixel, expected);
+    }
+
+    // Without transparent pixels the 4 color mode is used
+    let mut data = [0; 8];
+    let pixels: Block = core::array::from_fn(|idx| [idx as u8 * 17, 0, 0, 255]);
+    encode_block(TexturePixelFormat::PsmDxt1, &pixels, &mut data);
+    assert!(u16::from_le_bytes([data[4], data[5]]) > u16::from_le_bytes([data[6], data[7]]));
+}
+
+#[test]
+fn dxt3_alpha() {
+    let pixels: Block = core::array::from_fn(|idx| [0, 0, 0, [0, 128, 255, 17][idx % 4]]);
+    let decoded = round_trip(TexturePixelFormat::PsmDxt3, &pixels);
+    let alpha: Vec<u8> = decoded[..4].iter().map(|pixel| pixel[3]).collect();
+    // 4 bits per pixel, rounded to the nearest value
+    assert_eq!(alpha, [0, 136, 255, 17]);
+}
+
+#[test]
+fn dxt5_alpha() {
+    let pixels: Block = core::array::from_fn(|idx| [0, 0, 0, idx as u8 * 17]);
+    let decoded = round_trip(TexturePixelFormat::PsmDxt5, &pixels);
+    for (pixel, restored) in pixels.iter().zip(decoded) {
+        // Half the distance between the 8 interpolated values
+        assert!(pixel[3].abs_diff(restored[3]) <= 19);
+    }
+    assert_eq!(decoded[0][3], 0);
+    assert_eq!(decoded[15][3], 255);
+
+    // Equal endpoints select the 6 value mode, where 0 and 255 are explicit
+    let decoded = round_trip(TexturePixelFormat::PsmDxt5, &[[0, 0, 0, 90]; 16]);
+    assert!(decoded.iter().all(|pixel| pixel[3] == 90));
+    let mut data = [0; 16];
+    // Indices 6 and 7 of pixels 0 and 1
+    data[8] = 6 | (7 << 3);
+    data[14] = 10;
+    data[15] = 20;
+    let decoded = decode_block(TexturePixelFormat::PsmDxt5, &data);
+    assert_eq!((decoded[0][3], decoded[1][3], decoded[2][3]), (0, 255, 10));
+}
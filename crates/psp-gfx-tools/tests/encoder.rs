@@ -0,0 +1,316 @@
+//! Encoder output compared with the words pspsdk's `sceGu*` functions send
+
+mod common;
+
+use common::{encode, float, word};
+use psp_gfx_tools::ge::{
+    command, float24,
+    sys::{
+        ColorFunc, FrontFaceDirection, GeCommand, GuPrimitive, GuTexWrapMode, LogicalOperation,
+        MatrixMode, MipmapLevel, PatchPrimitive, ScePspFMatrix4, ScePspFVector4, ShadingModel,
+        SplineMode, StencilFunc, StencilOperation, TextureFilter, TextureLevelMode,
+        TexturePixelFormat, VertexType,
+    },
+};
+
+#[test]
+fn command_word() {
+    assert_eq!(command(GeCommand::Prim, 0x1234_5678), 0x0434_5678);
+    assert_eq!(command(GeCommand::End, 0), 0x0c00_0000);
+    assert_eq!(float24(1.), 0x3f8000);
+    assert_eq!(float24(-2.), 0xc00000);
+}
+
+#[test]
+fn draw_array() {
+    // sceGuDrawArray(GU_TRIANGLES, vtype, 3, 0, 0x0912_3450)
+    let vtype = VertexType::TEXTURE_32BITF | VertexType::VERTEX_32BITF | VertexType::TRANSFORM_2D;
+    let words = encode(|ge| {
+        ge.vertex_type(vtype)
+            .vertex_address(0x0912_3450)
+            .prim(GuPrimitive::Triangles, 3);
+    });
+    assert_eq!(
+        words,
+        [
+            word(18, 0x80_0183),
+            word(16, 0x09_0000),
+            word(1, 0x12_3450),
+            word(4, (3 << 16) | 3),
+        ]
+    );
+}
+
+#[test]
+fn draw_array_indexed() {
+    // sceGuDrawArray(GU_SPRITES, vtype, 0xffff, 0x0880_0000, 0x0440_0000)
+    let words = encode(|ge| {
+        ge.index_address(0x0880_0000)
+            .vertex_address(0x0440_0000)
+            .prim(GuPrimitive::Sprites, 0xffff);
+    });
+    assert_eq!(
+        words,
+        [
+            word(16, 0x08_0000),
+            word(2, 0x80_0000),
+            word(16, 0x04_0000),
+            word(1, 0x40_0000),
+            word(4, (6 << 16) | 0xffff),
+        ]
+    );
+}
+
+#[test]
+fn patches() {
+    let words = encode(|ge| {
+        ge.bezier(7, 4)
+            .spline(5, 6, SplineMode::OpenFill, SplineMode::FillOpen)
+            .patch_divide(16, 8)
+            .patch_front_face(FrontFaceDirection::CounterClockwise)
+            .patch_primitive(PatchPrimitive::Points)
+            .patch_primitive(PatchPrimitive::LineStrip)
+            .patch_primitive(PatchPrimitive::TriangleStrip);
+    });
+    assert_eq!(
+        words,
+        [
+            // sceGuDrawBezier
+            word(5, (4 << 8) | 7),
+            // sceGuDrawSpline(vtype, 5, 6, GU_OPEN_FILL, GU_FILL_OPEN, ..)
+            word(6, (2 << 18) | (1 << 16) | (6 << 8) | 5),
+            // sceGuPatchDivide
+            word(54, (8 << 8) | 16),
+            // sceGuPatchFrontFace
+            word(56, 1),
+            // sceGuPatchPrim(GU_POINTS), (GU_LINE_STRIP), (GU_TRIANGLE_STRIP)
+            word(55, 2),
+            word(55, 1),
+            word(55, 0),
+        ]
+    );
+}
+
+#[test]
+fn material_and_shading() {
+    let words = encode(|ge| {
+        ge.material_color(0x8012_3456)
+            .shade_model(ShadingModel::Smooth)
+            .shade_model(ShadingModel::Flat);
+    });
+    assert_eq!(
+        words,
+        [
+            // sceGuColor
+            word(85, 0x12_3456),
+            word(88, 0x80),
+            word(86, 0x12_3456),
+            word(87, 0x12_3456),
+            // sceGuShadeModel
+            word(80, 1),
+            word(80, 0),
+        ]
+    );
+}
+
+#[test]
+fn matrices() {
+    let row = |base: f32| ScePspFVector4 {
+        x: base,
+        y: base + 1.,
+        z: base + 2.,
+        w: base + 3.,
+    };
+    let matrix = ScePspFMatrix4 {
+        x: row(0.),
+        y: row(4.),
+        z: row(8.),
+        w: row(12.),
+    };
+
+    // sceGuSetMatrix(GU_PROJECTION, ..) sends all 16 values
+    let words = encode(|ge| {
+        ge.matrix(MatrixMode::Projection, &matrix);
+    });
+    let mut expected = vec![word(62, 0)];
+    expected.extend((0..16).map(|idx| float(63, idx as f32)));
+    assert_eq!(words, expected);
+
+    // The other matrices send the 4x3 part
+    for (mode, number) in [
+        (MatrixMode::View, 60),
+        (MatrixMode::Model, 58),
+        (MatrixMode::Texture, 64),
+    ] {
+        let words = encode(|ge| {
+            ge.matrix(mode, &matrix);
+        });
+        let mut expected = vec![word(number, 0)];
+        for row in 0..4 {
+            expected.extend((0..3).map(|col| float(number + 1, (row * 4 + col) as f32)));
+        }
+        assert_eq!(words, expected);
+    }
+}
+
+#[test]
+fn textures() {
+    let words = encode(|ge| {
+        ge.tex_mode(TexturePixelFormat::PsmDxt5, 2, true)
+            .tex_image(MipmapLevel::Level1, 0x0412_3400, 256, 64, 512)
+            .tex_filter(TextureFilter::LinearMipmapLinear, TextureFilter::Linear)
+            .tex_wrap(GuTexWrapMode::Clamp, GuTexWrapMode::Repeat)
+            .tex_scale(0.5, 2.)
+            .tex_offset(-1., 0.25)
+            .tex_level(TextureLevelMode::Const, -1.5)
+            .tex_level(TextureLevelMode::Auto, 100.)
+            .tex_slope(0.75)
+            .tex_env_color(0x00ff_8040)
+            .tex_sync();
+    });
+    assert_eq!(
+        words,
+        [
+            // sceGuTexMode(GU_PSM_DXT5, 2, 0, 1)
+            word(194, (2 << 16) | 1),
+            word(195, 10),
+            word(203, 0),
+            // sceGuTexImage(1, 256, 64, 512, 0x0412_3400)
+            word(161, 0x12_3400),
+            word(169, 0x04_0000 | 512),
+            word(185, (6 << 8) | 8),
+            word(203, 0),
+            // sceGuTexFilter
+            word(198, (1 << 8) | 7),
+            // sceGuTexWrap
+            word(199, 1),
+            // sceGuTexScale, sceGuTexOffset
+            float(72, 0.5),
+            float(73, 2.),
+            float(74, -1.),
+            float(75, 0.25),
+            // sceGuTexLevelMode, the bias is clamped to 128
+            word(200, ((-24i32 as u32) << 16) | 1),
+            word(200, 128 << 16),
+            // sceGuTexSlope, sceGuTexEnvColor, sceGuTexSync
+            float(208, 0.75),
+            word(202, 0xff_8040),
+            word(204, 0),
+        ]
+    );
+}
+
+#[test]
+fn viewport_and_offset() {
+    let words = encode(|ge| {
+        ge.viewport((2048., 2048.), 480., 272.)
+            .offset(2048 - 240, 2048 - 136);
+    });
+    assert_eq!(
+        words,
+        [
+            // sceGuViewport(2048, 2048, 480, 272)
+            float(66, 240.),
+            float(67, -136.),
+            float(69, 2048.),
+            float(70, 2048.),
+            // sceGuOffset
+            word(76, (2048 - 240) << 4),
+            word(77, (2048 - 136) << 4),
+        ]
+    );
+}
+
+#[test]
+fn pixel_state() {
+    let words = encode(|ge| {
+        ge.stencil_func(StencilFunc::Equal, 0x12, 0xf0)
+            .stencil_op(
+                StencilOperation::Keep,
+                StencilOperation::Zero,
+                StencilOperation::Replace,
+            )
+            .pixel_mask(0xff00_ff00)
+            .depth_write(false)
+            .depth_write(true)
+            .fog(10., 110., 0x00_8080)
+            .fog(5., 5., 0)
+            .color_test(ColorFunc::NotEqual, 0x00_ff00, 0xff_ffff)
+            .logic_op(LogicalOperation::Xor);
+    });
+    assert_eq!(
+        words,
+        [
+            // sceGuStencilFunc
+            word(220, (0xf0 << 16) | (0x12 << 8) | 2),
+            // sceGuStencilOp
+            word(221, (2 << 16) | (1 << 8)),
+            // sceGuPixelMask
+            word(232, 0x00_ff00),
+            word(233, 0xff),
+            // sceGuDepthMask
+            word(231, 1),
+            word(231, 0),
+            // sceGuFog, a zero distance keeps the scale at zero
+            word(207, 0x00_8080),
+            float(205, 110.),
+            float(206, 0.01),
+            word(207, 0),
+            float(205, 5.),
+            float(206, 0.),
+            // sceGuColorFunc
+            word(216, 3),
+            word(217, 0x00_ff00),
+            word(218, 0xff_ffff),
+            // sceGuLogicalOp
+            word(230, 6),
+        ]
+    );
+}
+
+#[test]
+fn dither() {
+    // sceGuSetDither with the matrix sceGuStart sets up
+    let matrix = [
+        [-4, 0, -3, 1],
+        [2, -2, 3, -1],
+        [-3, 1, -4, 0],
+        [3, -1, 2, -2],
+    ];
+    let words = encode(|ge| {
+        ge.dither(&matrix);
+    });
+    let row = |row: [i32; 4]| {
+        (row[0] as u32 & 0xf)
+            | ((row[1] as u32 & 0xf) << 4)
+            | ((row[2] as u32 & 0xf) << 8)
+            | ((row[3] as u32 & 0xf) << 12)
+    };
+    let expected: Vec<_> = (0..4)
+        .map(|idx| word(226 + idx as u32, row(matrix[idx].map(i32::from))))
+        .collect();
+    assert_eq!(words, expected);
+}
+
+#[test]
+fn transfer() {
+    // sceGuCopyImage(GU_PSM_8888, 8, 4, 100, 50, 512, 0x0400_0000, 16, 2, 256, 0x0910_0000)
+    let words = encode(|ge| {
+        ge.transfer_source(0x0400_0000, 512, 8, 4)
+            .transfer_destination(0x0910_0000, 256, 16, 2)
+            .transfer_start(100, 50, true);
+    });
+    assert_eq!(
+        words,
+        [
+            word(178, 0),
+            word(179, 0x04_0000 | 512),
+            word(235, (4 << 10) | 8),
+            word(180, 0x10_0000),
+            word(181, 0x09_0000 | 256),
+            word(236, (2 << 10) | 16),
+            word(238, (49 << 10) | 99),
+            word(234, 1),
+        ]
+    );
+}
@@ -0,0 +1,187 @@
+//! glTF importer
+
+use psp_gfx_tools::{gltf::parse, scene::SubmeshData};
+
+/// Build a GLB container with a JSON and a binary chunk
+fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = bin.to_vec();
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let mut out = b"glTF".to_vec();
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"BIN\0");
+    out.extend_from_slice(&bin);
+    out
+}
+
+fn no_files(_: &str) -> Option<Vec<u8>> {
+    None
+}
+
+const QUAD: [[f32; 3]; 4] = [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]];
+
+/// Quad with interleaved positions and normalized `u16` texture coordinates, behind 8
+/// bytes of padding, followed by `u8` indices
+fn interleaved_buffer() -> Vec<u8> {
+    let uvs = [[0, 0], [65535, 0], [65535, 65535], [0, 65535]];
+    let mut bin = vec![0xff; 8];
+    for (position, uv) in QUAD.iter().zip(uvs) {
+        for value in position {
+            bin.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in uv {
+            bin.extend_from_slice(&(value as u16).to_le_bytes());
+        }
+    }
+    bin.extend_from_slice(&[0, 1, 2, 3]);
+    bin
+}
+
+#[test]
+fn interleaved_glb() {
+    let json = r#"{
+        "buffers": [{"byteLength": 76}],
+        "bufferViews": [
+            {"buffer": 0, "byteOffset": 8, "byteLength": 64, "byteStride": 16},
+            {"buffer": 0, "byteOffset": 72, "byteLength": 4}
+        ],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"},
+            {"bufferView": 0, "byteOffset": 12, "componentType": 5123, "normalized": true,
+             "count": 4, "type": "VEC2"},
+            {"bufferView": 1, "componentType": 5121, "count": 4, "type": "SCALAR"}
+        ],
+        "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [0.5, 0.25, 1, 0.75]}}],
+        "meshes": [
+            {"primitives": [{"attributes": {"POSITION": 0, "TEXCOORD_0": 1}, "indices": 2,
+                             "mode": 6, "material": 0}]},
+            {"primitives": [{"attributes": {"POSITION": 0}}]}
+        ]
+    }"#;
+    let mesh = parse(&glb(json, &interleaved_buffer()), no_files).unwrap();
+
+    // Both primitives read the same positions through the stride
+    assert_eq!(mesh.positions, [QUAD, QUAD].concat());
+    // The second primitive has no texture coordinates, they are filled with zeros
+    assert_eq!(
+        mesh.uvs,
+        [
+            [0., 0.],
+            [1., 0.],
+            [1., 1.],
+            [0., 1.],
+            [0., 0.],
+            [0., 0.],
+            [0., 0.],
+            [0., 0.]
+        ]
+    );
+    assert!(mesh.normals.is_empty() && mesh.colors.is_empty());
+
+    // The fan is triangulated, the non-indexed list drops the incomplete triangle
+    assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3, 4, 5, 6]);
+    assert_eq!(
+        mesh.submeshes,
+        [
+            SubmeshData {
+                start: 0,
+                count: 6,
+                material: 0
+            },
+            SubmeshData {
+                start: 6,
+                count: 3,
+                material: 1
+            }
+        ]
+    );
+    assert_eq!(mesh.materials[0].color, [0.5, 0.25, 1., 0.75]);
+    assert_eq!(mesh.materials[1].color, [1.; 4]);
+}
+
+#[test]
+fn external_and_embedded_buffers() {
+    let json = r#"{
+        "buffers": [
+            {"uri": "quad%20positions.bin", "byteLength": 48},
+            {"uri": "data:application/octet-stream;base64,AAABAAIAAwA=", "byteLength": 8}
+        ],
+        "bufferViews": [
+            {"buffer": 0, "byteLength": 48},
+            {"buffer": 1, "byteLength": 8}
+        ],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"},
+            {"bufferView": 1, "componentType": 5123, "count": 4, "type": "SCALAR"},
+            {"componentType": 5126, "count": 4, "type": "VEC3"}
+        ],
+        "meshes": [{"primitives": [{"attributes": {"POSITION": 0, "NORMAL": 2},
+                                    "indices": 1, "mode": 5}]}]
+    }"#;
+    let positions: Vec<u8> = QUAD
+        .iter()
+        .flatten()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    let load_file = |name: &str| (name == "quad positions.bin").then(|| positions.clone());
+    let mesh = parse(json.as_bytes(), load_file).unwrap();
+
+    assert_eq!(mesh.positions, QUAD);
+    // Accessors without a buffer view are zeros
+    assert_eq!(mesh.normals, [[0.; 3]; 4]);
+    // Every other triangle of the strip is flipped to keep the winding
+    assert_eq!(mesh.indices, [0, 1, 2, 2, 1, 3]);
+
+    assert!(parse(json.as_bytes(), no_files).is_err());
+}
+
+#[test]
+fn invalid_accessors() {
+    let document = |accessors: &str, indices: &str| {
+        format!(
+            r#"{{
+                "buffers": [{{"byteLength": 76}}],
+                "bufferViews": [
+                    {{"buffer": 0, "byteOffset": 8, "byteLength": 64, "byteStride": 16}},
+                    {{"buffer": 0, "byteOffset": 72, "byteLength": 4}}
+                ],
+                "accessors": [{accessors}],
+                "meshes": [{{"primitives": [{{"attributes": {{"POSITION": 0}}{indices}}}]}}]
+            }}"#
+        )
+    };
+    let bin = interleaved_buffer();
+    let parse_document = |json: String| parse(&glb(&json, &bin), no_files);
+
+    let positions = r#"{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3"}"#;
+    assert!(parse_document(document(positions, "")).is_ok());
+
+    // The fifth element would start past the end of the buffer
+    let too_many = r#"{"bufferView": 0, "componentType": 5126, "count": 5, "type": "VEC3"}"#;
+    assert_eq!(
+        parse_document(document(too_many, "")).unwrap_err(),
+        "accessor out of buffer bounds"
+    );
+
+    // Indices 0..4 with only 3 vertices
+    let three = r#"{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+        {"bufferView": 1, "componentType": 5121, "count": 4, "type": "SCALAR"}"#;
+    assert_eq!(
+        parse_document(document(three, r#", "indices": 1"#)).unwrap_err(),
+        "index out of range"
+    );
+
+    let sparse = r#"{"bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3",
+        "sparse": {"count": 1}}"#;
+    assert!(parse_document(document(sparse, "")).is_err());
+    let matrix = r#"{"bufferView": 0, "componentType": 5126, "count": 1, "type": "MAT4"}"#;
+    assert!(parse_document(document(matrix, "")).is_err());
+    assert!(parse_document(document(positions, r#", "mode": 1"#)).is_err());
+}
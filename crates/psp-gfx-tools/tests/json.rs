@@ -0,0 +1,85 @@
+//! JSON parser used by the glTF importer
+
+use std::collections::BTreeMap;
+
+use psp_gfx_tools::json::{Value, parse};
+
+fn string(text: &str) -> String {
+    parse(text).unwrap().as_str().unwrap().to_owned()
+}
+
+#[test]
+fn escapes() {
+    assert_eq!(string(r#""a\"b\\c\/d""#), "a\"b\\c/d");
+    assert_eq!(string(r#""\b\f\n\r\t""#), "\u{8}\u{c}\n\r\t");
+    assert_eq!(string(r#""\u00e9\u20AC""#), "é€");
+    // Surrogate pairs are combined
+    assert_eq!(string(r#""\ud83d\ude00""#), "😀");
+    // Raw UTF-8 is kept
+    assert_eq!(string("\"é\""), "é");
+
+    assert!(parse(r#""\x""#).is_err());
+    assert!(parse(r#""\u12""#).is_err());
+    assert!(parse(r#""abc"#).is_err());
+}
+
+#[test]
+fn numbers() {
+    let number = |text: &str| parse(text).unwrap().as_f64().unwrap();
+    assert_eq!(number("0"), 0.);
+    assert_eq!(number("-12"), -12.);
+    assert_eq!(number("3.25"), 3.25);
+    assert_eq!(number("1e3"), 1000.);
+    assert_eq!(number("-2.5E-2"), -0.025);
+    assert_eq!(number("4e+1"), 40.);
+
+    assert!(parse("-").is_err());
+    assert!(parse("1.2.3").is_err());
+
+    // Only non-negative integers are indices
+    assert_eq!(parse("7").unwrap().as_usize(), Some(7));
+    assert_eq!(parse("7.5").unwrap().as_usize(), None);
+    assert_eq!(parse("-1").unwrap().as_usize(), None);
+}
+
+#[test]
+fn nesting() {
+    let value =
+        parse(r#" { "a": [1, {"b": [true, false, null]}, []], "c": {}, "d": "e" } "#).unwrap();
+    let expected = Value::Object(BTreeMap::from([
+        (
+            "a".to_owned(),
+            Value::Array(vec![
+                Value::Number(1.),
+                Value::Object(BTreeMap::from([(
+                    "b".to_owned(),
+                    Value::Array(vec![Value::Bool(true), Value::Bool(false), Value::Null]),
+                )])),
+                Value::Array(Vec::new()),
+            ]),
+        ),
+        ("c".to_owned(), Value::Object(BTreeMap::new())),
+        ("d".to_owned(), Value::String("e".to_owned())),
+    ]));
+    assert_eq!(value, expected);
+    assert_eq!(value.get("d").and_then(Value::as_str), Some("e"));
+    assert_eq!(value.get("missing"), None);
+    assert_eq!(value.get("a").unwrap().get("b"), None);
+}
+
+#[test]
+fn malformed() {
+    for text in [
+        "",
+        "[1,]",
+        "[1 2]",
+        "{\"a\" 1}",
+        "{1: 2}",
+        "[1] x",
+        "tru",
+        "[",
+        "{",
+    ] {
+        assert!(parse(text).is_err(), "{text:?}");
+    }
+}
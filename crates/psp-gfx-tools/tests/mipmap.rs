@@ -0,0 +1,160 @@
+//! Mip level layout and downsampling shared with `psp_gfx::texture`
+
+use psp_gfx_tools::{
+    ge::sys::TexturePixelFormat,
+    texture::{MipmapFilter, TextureLevel, layout_levels, mipmap::downsample},
+};
+
+/// Width, height and buffer width of each level
+fn sizes(levels: &[TextureLevel]) -> Vec<(u32, u32, u32)> {
+    levels
+        .iter()
+        .map(|level| (level.width, level.height, level.buffer_width))
+        .collect()
+}
+
+/// Downsample the RGBA pixels of level `idx - 1` into level `idx`
+fn downsample_rgba(
+    filter: MipmapFilter,
+    levels: &[TextureLevel],
+    idx: usize,
+    pixels: &[[u8; 4]],
+) -> Vec<[u8; 4]> {
+    let format = TexturePixelFormat::Psm8888;
+    let (src_level, dst_level) = (levels[idx - 1], levels[idx]);
+    let src: Vec<u8> = pixels.iter().flatten().copied().collect();
+    assert_eq!(src.len(), src_level.byte_size(format));
+    let mut dst = vec![0; dst_level.byte_size(format)];
+    downsample(format, filter, &src, src_level, &mut dst, dst_level);
+    dst.chunks_exact(4)
+        .map(|pixel| pixel.try_into().unwrap())
+        .collect()
+}
+
+fn gray(value: u8) -> [u8; 4] {
+    [value, value, value, 255]
+}
+
+#[test]
+fn non_square_chain() {
+    let levels = layout_levels(TexturePixelFormat::Psm8888, 64, 16, 7);
+    assert_eq!(
+        sizes(&levels),
+        [
+            (64, 16, 64),
+            (32, 8, 32),
+            (16, 4, 16),
+            (8, 2, 8),
+            (4, 1, 4),
+            (2, 1, 4),
+            (1, 1, 4)
+        ]
+    );
+}
+
+#[test]
+fn non_power_of_two_chain() {
+    let levels = layout_levels(TexturePixelFormat::Psm5650, 24, 10, 5);
+    // Rows of 16-bit pixels are padded to 8 pixels
+    assert_eq!(
+        sizes(&levels),
+        [(24, 10, 24), (12, 5, 16), (6, 2, 8), (3, 1, 8), (1, 1, 8)]
+    );
+    for pair in levels.windows(2) {
+        let end = pair[0].offset + pair[0].byte_size(TexturePixelFormat::Psm5650);
+        assert_eq!(pair[1].offset, end.next_multiple_of(16));
+    }
+}
+
+#[test]
+fn compressed_chain() {
+    let levels = layout_levels(TexturePixelFormat::PsmDxt1, 16, 8, 5);
+    assert_eq!(
+        sizes(&levels),
+        [(16, 8, 16), (8, 4, 8), (4, 2, 4), (2, 1, 4), (1, 1, 4)]
+    );
+    // 8 bytes per 4x4 block, levels smaller than a block still take a whole one
+    let bytes: Vec<usize> = levels
+        .iter()
+        .map(|level| level.byte_size(TexturePixelFormat::PsmDxt1))
+        .collect();
+    assert_eq!(bytes, [64, 16, 8, 8, 8]);
+}
+
+#[test]
+fn box_filter() {
+    let levels = layout_levels(TexturePixelFormat::Psm8888, 4, 2, 2);
+    let pixels = [0, 10, 100, 200, 20, 30, 50, 51].map(gray);
+    let result = downsample_rgba(MipmapFilter::Box, &levels, 1, &pixels);
+    // (0 + 10 + 20 + 30) / 4 and (100 + 200 + 50 + 51) / 4, rounded
+    assert_eq!(result[..2], [gray(15), gray(100)]);
+}
+
+#[test]
+fn triangle_filter() {
+    let levels = layout_levels(TexturePixelFormat::Psm8888, 4, 4, 2);
+    let pixels: Vec<[u8; 4]> = (0..16).map(|idx| gray((idx % 4) as u8 * 60)).collect();
+    let result = downsample_rgba(MipmapFilter::Triangle, &levels, 1, &pixels);
+    // 1 3 3 1 weights with clamped edges: (0 + 0 + 180 + 120) / 8, (60 + 360 + 540 + 180) / 8
+    assert_eq!(result[..2], [gray(38), gray(143)]);
+    assert_eq!(result[4..6], [gray(38), gray(143)]);
+}
+
+#[test]
+fn triangle_keeps_a_single_pixel_axis() {
+    // Columns of a 1 pixel wide level are only filtered vertically
+    let levels = layout_levels(TexturePixelFormat::Psm8888, 1, 4, 2);
+    let mut pixels = vec![[0; 4]; 16];
+    for (row, value) in [0, 60, 120, 180].into_iter().enumerate() {
+        pixels[row * 4] = gray(value);
+    }
+    let result = downsample_rgba(MipmapFilter::Triangle, &levels, 1, &pixels);
+    assert_eq!([result[0], result[4]], [gray(38), gray(143)]);
+
+    // A 1x1 level is copied
+    let levels = layout_levels(TexturePixelFormat::Psm8888, 1, 1, 2);
+    let mut pixels = vec![[0; 4]; 4];
+    pixels[0] = [1, 2, 3, 4];
+    for filter in [MipmapFilter::Box, MipmapFilter::Triangle] {
+        assert_eq!(
+            downsample_rgba(filter, &levels, 1, &pixels)[0],
+            [1, 2, 3, 4]
+        );
+    }
+}
+
+#[test]
+fn non_power_of_two_downsample() {
+    let levels = layout_levels(TexturePixelFormat::Psm8888, 6, 2, 3);
+    let mut pixels = vec![[0; 4]; 8 * 2];
+    for (x, value) in [0, 40, 80, 120, 160, 200].into_iter().enumerate() {
+        pixels[x] = gray(value);
+        pixels[8 + x] = gray(value);
+    }
+    let level1 = downsample_rgba(MipmapFilter::Box, &levels, 1, &pixels);
+    assert_eq!(level1[..3], [gray(20), gray(100), gray(180)]);
+    // 3 pixels reduced to 1, the box filter uses the outer pixels
+    let level2 = downsample_rgba(MipmapFilter::Box, &levels, 2, &level1);
+    assert_eq!(level2[0], gray(100));
+}
+
+#[test]
+fn compressed_downsample() {
+    let format = TexturePixelFormat::PsmDxt1;
+    let levels = layout_levels(format, 8, 8, 2);
+    // Solid blocks of an exactly representable color stay the same
+    let mut block = [0; 8];
+    block[4..6].copy_from_slice(&0xf800u16.to_le_bytes());
+    block[6..8].copy_from_slice(&0xf800u16.to_le_bytes());
+    let src = block.repeat(4);
+    let mut dst = vec![0xaa; levels[1].byte_size(format)];
+    downsample(
+        format,
+        MipmapFilter::Box,
+        &src,
+        levels[0],
+        &mut dst,
+        levels[1],
+    );
+    assert_eq!(dst, block);
+}
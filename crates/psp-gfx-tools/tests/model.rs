@@ -0,0 +1,250 @@
+//! Model writer checked against the parser shared with `psp_gfx::model`
+
+use bytemuck::{Pod, Zeroable};
+use psp_gfx_tools::{
+    color::Color32,
+    ge::sys::{GuPrimitive, VertexType},
+    model::{
+        Format, VertexLayout,
+        file::{ModelError, ModelFile, SubmeshRecord},
+        write_model,
+    },
+    scene::{MaterialData, MeshData, SubmeshData},
+};
+
+/// Vertex of `LAYOUT`, as `define_vertex_layout!` lays it out
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+struct Vertex {
+    uv: [f32; 2],
+    color: u32,
+    normal: [i8; 3],
+    _pad: u8,
+    position: [f32; 3],
+}
+
+const LAYOUT: VertexLayout = VertexLayout {
+    uv: Some(Format::F32),
+    color: true,
+    normal: Some(Format::I8),
+    position: Format::F32,
+};
+
+fn vertex_type() -> VertexType {
+    VertexType::TEXTURE_32BITF
+        | VertexType::COLOR_8888
+        | VertexType::NORMAL_8BIT
+        | VertexType::VERTEX_32BITF
+}
+
+/// Quad with a plain and a textured triangle
+fn quad() -> MeshData {
+    MeshData {
+        positions: vec![[0., 0., 0.], [2., 0., 0.], [2., 1., 0.], [0., 1., -1.]],
+        uvs: vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]],
+        normals: vec![[0., 0., 1.]; 4],
+        colors: vec![
+            [1., 0., 0., 1.],
+            [0., 1., 0., 1.],
+            [0., 0., 1., 1.],
+            [1.; 4],
+        ],
+        indices: vec![0, 1, 2, 0, 2, 3],
+        submeshes: vec![
+            SubmeshData {
+                start: 0,
+                count: 3,
+                material: 0,
+            },
+            SubmeshData {
+                start: 3,
+                count: 3,
+                material: 1,
+            },
+        ],
+        materials: vec![
+            MaterialData {
+                color: [0., 1., 0., 1.],
+                texture: None,
+            },
+            MaterialData {
+                color: [1.; 4],
+                texture: Some("stone.png".into()),
+            },
+        ],
+    }
+}
+
+/// Offset of the submesh records: header, transforms and the `stone.png` name
+const SUBMESHES: usize = 36 + 40 + 2 + 9;
+
+fn parse_error(data: &[u8]) -> Option<ModelError> {
+    ModelFile::parse(data).err()
+}
+
+fn set_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn round_trip() {
+    let mesh = quad();
+    let data = write_model(&mesh, &LAYOUT).unwrap();
+    let file = ModelFile::parse(&data).unwrap();
+
+    assert_eq!(file.vertex_type.bits(), vertex_type().bits());
+    assert_eq!(file.stride, size_of::<Vertex>());
+    assert_eq!(file.primitive, GuPrimitive::Triangles);
+    assert_eq!(file.indices.as_deref(), Some(&[0, 1, 2, 0, 2, 3][..]));
+    assert_eq!(file.texture_files, ["stone.png"]);
+    assert_eq!(
+        file.submeshes,
+        [
+            SubmeshRecord {
+                range: 0..3,
+                color: Color32::new(0, 255, 0, 255),
+                texture: None,
+            },
+            SubmeshRecord {
+                range: 3..6,
+                color: Color32::new(255, 255, 255, 255),
+                texture: Some(0),
+            },
+        ]
+    );
+    // Float attributes are stored as is
+    assert_eq!(file.position_scale, [1.; 3]);
+    assert_eq!(file.position_offset, [0.; 3]);
+    assert_eq!((file.uv_scale, file.uv_offset), ((1., 1.), (0., 0.)));
+
+    let vertices = file.vertices::<Vertex>(vertex_type()).unwrap();
+    let colors = [0xff0000ff, 0xff00ff00, 0xffff0000, 0xffffffff];
+    for (idx, vertex) in vertices.iter().enumerate() {
+        assert_eq!(
+            *vertex,
+            Vertex {
+                uv: mesh.uvs[idx],
+                color: colors[idx],
+                normal: [0, 0, 127],
+                _pad: 0,
+                position: mesh.positions[idx],
+            }
+        );
+    }
+    // The transform flag of the requested layout is ignored
+    assert!(
+        file.vertices::<Vertex>(vertex_type() | VertexType::TRANSFORM_2D)
+            .is_ok()
+    );
+}
+
+#[test]
+fn quantized_round_trip() {
+    let layout = VertexLayout {
+        uv: None,
+        color: false,
+        normal: None,
+        position: Format::I16,
+    };
+    let mesh = quad();
+    let file = ModelFile::parse(&write_model(&mesh, &layout).unwrap()).unwrap();
+    assert_eq!(file.vertex_type.bits(), VertexType::VERTEX_16BIT.bits());
+    // 3 `i16` components, padded to 4 bytes
+    assert_eq!(file.stride, 8);
+
+    let vertices = file.vertices::<[i16; 4]>(VertexType::VERTEX_16BIT).unwrap();
+    for (vertex, position) in vertices.iter().zip(&mesh.positions) {
+        for axis in 0..3 {
+            // The GE normalizes 16-bit positions, the transform restores the range
+            let normalized = vertex[axis] as f32 / 32767.;
+            let decoded = normalized * file.position_scale[axis] + file.position_offset[axis];
+            assert!(
+                (decoded - position[axis]).abs() < 1e-4,
+                "{decoded} {position:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn layout_mismatch() {
+    let file = ModelFile::parse(&write_model(&quad(), &LAYOUT).unwrap()).unwrap();
+    let other = VertexType::TEXTURE_32BITF | VertexType::COLOR_8888 | VertexType::VERTEX_32BITF;
+    assert_eq!(
+        file.vertices::<Vertex>(other).err(),
+        Some(ModelError::LayoutMismatch)
+    );
+    assert_eq!(
+        file.vertices::<[u32; 8]>(vertex_type()).err(),
+        Some(ModelError::LayoutMismatch)
+    );
+}
+
+#[test]
+fn truncated() {
+    let data = write_model(&quad(), &LAYOUT).unwrap();
+    for len in 0..data.len() {
+        assert_eq!(
+            parse_error(&data[..len]),
+            Some(ModelError::UnexpectedEof),
+            "{len}"
+        );
+    }
+}
+
+#[test]
+fn invalid_header() {
+    let data = write_model(&quad(), &LAYOUT).unwrap();
+
+    let mut bad = data.clone();
+    bad[0] = b'X';
+    assert_eq!(parse_error(&bad), Some(ModelError::InvalidMagic));
+
+    let mut bad = data.clone();
+    set_u32(&mut bad, 4, 2);
+    assert_eq!(parse_error(&bad), Some(ModelError::UnsupportedVersion(2)));
+
+    // Unknown vertex type bits, zero stride and unknown primitive
+    for (offset, value) in [(8, 1 << 30), (12, 0), (16, 7)] {
+        let mut bad = data.clone();
+        set_u32(&mut bad, offset, value);
+        assert_eq!(
+            parse_error(&bad),
+            Some(ModelError::InvalidValue),
+            "{offset}"
+        );
+    }
+
+    let mut bad = data.clone();
+    bad[76 + 2] = 0xff;
+    assert_eq!(parse_error(&bad), Some(ModelError::InvalidUtf8));
+}
+
+#[test]
+fn out_of_range_submeshes() {
+    let data = write_model(&quad(), &LAYOUT).unwrap();
+
+    // Second submesh ends past the 6 indices
+    let mut bad = data.clone();
+    set_u32(&mut bad, SUBMESHES + 16 + 4, 4);
+    assert_eq!(parse_error(&bad), Some(ModelError::InvalidValue));
+
+    // Start + count overflows
+    let mut bad = data.clone();
+    set_u32(&mut bad, SUBMESHES + 16, u32::MAX);
+    assert_eq!(parse_error(&bad), Some(ModelError::InvalidValue));
+
+    // Only one texture
+    let mut bad = data.clone();
+    set_u32(&mut bad, SUBMESHES + 12, 1);
+    assert_eq!(parse_error(&bad), Some(ModelError::InvalidValue));
+}
+
+#[test]
+fn out_of_range_indices() {
+    let mut data = write_model(&quad(), &LAYOUT).unwrap();
+    // Last index refers to vertex 4 of 4
+    let len = data.len();
+    data[len - 2..].copy_from_slice(&4u16.to_le_bytes());
+    assert_eq!(parse_error(&data), Some(ModelError::InvalidValue));
+}
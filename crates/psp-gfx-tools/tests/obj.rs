@@ -0,0 +1,143 @@
+//! Wavefront OBJ importer
+
+use psp_gfx_tools::{
+    obj::parse,
+    scene::{MaterialData, SubmeshData},
+};
+
+fn no_files(_: &str) -> Option<String> {
+    None
+}
+
+#[test]
+fn polygons_are_fans() {
+    let text = "
+        v 0 0 0
+        v 1 0 0
+        v 1 1 0
+        v 0 1 0
+        v -1 1 0
+        f 1 2 3 4 5
+        f 1 2 3
+    ";
+    let mesh = parse(text, no_files).unwrap();
+    assert_eq!(mesh.positions.len(), 5);
+    assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 1, 2]);
+    // Faces before `usemtl` use one default material
+    assert_eq!(
+        mesh.submeshes,
+        [SubmeshData {
+            start: 0,
+            count: 12,
+            material: 0
+        }]
+    );
+    assert_eq!(mesh.materials, [MaterialData::default()]);
+}
+
+#[test]
+fn negative_indices() {
+    let text = "
+        v 0 0 0
+        v 1 0 0
+        vt 0 0
+        v 0 1 0
+        vt 1 1
+        vn 0 0 1
+        f -3/-2/-1 -2/-1/-1 -1/-1/-1
+    ";
+    let mesh = parse(text, no_files).unwrap();
+    // Relative to the end of each list when the face is read
+    assert_eq!(mesh.positions, [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]);
+    // Texture coordinates are flipped vertically
+    assert_eq!(mesh.uvs, [[0., 1.], [1., 0.], [1., 0.]]);
+    assert_eq!(mesh.normals, [[0., 0., 1.]; 3]);
+
+    assert!(parse("v 0 0 0\nf -2 -1 -1", no_files).is_err());
+    assert!(parse("v 0 0 0\nf 0 1 1", no_files).is_err());
+    assert!(parse("v 0 0 0\nf 1 1 2", no_files).is_err());
+}
+
+#[test]
+fn missing_attributes() {
+    let positions = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    let mesh = parse(&format!("{positions}f 1 2 3"), no_files).unwrap();
+    assert!(mesh.uvs.is_empty());
+    assert!(mesh.normals.is_empty());
+
+    // `v//vn` skips the texture coordinates
+    let text = format!("{positions}vn 0 0 1\nf 1//1 2//1 3//1");
+    let mesh = parse(&text, no_files).unwrap();
+    assert!(mesh.uvs.is_empty());
+    assert_eq!(mesh.normals, [[0., 0., 1.]; 3]);
+
+    // Vertices without texture coordinates get zeros when other vertices have them
+    let text = format!("{positions}vt 0.5 0.25\nf 1/1 2/1 3\n");
+    let mesh = parse(&text, no_files).unwrap();
+    assert_eq!(mesh.uvs, [[0.5, 0.75], [0.5, 0.75], [0., 0.]]);
+    assert!(mesh.normals.is_empty());
+}
+
+#[test]
+fn shared_vertices() {
+    // The same position with different texture coordinates is split
+    let text = "
+        v 0 0 0
+        v 1 0 0
+        v 0 1 0
+        vt 0 0
+        vt 1 0
+        f 1/1 2/1 3/1
+        f 1/2 3/1 2/1
+    ";
+    let mesh = parse(text, no_files).unwrap();
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.indices, [0, 1, 2, 3, 2, 1]);
+}
+
+#[test]
+fn materials() {
+    let mtl = "
+        newmtl red
+        Kd 1 0 0
+        d 0.5
+        newmtl textured
+        map_Kd -s 1 1 1 wood.png
+    ";
+    let text = "
+        mtllib scene.mtl
+        v 0 0 0
+        v 1 0 0
+        v 0 1 0
+        usemtl red
+        f 1 2 3
+        f 1 3 2
+        usemtl textured
+        f 1 2 3
+        usemtl red
+        f 2 1 3
+    ";
+    let load_file = |name: &str| (name == "scene.mtl").then(|| mtl.to_owned());
+    let mesh = parse(text, load_file).unwrap();
+    let submeshes: Vec<_> = mesh
+        .submeshes
+        .iter()
+        .map(|submesh| (submesh.start, submesh.count, submesh.material))
+        .collect();
+    assert_eq!(submeshes, [(0, 6, 0), (6, 3, 1), (9, 3, 0)]);
+    assert_eq!(mesh.materials[0].color, [1., 0., 0., 0.5]);
+    assert_eq!(mesh.materials[1].texture.as_deref(), Some("wood.png"));
+
+    assert!(parse(text, no_files).is_err());
+}
+
+#[test]
+fn malformed() {
+    assert!(parse("v 0 0", no_files).is_err());
+    assert!(parse("v 0 0 x", no_files).is_err());
+    assert!(parse("v 0 0 0\nv 1 0 0\nf 1 2", no_files).is_err());
+    assert!(parse("v 0 0 0\nf 1/x 1 1", no_files).is_err());
+    // Comments and unknown statements are ignored
+    assert!(parse("# comment\no object\ns off\ng group", no_files).is_ok());
+}
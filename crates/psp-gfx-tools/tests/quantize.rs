@@ -0,0 +1,106 @@
+//! Vertex quantization shared with `psp_gfx::quantize`
+
+use psp_gfx_tools::quantize::{Fixed, Quantizer, quantize_normal, quantize_raw};
+
+/// Values spread over a range, including both ends
+fn samples(min: [f32; 3], max: [f32; 3]) -> impl Iterator<Item = [f32; 3]> + Clone {
+    (0..=100).map(move |step| {
+        let t = step as f32 / 100.;
+        core::array::from_fn(|idx| min[idx] + (max[idx] - min[idx]) * t)
+    })
+}
+
+fn assert_round_trip<T: Fixed>() {
+    let (min, max) = ([-3.5, 0., 10.], [12.25, 0.001, 4000.]);
+    let quantizer = Quantizer::<T, 3>::from_values(samples(min, max));
+    let max_error = quantizer.max_error();
+    for value in samples(min, max) {
+        let restored = quantizer.dequantize(quantizer.quantize(value));
+        for idx in 0..3 {
+            let error = (restored[idx] - value[idx]).abs();
+            // Allow for float rounding on top of the quantization step
+            let tolerance = max_error[idx] * 1.001 + value[idx].abs() * f32::EPSILON * 4.;
+            assert!(
+                error <= tolerance,
+                "{} of {value:?} restored as {restored:?}, max error {max_error:?}",
+                core::any::type_name::<T>()
+            );
+        }
+    }
+}
+
+#[test]
+fn round_trip_i8() {
+    assert_round_trip::<i8>();
+}
+
+#[test]
+fn round_trip_i16() {
+    assert_round_trip::<i16>();
+}
+
+#[test]
+fn round_trip_u8() {
+    assert_round_trip::<u8>();
+}
+
+#[test]
+fn round_trip_u16() {
+    assert_round_trip::<u16>();
+}
+
+#[test]
+fn bounds_map_to_the_full_range() {
+    let signed = Quantizer::<i8, 1>::from_bounds([-2.], [6.]);
+    assert_eq!(signed.quantize([-2.]), [-127]);
+    assert_eq!(signed.quantize([2.]), [0]);
+    assert_eq!(signed.quantize([6.]), [127]);
+    let unsigned = Quantizer::<u16, 1>::from_bounds([-2.], [6.]);
+    assert_eq!(unsigned.quantize([-2.]), [0]);
+    assert_eq!(unsigned.quantize([6.]), [u16::MAX]);
+    // Values outside of the range saturate
+    assert_eq!(signed.quantize([100.]), [127]);
+    assert_eq!(unsigned.quantize([-100.]), [0]);
+}
+
+#[test]
+fn degenerate_ranges() {
+    // A flat axis and no values at all still give a usable quantizer
+    let quantizer = Quantizer::<i16, 2>::from_values([[1., 5.], [3., 5.]]);
+    assert_eq!(quantizer.dequantize(quantizer.quantize([1., 5.])), [1., 5.]);
+    assert!(quantizer.scale()[1] > 0.);
+    let empty = Quantizer::<u8, 2>::from_values([]);
+    assert_eq!(empty.offset(), [0., 0.]);
+    assert!(empty.scale().iter().all(|&scale| scale > 0.));
+}
+
+#[test]
+fn normals() {
+    assert_eq!(quantize_normal::<i8>([1., -1., 0.]), [127, -128, 0]);
+    assert_eq!(quantize_normal::<i8>([2., -0.5, 0.25]), [127, -64, 32]);
+    assert_eq!(
+        quantize_normal::<i16>([0.5, 1., -1.]),
+        [16384, 32767, -32768]
+    );
+}
+
+#[test]
+fn raw_values() {
+    assert_eq!(quantize_raw::<u16, 2>([10.4, 479.6]), [10, 480]);
+    assert_eq!(quantize_raw::<i8, 2>([-200., 200.]), [-128, 127]);
+    assert_eq!(i16::from_f32(-1.5).to_f32(), -2.);
+    assert_eq!(u8::from_f32(127.).to_normalized(), 127. / 128.);
+}
+
+#[test]
+fn transforms() {
+    let quantizer = Quantizer::<i16, 3>::new([2., 3., 4.], [5., 6., 7.]);
+    let matrix = quantizer.matrix();
+    assert_eq!([matrix.x.x, matrix.y.y, matrix.z.z], [2., 3., 4.]);
+    assert_eq!(
+        [matrix.w.x, matrix.w.y, matrix.w.z, matrix.w.w],
+        [5., 6., 7., 1.]
+    );
+    let uv = Quantizer::<u8, 2>::new([0.5, 0.25], [1., 2.]);
+    assert_eq!(uv.uv_transform(), ((0.5, 0.25), (1., 2.)));
+}
@@ -0,0 +1,161 @@
+//! Rectangle geometry shared with `psp_gfx::rect`
+
+use psp_gfx_tools::rect::{HorizontalAlign, Point, Rect, Size, VerticalAlign};
+
+#[test]
+fn edges_are_exclusive() {
+    let rect = Rect::new(10, 20, 30, 40);
+    assert_eq!((rect.right(), rect.bottom()), (40, 60));
+    assert!(rect.contains(Point::new(10, 20)));
+    assert!(rect.contains(Point::new(39, 59)));
+    assert!(!rect.contains(Point::new(40, 59)));
+    assert!(!rect.contains(Point::new(39, 60)));
+    assert!(!Rect::new(0, 0, 0, 10).contains(Point::ZERO));
+}
+
+#[test]
+fn from_corners() {
+    let rect = Rect::from_corners(Point::new(5, 1), Point::new(-3, 9));
+    assert_eq!(rect, Rect::new(-3, 1, 8, 8));
+    assert_eq!(
+        Rect::from_point_size(Point::new(-3, 1), Size::new(8, 8)),
+        rect
+    );
+    assert_eq!(rect.center(), Point::new(1, 5));
+}
+
+#[test]
+fn intersection() {
+    let a = Rect::new(0, 0, 10, 10);
+    assert_eq!(
+        a.intersection(&Rect::new(5, -5, 10, 10)),
+        Some(Rect::new(5, 0, 5, 5))
+    );
+    assert_eq!(
+        a.intersection(&Rect::new(2, 3, 4, 5)),
+        Some(Rect::new(2, 3, 4, 5))
+    );
+    // Touching edges don't overlap
+    assert_eq!(a.intersection(&Rect::new(10, 0, 5, 5)), None);
+    assert_eq!(a.intersection(&Rect::new(0, 10, 5, 5)), None);
+    // Empty rectangles never intersect, even when inside
+    assert_eq!(a.intersection(&Rect::new(5, 5, 0, 3)), None);
+    assert_eq!(a.intersection(&Rect::new(5, 5, 3, -1)), None);
+    assert!(!Rect::new(5, 5, 0, 0).intersects(&a));
+}
+
+#[test]
+fn union() {
+    let a = Rect::new(0, 0, 10, 10);
+    assert_eq!(a.union(&Rect::new(20, -5, 5, 5)), Rect::new(0, -5, 25, 15));
+    assert_eq!(a.union(&Rect::new(2, 2, 2, 2)), a);
+    // Empty rectangles are ignored, wherever they are
+    assert_eq!(a.union(&Rect::new(100, 100, 0, 5)), a);
+    assert_eq!(Rect::new(-50, -50, 5, -1).union(&a), a);
+    let empty = Rect::new(3, 3, 0, 0);
+    assert_eq!(empty.union(&empty), empty);
+}
+
+#[test]
+fn contains_rect() {
+    let a = Rect::new(0, 0, 10, 10);
+    assert!(a.contains_rect(&a));
+    assert!(a.contains_rect(&Rect::new(9, 9, 1, 1)));
+    assert!(!a.contains_rect(&Rect::new(9, 9, 2, 1)));
+    assert!(!a.contains_rect(&Rect::new(-1, 0, 2, 2)));
+}
+
+#[test]
+fn inset_and_outset() {
+    let a = Rect::new(0, 0, 10, 6);
+    assert_eq!(a.inset(2, 1), Rect::new(2, 1, 6, 4));
+    assert_eq!(a.outset(2, 1), Rect::new(-2, -1, 14, 8));
+    // The size is clamped, the position still moves
+    assert_eq!(a.inset(4, 4), Rect::new(4, 4, 2, 0));
+}
+
+#[test]
+fn split_left() {
+    let a = Rect::new(10, 20, 30, 40);
+    assert_eq!(
+        a.split_left(12),
+        (Rect::new(10, 20, 12, 40), Rect::new(22, 20, 18, 40))
+    );
+    assert_eq!(
+        a.split_right(12),
+        (Rect::new(10, 20, 18, 40), Rect::new(28, 20, 12, 40))
+    );
+    // Split positions are clamped to the rectangle
+    assert_eq!(
+        a.split_left(-5),
+        (Rect::new(10, 20, 0, 40), Rect::new(10, 20, 30, 40))
+    );
+    assert_eq!(
+        a.split_left(100),
+        (Rect::new(10, 20, 30, 40), Rect::new(40, 20, 0, 40))
+    );
+    assert_eq!(
+        a.split_right(100),
+        (Rect::new(10, 20, 0, 40), Rect::new(10, 20, 30, 40))
+    );
+    // Negative widths split into two empty rectangles at the left edge
+    assert_eq!(
+        Rect::new(0, 0, -4, 5).split_left(2),
+        (Rect::new(0, 0, 0, 5), Rect::new(0, 0, -4, 5))
+    );
+}
+
+#[test]
+fn split_top() {
+    let a = Rect::new(10, 20, 30, 40);
+    assert_eq!(
+        a.split_top(15),
+        (Rect::new(10, 20, 30, 15), Rect::new(10, 35, 30, 25))
+    );
+    assert_eq!(
+        a.split_bottom(15),
+        (Rect::new(10, 20, 30, 25), Rect::new(10, 45, 30, 15))
+    );
+    assert_eq!(
+        a.split_top(-1),
+        (Rect::new(10, 20, 30, 0), Rect::new(10, 20, 30, 40))
+    );
+    assert_eq!(
+        a.split_bottom(41),
+        (Rect::new(10, 20, 30, 0), Rect::new(10, 20, 30, 40))
+    );
+}
+
+#[test]
+fn align() {
+    let a = Rect::new(0, 0, 100, 50);
+    let size = Size::new(20, 10);
+    assert_eq!(
+        a.align(size, HorizontalAlign::Left, VerticalAlign::Top),
+        Rect::new(0, 0, 20, 10)
+    );
+    assert_eq!(
+        a.align(size, HorizontalAlign::Center, VerticalAlign::Middle),
+        Rect::new(40, 20, 20, 10)
+    );
+    assert_eq!(
+        a.align(size, HorizontalAlign::Right, VerticalAlign::Bottom),
+        Rect::new(80, 40, 20, 10)
+    );
+    // Larger areas extend outside of the rectangle
+    assert_eq!(
+        a.align(
+            Size::new(120, 10),
+            HorizontalAlign::Center,
+            VerticalAlign::Top
+        ),
+        Rect::new(-10, 0, 120, 10)
+    );
+}
+
+#[test]
+fn points() {
+    assert_eq!(Point::new(1, 2).offset(-3, 4), Point::new(-2, 6));
+    assert_eq!(Point::<f32>::from(Point::new(1, -2)), Point::new(1., -2.));
+    assert_eq!(Point::new(0.5, 1.).offset(0.25, -1.), Point::new(0.75, 0.));
+}
@@ -0,0 +1,218 @@
+//! Framebuffer conversion and encoding shared with `psp_gfx::screenshot`
+
+use psp_gfx_tools::{
+    ge::sys::DisplayPixelFormat,
+    screenshot::{Framebuffer, bytes_per_pixel, pixel_color},
+};
+
+fn framebuffer(width: u32, height: u32, format: DisplayPixelFormat, pixels: &[u32]) -> Framebuffer {
+    let bpp = bytes_per_pixel(format);
+    let data: Vec<u8> = pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_le_bytes()[..bpp].to_vec())
+        .collect();
+    Framebuffer::from_raw(width, height, width, format, &data)
+}
+
+fn u16_le(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn u32_be(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// Split a PNG file into its chunks, checking the signature
+fn png_chunks(png: &[u8]) -> Vec<([u8; 4], &[u8], u32)> {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let mut chunks = Vec::new();
+    let mut pos = 8;
+    while pos < png.len() {
+        let len = u32_be(png, pos) as usize;
+        let ty = png[pos + 4..pos + 8].try_into().unwrap();
+        let data = &png[pos + 8..pos + 8 + len];
+        let crc = u32_be(png, pos + 8 + len);
+        chunks.push((ty, data, crc));
+        pos += 12 + len;
+    }
+    assert_eq!(pos, png.len());
+    chunks
+}
+
+/// Decode a zlib stream of stored deflate blocks
+fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+    assert_eq!(&zlib[..2], [0x78, 0x01]);
+    assert_eq!(u16::from_be_bytes([zlib[0], zlib[1]]) % 31, 0);
+    let mut out = Vec::new();
+    let mut pos = 2;
+    loop {
+        let header = zlib[pos];
+        assert_eq!(header & !1, 0, "only stored blocks are expected");
+        let len = u16_le(zlib, pos + 1);
+        assert_eq!(!len, u16_le(zlib, pos + 3));
+        out.extend_from_slice(&zlib[pos + 5..pos + 5 + len as usize]);
+        pos += 5 + len as usize;
+        if header & 1 != 0 {
+            break;
+        }
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in &out {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    assert_eq!(u32_be(zlib, pos), (b << 16) | a, "adler32 mismatch");
+    assert_eq!(pos + 4, zlib.len());
+    out
+}
+
+#[test]
+fn formats() {
+    assert_eq!(bytes_per_pixel(DisplayPixelFormat::Psm5650), 2);
+    assert_eq!(bytes_per_pixel(DisplayPixelFormat::Psm4444), 2);
+    assert_eq!(bytes_per_pixel(DisplayPixelFormat::Psm8888), 4);
+}
+
+#[test]
+fn rgb565() {
+    let rgba =
+        |pixel: u16| pixel_color(DisplayPixelFormat::Psm5650, &pixel.to_le_bytes()).to_array();
+    assert_eq!(rgba(0x001f), [255, 0, 0, 255]);
+    assert_eq!(rgba(0x07e0), [0, 255, 0, 255]);
+    assert_eq!(rgba(0xf800), [0, 0, 255, 255]);
+    assert_eq!(rgba(0x0000), [0, 0, 0, 255]);
+    // 16/31 and 32/63 of the range, rounded to the nearest value
+    assert_eq!(rgba((16 << 11) | (32 << 5) | 16), [132, 130, 132, 255]);
+}
+
+#[test]
+fn rgba5551() {
+    let rgba =
+        |pixel: u16| pixel_color(DisplayPixelFormat::Psm5551, &pixel.to_le_bytes()).to_array();
+    assert_eq!(rgba(0x801f), [255, 0, 0, 255]);
+    assert_eq!(rgba(0x03e0), [0, 255, 0, 0]);
+    assert_eq!(rgba(0x7c00), [0, 0, 255, 0]);
+    assert_eq!(rgba(0xffff), [255; 4]);
+}
+
+#[test]
+fn rgba4444() {
+    let rgba =
+        |pixel: u16| pixel_color(DisplayPixelFormat::Psm4444, &pixel.to_le_bytes()).to_array();
+    assert_eq!(rgba(0x4321), [17, 34, 51, 68]);
+    assert_eq!(rgba(0xf00f), [255, 0, 0, 255]);
+    assert_eq!(rgba(0x0f00), [0, 0, 255, 0]);
+}
+
+#[test]
+fn rgba8888() {
+    assert_eq!(
+        pixel_color(DisplayPixelFormat::Psm8888, &[1, 2, 3, 4]).to_array(),
+        [1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn from_raw_drops_the_stride() {
+    // 2x2 16-bit pixels in rows of 4 pixels, the stride padding is dropped
+    let data: Vec<u8> = (0..16).collect();
+    let framebuffer = Framebuffer::from_raw(2, 2, 4, DisplayPixelFormat::Psm5650, &data);
+    assert_eq!(framebuffer.data, [0, 1, 2, 3, 8, 9, 10, 11]);
+    // The last row doesn't need the padding
+    let framebuffer = Framebuffer::from_raw(2, 2, 4, DisplayPixelFormat::Psm5650, &data[..12]);
+    assert_eq!(framebuffer.data.len(), 8);
+}
+
+#[test]
+fn bmp() {
+    // 3x2 pixels, red green blue on top of white black white
+    let pixels = [
+        0x0000_00ff,
+        0x0000_ff00,
+        0x00ff_0000,
+        0xffff_ffff,
+        0xff00_0000,
+        0x00ff_ffff,
+    ];
+    let bmp = framebuffer(3, 2, DisplayPixelFormat::Psm8888, &pixels).encode_bmp();
+    // Rows of 9 bytes are padded to 12
+    assert_eq!(bmp.len(), 54 + 2 * 12);
+    assert_eq!(&bmp[..2], b"BM");
+    assert_eq!(u32_le(&bmp, 2), bmp.len() as u32);
+    assert_eq!(u32_le(&bmp, 10), 54);
+    assert_eq!(u32_le(&bmp, 14), 40);
+    assert_eq!((u32_le(&bmp, 18), u32_le(&bmp, 22)), (3, 2));
+    assert_eq!((u16_le(&bmp, 26), u16_le(&bmp, 28)), (1, 24));
+    assert_eq!(u32_le(&bmp, 30), 0);
+    assert_eq!(u32_le(&bmp, 34), 24);
+    // Bottom row first, in BGR order, alpha is dropped
+    assert_eq!(
+        &bmp[54..],
+        [
+            255, 255, 255, 0, 0, 0, 255, 255, 255, 0, 0, 0, //
+            0, 0, 255, 0, 255, 0, 255, 0, 0, 0, 0, 0,
+        ]
+    );
+}
+
+#[test]
+fn bmp_converts_16_bit_pixels() {
+    let bmp = framebuffer(1, 1, DisplayPixelFormat::Psm5650, &[0x001f]).encode_bmp();
+    assert_eq!(bmp.len(), 54 + 4);
+    assert_eq!(&bmp[54..], [0, 0, 255, 0]);
+}
+
+#[test]
+fn png() {
+    let pixels = [0x0000_f800, 0x0000_07e0];
+    let png = framebuffer(1, 2, DisplayPixelFormat::Psm5650, &pixels).encode_png();
+    let chunks = png_chunks(&png);
+    let types: Vec<_> = chunks.iter().map(|(ty, ..)| ty).collect();
+    assert_eq!(types, [b"IHDR", b"IDAT", b"IEND"]);
+
+    let (_, header, _) = chunks[0];
+    assert_eq!(header.len(), 13);
+    assert_eq!((u32_be(header, 0), u32_be(header, 4)), (1, 2));
+    // 8-bit RGB, deflate, no filter, no interlace
+    assert_eq!(&header[8..], [8, 2, 0, 0, 0]);
+
+    // Each row starts with filter type none
+    let (_, data, _) = chunks[1];
+    assert_eq!(inflate_stored(data), [0, 0, 0, 255, 0, 0, 255, 0]);
+
+    // The IEND chunk is the same in every file
+    assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+}
+
+#[test]
+fn png_crc() {
+    // Header of a 1x1 RGB image as written by other encoders
+    let png = framebuffer(1, 1, DisplayPixelFormat::Psm8888, &[0]).encode_png();
+    assert_eq!(
+        &png[8..33],
+        b"\0\0\0\x0dIHDR\0\0\0\x01\0\0\0\x01\x08\x02\0\0\0\x90\x77\x53\xde"
+    );
+}
+
+#[test]
+fn png_splits_stored_blocks() {
+    // 200 rows of 1 + 200 * 3 bytes need two stored blocks
+    let pixels: Vec<u32> = (0..200 * 200).map(|idx| idx * 0x010203).collect();
+    let framebuffer = framebuffer(200, 200, DisplayPixelFormat::Psm8888, &pixels);
+    let png = framebuffer.encode_png();
+    let (_, data, _) = png_chunks(&png)[1];
+    let raw = inflate_stored(data);
+    assert_eq!(raw.len(), 200 * 601);
+    for (row, pixels) in raw.chunks_exact(601).zip(pixels.chunks_exact(200)) {
+        assert_eq!(row[0], 0);
+        let expected: Vec<u8> = pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_le_bytes()[..3].to_vec())
+            .collect();
+        assert_eq!(row[1..], expected);
+    }
+}
@@ -0,0 +1,143 @@
+//! Shape tessellation shared with `psp_gfx::gfx_ext::shapes`
+
+use psp_gfx_tools::{ge::sys::GuPrimitive, rect::Point, shapes};
+
+fn assert_near(actual: Point<f32>, expected: Point<f32>) {
+    assert!(
+        (actual.x - expected.x).abs() < 1e-4 && (actual.y - expected.y).abs() < 1e-4,
+        "{actual:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn segments_for_radius() {
+    assert_eq!(shapes::segments_for_radius(0.), 8);
+    assert_eq!(shapes::segments_for_radius(10.), 16);
+    assert_eq!(shapes::segments_for_radius(-10.), 16);
+    assert_eq!(shapes::segments_for_radius(1000.), 64);
+}
+
+#[test]
+fn thin_line() {
+    let shape = shapes::line(Point::new(1., 2.), Point::new(3., 4.), 1.);
+    assert_eq!(shape.primitive, GuPrimitive::Lines);
+    assert_eq!(shape.points, [Point::new(1., 2.), Point::new(3., 4.)]);
+}
+
+#[test]
+fn thick_line() {
+    let shape = shapes::line(Point::new(0., 0.), Point::new(10., 0.), 4.);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleStrip);
+    assert_eq!(
+        shape.points,
+        [
+            Point::new(0., 2.),
+            Point::new(0., -2.),
+            Point::new(10., 2.),
+            Point::new(10., -2.),
+        ]
+    );
+    // Zero length lines don't divide by zero
+    let shape = shapes::line(Point::new(5., 5.), Point::new(5., 5.), 4.);
+    assert!(
+        shape
+            .points
+            .iter()
+            .all(|&point| point == Point::new(5., 5.))
+    );
+}
+
+#[test]
+fn polyline() {
+    let points = [
+        Point::new(0., 0.),
+        Point::new(10., 0.),
+        Point::new(10., 10.),
+    ];
+    let shape = shapes::polyline(&points, 1., true);
+    assert_eq!(shape.primitive, GuPrimitive::LineStrip);
+    assert_eq!(shape.points.len(), 4);
+    assert_eq!(shape.points[3], points[0]);
+
+    // Two triangles per segment, closing adds a segment
+    let shape = shapes::polyline(&points, 2., false);
+    assert_eq!(shape.primitive, GuPrimitive::Triangles);
+    assert_eq!(shape.points.len(), 12);
+    assert_eq!(shapes::polyline(&points, 2., true).points.len(), 18);
+    assert!(shapes::polyline(&[], 2., true).points.is_empty());
+}
+
+#[test]
+fn ellipse() {
+    let center = Point::new(100., 50.);
+    let shape = shapes::ellipse(center, 20., 10.);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleFan);
+    // Center and a closed ring of points
+    let segments = shapes::segments_for_radius(20.);
+    assert_eq!(shape.points.len(), segments + 2);
+    assert_eq!(shape.points[0], center);
+    assert_near(shape.points[1], Point::new(120., 50.));
+    assert_near(shape.points[segments + 1], Point::new(120., 50.));
+    // Clockwise with Y pointing down, a quarter turn later is below the center
+    assert_near(shape.points[1 + segments / 4], Point::new(100., 60.));
+}
+
+#[test]
+fn arc_and_pie() {
+    let center = Point::new(0., 0.);
+    let quarter = core::f32::consts::FRAC_PI_2;
+    let segments = shapes::segments_for_radius(10.).div_ceil(4);
+
+    let shape = shapes::arc(center, 10., 0., quarter, 1.);
+    assert_eq!(shape.primitive, GuPrimitive::LineStrip);
+    assert_eq!(shape.points.len(), segments + 1);
+    assert_near(shape.points[0], Point::new(10., 0.));
+    assert_near(shape.points[segments], Point::new(0., 10.));
+
+    // Outer and inner points alternate, without closing the ring
+    let shape = shapes::arc(center, 10., 0., quarter, 4.);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleStrip);
+    assert_eq!(shape.points.len(), 2 * (segments + 1));
+    assert_near(shape.points[0], Point::new(12., 0.));
+    assert_near(shape.points[1], Point::new(8., 0.));
+
+    let shape = shapes::pie(center, 10., 0., quarter);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleFan);
+    assert_eq!(shape.points.len(), segments + 2);
+    assert_eq!(shape.points[0], center);
+}
+
+#[test]
+fn rect_outline() {
+    let shape = shapes::rect_outline(0., 0., 10., 6., 1.);
+    assert_eq!(shape.primitive, GuPrimitive::LineStrip);
+    assert_eq!(shape.points.len(), 5);
+    assert_eq!(shape.points[0], shape.points[4]);
+
+    let shape = shapes::rect_outline(0., 0., 10., 6., 2.);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleStrip);
+    assert_eq!(shape.points.len(), 10);
+    assert_eq!(shape.points[0], Point::new(0., 0.));
+    assert_eq!(shape.points[1], Point::new(2., 2.));
+    assert_eq!(shape.points[5], Point::new(8., 4.));
+    // The thickness is clamped to half of the smaller side
+    let shape = shapes::rect_outline(0., 0., 10., 6., 100.);
+    assert_eq!(shape.points[1], Point::new(3., 3.));
+}
+
+#[test]
+fn rounded_rect() {
+    let segments = (shapes::segments_for_radius(4.) / 4).max(1);
+    let shape = shapes::rounded_rect(0., 0., 20., 10., 4.);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleFan);
+    // Center, four corners and the first perimeter point again
+    assert_eq!(shape.points.len(), 4 * (segments + 1) + 2);
+    assert_eq!(shape.points[0], Point::new(10., 5.));
+    assert_near(shape.points[1], Point::new(16., 0.));
+    assert_eq!(shape.points[1], *shape.points.last().unwrap());
+
+    let shape = shapes::rounded_rect_outline(0., 0., 20., 10., 4., 2.);
+    assert_eq!(shape.primitive, GuPrimitive::TriangleStrip);
+    assert_eq!(shape.points.len(), 2 * 4 * (segments + 1) + 2);
+    assert_near(shape.points[1], Point::new(16., 2.));
+}
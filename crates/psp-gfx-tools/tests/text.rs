@@ -0,0 +1,251 @@
+//! BMFont parsing and text layout shared with `psp_gfx::text`
+
+use psp_gfx_tools::{
+    rect::Rect,
+    text::{
+        Font, Glyph, Line,
+        bmfont::{BmFontError, BmFontFile},
+        layout, measure,
+    },
+};
+
+/// Character id, position, size, offset, advance and page of a `char` entry
+type CharEntry = (i32, [i32; 4], [i32; 2], i32, u8);
+
+/// Letters are 4 pixels wide, spaces 2, `A` and `V` 6 with a kerning pair, and a missing
+/// glyph on the second page
+fn chars() -> Vec<CharEntry> {
+    let mut chars = vec![
+        (-1, [60, 0, 4, 8], [0, 0], 5, 1),
+        (' ' as i32, [0, 0, 0, 0], [0, 0], 2, 0),
+        ('A' as i32, [0, 8, 6, 8], [-1, 2], 6, 0),
+        ('V' as i32, [8, 8, 6, 8], [0, 2], 6, 0),
+    ];
+    for (idx, ch) in ('a'..='z').enumerate() {
+        chars.push((ch as i32, [idx as i32 * 4, 0, 4, 8], [0, 1], 4, 0));
+    }
+    chars
+}
+
+const KERNING: [(i32, i32, i32); 2] = [('A' as i32, 'V' as i32, -2), (-1, 'a' as i32, 1)];
+
+fn text_descriptor(chars: &[CharEntry]) -> String {
+    let mut text = String::from(
+        "info face=\"Test Font\" size=8 bold=0 padding=0,0,0,0 spacing=1,1\n\
+         common lineHeight=10 base=8 scaleW=128 scaleH=128 pages=2 packed=0\n\
+         page id=0 file=\"page 0.png\"\n\
+         page id=1 file=\"page1.png\"\n",
+    );
+    text += &format!("chars count={}\n", chars.len());
+    for (id, [x, y, w, h], [ox, oy], advance, page) in chars {
+        text += &format!(
+            "char id={id:<4} x={x} y={y} width={w} height={h} xoffset={ox} yoffset={oy} \
+             xadvance={advance} page={page} chnl=15\n"
+        );
+    }
+    for (first, second, amount) in KERNING {
+        text += &format!("kerning first={first} second={second} amount={amount}\n");
+    }
+    text
+}
+
+fn binary_descriptor(chars: &[CharEntry]) -> Vec<u8> {
+    fn block(out: &mut Vec<u8>, block_type: u8, data: &[u8]) {
+        out.push(block_type);
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+    }
+    let u16 = |value: i32| (value as u16).to_le_bytes();
+
+    let mut out = b"BMF\x03".to_vec();
+    let mut info = vec![8, 0, 0, 100, 0, 1, 0, 0, 0, 0, 1, 1, 0];
+    info.extend_from_slice(b"Test Font\0");
+    block(&mut out, 1, &info);
+    let mut common = [10, 8, 128, 128, 2].map(u16).concat();
+    common.extend_from_slice(&[0, 0, 0, 0, 0]);
+    block(&mut out, 2, &common);
+    block(&mut out, 3, b"page 0.png\0page1.png\0");
+    let mut entries = Vec::new();
+    for &(id, [x, y, w, h], [ox, oy], advance, page) in chars {
+        entries.extend_from_slice(&(id as u32).to_le_bytes());
+        for value in [x, y, w, h, ox, oy, advance] {
+            entries.extend_from_slice(&u16(value));
+        }
+        entries.extend_from_slice(&[page, 15]);
+    }
+    block(&mut out, 4, &entries);
+    let mut pairs = Vec::new();
+    for (first, second, amount) in KERNING {
+        pairs.extend_from_slice(&(first as u32).to_le_bytes());
+        pairs.extend_from_slice(&(second as u32).to_le_bytes());
+        pairs.extend_from_slice(&u16(amount));
+    }
+    block(&mut out, 5, &pairs);
+    out
+}
+
+fn font() -> BmFontFile {
+    BmFontFile::parse(text_descriptor(&chars()).as_bytes()).unwrap()
+}
+
+fn parse_error(data: &[u8]) -> Option<BmFontError> {
+    BmFontFile::parse(data).err()
+}
+
+fn check_font(font: &BmFontFile) {
+    assert_eq!((font.line_height, font.base), (10, 8));
+    assert_eq!(font.page_files, ["page 0.png", "page1.png"]);
+    assert_eq!(font.glyphs.len(), 29);
+    assert_eq!(
+        font.glyph('A'),
+        Some(Glyph {
+            page: 0,
+            src: Rect::new(0, 8, 6, 8),
+            offset_x: -1,
+            offset_y: 2,
+            advance: 6,
+        })
+    );
+    assert_eq!(font.glyph('z').unwrap().src, Rect::new(100, 0, 4, 8));
+
+    // Characters without a glyph use the `id=-1` glyph
+    let missing = Glyph {
+        page: 1,
+        src: Rect::new(60, 0, 4, 8),
+        offset_x: 0,
+        offset_y: 0,
+        advance: 5,
+    };
+    assert_eq!(font.missing, Some(missing));
+    assert_eq!(font.glyph('€'), Some(missing));
+
+    assert_eq!(font.kerning('A', 'V'), -2);
+    assert_eq!(font.kerning('V', 'A'), 0);
+    assert_eq!(font.kerning('a', 'a'), 0);
+}
+
+#[test]
+fn text_format() {
+    check_font(&font());
+}
+
+#[test]
+fn binary_format() {
+    check_font(&BmFontFile::parse(&binary_descriptor(&chars())).unwrap());
+}
+
+#[test]
+fn without_missing_glyph() {
+    let chars = &chars()[1..];
+    for data in [
+        text_descriptor(chars).into_bytes(),
+        binary_descriptor(chars),
+    ] {
+        let font = BmFontFile::parse(&data).unwrap();
+        assert_eq!(font.missing, None);
+        assert_eq!(font.glyph('€'), None);
+        assert!(font.glyph('a').is_some());
+    }
+}
+
+#[test]
+fn invalid_descriptors() {
+    let text = text_descriptor(&chars());
+    let replace = |from: &str, to: &str| text.replacen(from, to, 1).into_bytes();
+
+    assert_eq!(
+        parse_error(&replace("common lineHeight=10", "common")),
+        Some(BmFontError::MissingCommon)
+    );
+    assert_eq!(
+        parse_error(&replace("page=1", "page=2")),
+        Some(BmFontError::MissingPage(2))
+    );
+    for (from, to) in [
+        ("id=-1 ", "id=-2 "),
+        ("id=32", "id=x"),
+        ("xadvance=6", "xadvance=6.5"),
+        ("page=0", "page=256"),
+        ("amount=-2", "amount=two"),
+    ] {
+        assert_eq!(
+            parse_error(&replace(from, to)),
+            Some(BmFontError::InvalidValue),
+            "{to}"
+        );
+    }
+    assert_eq!(parse_error(b"info \xff"), Some(BmFontError::InvalidUtf8));
+
+    let binary = binary_descriptor(&chars());
+    let mut bad = binary.clone();
+    bad[3] = 2;
+    assert_eq!(parse_error(&bad), Some(BmFontError::UnsupportedVersion(2)));
+    // Truncated inside of the header, a block and a character entry
+    for len in [5, 7, binary.len() - 3] {
+        assert_eq!(
+            parse_error(&binary[..len]),
+            Some(BmFontError::UnexpectedEof),
+            "{len}"
+        );
+    }
+}
+
+/// Text and width of each line
+fn lines<'a>(font: &impl Font, text: &'a str, max_width: Option<i32>) -> Vec<(&'a str, i32)> {
+    layout(font, text, max_width)
+        .into_iter()
+        .map(|Line { range, width }| (&text[range], width))
+        .collect()
+}
+
+#[test]
+fn kerning_pairs() {
+    let font = font();
+    assert_eq!(measure(&font, "AV", None), (10, 10));
+    assert_eq!(measure(&font, "VA", None), (12, 10));
+    // Pairs with the missing glyph never apply
+    assert_eq!(measure(&font, "€a", None), (9, 10));
+}
+
+#[test]
+fn wrap_at_words() {
+    let font = font();
+    assert_eq!(
+        lines(&font, "ab cd ef", Some(18)),
+        [("ab cd", 18), ("ef", 8)]
+    );
+    // Whitespace never breaks a line, and is trimmed from the end of lines
+    assert_eq!(
+        lines(&font, "ab cd   ef", Some(18)),
+        [("ab cd", 18), ("ef", 8)]
+    );
+    assert_eq!(lines(&font, "ab   ", Some(8)), [("ab", 8)]);
+    // The kerning of the first pair of the next line is measured again
+    assert_eq!(lines(&font, "AV AV", Some(12)), [("AV", 10), ("AV", 10)]);
+    // Words longer than the width are split at character boundaries
+    assert_eq!(
+        lines(&font, "abcdefghi", Some(10)),
+        [("ab", 8), ("cd", 8), ("ef", 8), ("gh", 8), ("i", 4)]
+    );
+    // A single character wider than the width still makes progress
+    assert_eq!(lines(&font, "ab", Some(1)), [("a", 4), ("b", 4)]);
+    assert_eq!(lines(&font, "ab cd ef", None), [("ab cd ef", 28)]);
+}
+
+#[test]
+fn wrap_at_line_breaks() {
+    let font = font();
+    assert_eq!(lines(&font, "ab\ncd", None), [("ab", 8), ("cd", 8)]);
+    assert_eq!(
+        lines(&font, "ab\n\ncd ef\n", Some(12)),
+        [("ab", 8), ("", 0), ("cd", 8), ("ef", 8), ("", 0)]
+    );
+
+    // Ranges are byte offsets into the whole text
+    let ranges: Vec<_> = layout(&font, "ab\ncd ef", Some(12))
+        .into_iter()
+        .map(|line| line.range)
+        .collect();
+    assert_eq!(ranges, [0..2, 3..5, 6..8]);
+    assert_eq!(measure(&font, "ab\ncd ef", Some(12)), (8, 30));
+}
@@ -0,0 +1,248 @@
+//! Block transfer clipping and splitting shared with `psp_gfx::transfer`
+
+mod common;
+
+use common::word;
+use psp_gfx_tools::{
+    ge::Encoder,
+    rect::{Point, Rect},
+    transfer::{TransferImage, clip, encode_copy, encode_linear_copy, linear_blocks},
+};
+
+/// Row length in pixels of linear copies
+const LINEAR_WIDTH: u32 = 512;
+const LINEAR_ROW: u32 = LINEAR_WIDTH * 4;
+
+fn src() -> TransferImage {
+    TransferImage::new(0x0880_0000, 128, 100, 50, true)
+}
+
+fn dst() -> TransferImage {
+    TransferImage::new(0x0410_0000, 64, 64, 32, true)
+}
+
+#[test]
+fn clip_inside() {
+    let rect = Rect::new(10, 10, 20, 10);
+    assert_eq!(
+        clip(&src(), rect, &dst(), Point::new(5, 5)),
+        Some((rect, Point::new(5, 5)))
+    );
+}
+
+#[test]
+fn clip_to_source() {
+    assert_eq!(
+        clip(&src(), Rect::new(90, 45, 20, 10), &dst(), Point::ZERO),
+        Some((Rect::new(90, 45, 10, 5), Point::ZERO))
+    );
+    // Negative source positions move the destination along
+    assert_eq!(
+        clip(
+            &src(),
+            Rect::new(-5, -3, 20, 10),
+            &dst(),
+            Point::new(10, 10)
+        ),
+        Some((Rect::new(0, 0, 15, 7), Point::new(15, 13)))
+    );
+}
+
+#[test]
+fn clip_to_destination() {
+    assert_eq!(
+        clip(&src(), Rect::new(0, 0, 30, 20), &dst(), Point::new(50, 20)),
+        Some((Rect::new(0, 0, 14, 12), Point::new(50, 20)))
+    );
+    // Negative destination positions move the source along
+    assert_eq!(
+        clip(
+            &src(),
+            Rect::new(10, 10, 20, 10),
+            &dst(),
+            Point::new(-4, -6)
+        ),
+        Some((Rect::new(14, 16, 16, 4), Point::ZERO))
+    );
+}
+
+#[test]
+fn clip_to_both() {
+    assert_eq!(
+        clip(
+            &src(),
+            Rect::new(-10, -10, 200, 100),
+            &dst(),
+            Point::new(-20, 5)
+        ),
+        Some((Rect::new(10, 0, 64, 17), Point::new(0, 15)))
+    );
+}
+
+#[test]
+fn clip_nothing_left() {
+    let rect = Rect::new(0, 0, 10, 10);
+    assert_eq!(clip(&src(), rect, &dst(), Point::new(64, 0)), None);
+    assert_eq!(clip(&src(), rect, &dst(), Point::new(0, -10)), None);
+    assert_eq!(
+        clip(&src(), Rect::new(100, 0, 10, 10), &dst(), Point::ZERO),
+        None
+    );
+    assert_eq!(
+        clip(&src(), Rect::new(-10, 0, 10, 10), &dst(), Point::ZERO),
+        None
+    );
+    assert_eq!(
+        clip(&src(), Rect::new(5, 5, 0, 10), &dst(), Point::ZERO),
+        None
+    );
+}
+
+#[test]
+fn byte_range() {
+    let image = TransferImage::new(0x0410_0000, 64, 64, 32, true);
+    // From the first pixel of the first row to the end of the last row
+    let first = 3 * 64 + 2;
+    let last = 7 * 64 + 6;
+    assert_eq!(
+        image.byte_range(Rect::new(2, 3, 4, 5)),
+        0x0410_0000 + first * 4..0x0410_0000 + last * 4
+    );
+    let image = TransferImage::new(0x0410_0000, 64, 64, 32, false);
+    assert_eq!(
+        image.byte_range(Rect::new(2, 3, 4, 5)),
+        0x0410_0000 + first * 2..0x0410_0000 + last * 2
+    );
+    // Single rows don't include the rest of the stride
+    assert_eq!(
+        image.byte_range(Rect::new(0, 0, 64, 1)),
+        0x0410_0000..0x0410_0000 + 128
+    );
+    assert_eq!(
+        image.byte_range(image.bounds()),
+        0x0410_0000..0x0410_0000 + 32 * 64 * 2
+    );
+}
+
+#[test]
+fn image_location() {
+    assert!(dst().is_vram());
+    assert!(TransferImage::new(0x041f_fff0, 8, 8, 1, false).is_vram());
+    assert!(!src().is_vram());
+    assert!(!TransferImage::new(0x0420_0000, 8, 8, 1, false).is_vram());
+}
+
+#[test]
+#[should_panic(expected = "16 byte aligned")]
+fn unaligned_image() {
+    TransferImage::new(0x0880_0008, 64, 64, 32, true);
+}
+
+#[test]
+#[should_panic(expected = "multiple of 8")]
+fn unaligned_buffer_width() {
+    TransferImage::new(0x0880_0000, 60, 60, 32, true);
+}
+
+#[test]
+fn linear_blocks_short() {
+    assert_eq!(linear_blocks(0).collect::<Vec<_>>(), []);
+    assert_eq!(linear_blocks(16).collect::<Vec<_>>(), [(0, 4, 1)]);
+    // Exactly one row, then one row and a partial row
+    assert_eq!(
+        linear_blocks(LINEAR_ROW).collect::<Vec<_>>(),
+        [(0, LINEAR_WIDTH, 1)]
+    );
+    assert_eq!(
+        linear_blocks(LINEAR_ROW + 8).collect::<Vec<_>>(),
+        [(0, LINEAR_WIDTH, 1), (LINEAR_ROW, 2, 1)]
+    );
+}
+
+#[test]
+fn linear_blocks_split_at_1024_rows() {
+    assert_eq!(
+        linear_blocks(1024 * LINEAR_ROW).collect::<Vec<_>>(),
+        [(0, LINEAR_WIDTH, 1024)]
+    );
+    let len = 1500 * LINEAR_ROW + 40;
+    assert_eq!(
+        linear_blocks(len).collect::<Vec<_>>(),
+        [
+            (0, LINEAR_WIDTH, 1024),
+            (1024 * LINEAR_ROW, LINEAR_WIDTH, 476),
+            (1500 * LINEAR_ROW, 10, 1),
+        ]
+    );
+    // The blocks cover the copy without gaps
+    let covered: u32 = linear_blocks(len)
+        .map(|(_, width, height)| width * height * 4)
+        .sum();
+    assert_eq!(covered, len);
+}
+
+#[test]
+#[should_panic(expected = "multiple of 4")]
+fn linear_blocks_unaligned() {
+    let _ = linear_blocks(6);
+}
+
+#[test]
+fn encode_clipped_copy() {
+    let (rect, pos) = clip(
+        &src(),
+        Rect::new(-5, -3, 20, 10),
+        &dst(),
+        Point::new(10, 10),
+    )
+    .unwrap();
+    let mut ge = Encoder::new(Vec::new());
+    encode_copy(&mut ge, &src(), rect, &dst(), pos);
+    assert_eq!(
+        ge.into_inner(),
+        [
+            // Source at 0,0 with a stride of 128
+            word(178, 0x80_0000),
+            word(179, 0x08_0000 | 128),
+            word(235, 0),
+            // Destination at 15,13 with a stride of 64
+            word(180, 0x10_0000),
+            word(181, 0x04_0000 | 64),
+            word(236, (13 << 10) | 15),
+            // 15x7 32-bit pixels, then texture sync and flush
+            word(238, (6 << 10) | 14),
+            word(234, 1),
+            word(204, 0),
+            word(203, 0),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "same pixel size")]
+fn encode_copy_pixel_size_mismatch() {
+    let dst = TransferImage::new(0x0410_0000, 64, 64, 32, false);
+    encode_copy(
+        &mut Encoder::new(Vec::new()),
+        &src(),
+        Rect::new(0, 0, 8, 8),
+        &dst,
+        Point::ZERO,
+    );
+}
+
+#[test]
+fn encode_linear() {
+    let mut ge = Encoder::new(Vec::new());
+    encode_linear_copy(&mut ge, 0x0880_0000, 0x0410_0000, 1025 * LINEAR_ROW);
+    let words = ge.into_inner();
+    // Two blocks of eight commands and one texture sync and flush
+    assert_eq!(words.len(), 2 * 8 + 2);
+    let offset = 1024 * LINEAR_ROW;
+    assert_eq!(words[8], word(178, 0x80_0000 + offset));
+    assert_eq!(words[10], word(235, 0));
+    assert_eq!(words[11], word(180, 0x10_0000 + offset));
+    // 512x1 pixels after the first 512x1024 block
+    assert_eq!(words[6], word(238, (1023 << 10) | 511));
+    assert_eq!(words[14], word(238, 511));
+}
@@ -0,0 +1,195 @@
+//! VRAM allocator shared with `psp_gfx::vram`, against a fake 2 MiB region
+
+use psp_gfx_tools::vram::{VramHeap, VramMove, VramStats};
+
+const VRAM_SIZE: u32 = 2 * 1024 * 1024;
+/// 512x272 32-bit framebuffer
+const FRAMEBUFFER: u32 = 512 * 272 * 4;
+
+fn stats(heap: &VramHeap) -> VramStats {
+    let stats = heap.stats();
+    assert_eq!(stats.total, VRAM_SIZE);
+    assert_eq!(stats.used + stats.free, VRAM_SIZE);
+    stats
+}
+
+#[test]
+fn first_fit() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    assert_eq!(heap.alloc(FRAMEBUFFER, 16), Some(0));
+    assert_eq!(heap.alloc(FRAMEBUFFER, 16), Some(FRAMEBUFFER));
+    assert_eq!(heap.alloc(100, 16), Some(2 * FRAMEBUFFER));
+    assert_eq!(heap.alloc(100, 16), Some(2 * FRAMEBUFFER + 112));
+    let stats = stats(&heap);
+    assert_eq!(stats.allocations, 4);
+    // The alignment padding between the small blocks stays free
+    assert_eq!(stats.used, 2 * FRAMEBUFFER + 200);
+    assert_eq!(stats.largest_free, VRAM_SIZE - (2 * FRAMEBUFFER + 212));
+}
+
+#[test]
+fn alignment() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    assert_eq!(heap.alloc(1, 1), Some(0));
+    assert_eq!(heap.alloc(16, 16), Some(16));
+    assert_eq!(heap.alloc(1, 8192), Some(8192));
+    // The gaps before aligned blocks are used by later allocations
+    assert_eq!(heap.alloc(15, 1), Some(1));
+    assert_eq!(heap.alloc(64, 64), Some(64));
+    // Zero sized allocations still get a unique offset
+    assert_eq!(heap.alloc(0, 1), Some(32));
+    assert_eq!(stats(&heap).allocations, 6);
+}
+
+#[test]
+#[should_panic(expected = "power of two")]
+fn alignment_must_be_a_power_of_two() {
+    VramHeap::new(VRAM_SIZE).alloc(16, 24);
+}
+
+#[test]
+fn out_of_memory() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    assert_eq!(heap.alloc(VRAM_SIZE + 1, 1), None);
+    assert_eq!(heap.alloc(u32::MAX, 1), None);
+    assert_eq!(heap.alloc(VRAM_SIZE, 1), Some(0));
+    assert_eq!(heap.alloc(1, 1), None);
+    assert_eq!(stats(&heap).free, 0);
+
+    // Alignment padding can make an allocation fail even though enough bytes are free
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    heap.alloc(1, 1).unwrap();
+    assert_eq!(heap.alloc(VRAM_SIZE - 1, 16), None);
+    assert_eq!(heap.alloc(VRAM_SIZE - 1, 1), Some(1));
+}
+
+#[test]
+fn free_merges_neighbours() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    let blocks: Vec<_> = (0..4).map(|_| heap.alloc(0x1000, 16).unwrap()).collect();
+    heap.free(blocks[1], 0x1000);
+    assert_eq!(stats(&heap).largest_free, VRAM_SIZE - 0x4000);
+    // Merged with the previous free range
+    heap.free(blocks[2], 0x1000);
+    assert_eq!(stats(&heap).largest_free, VRAM_SIZE - 0x4000);
+    assert_eq!(heap.alloc(0x2000, 16), Some(0x1000));
+    heap.free(0x1000, 0x2000);
+    // Merged with both neighbours, then with the rest of the heap
+    heap.free(blocks[0], 0x1000);
+    assert_eq!(stats(&heap).largest_free, VRAM_SIZE - 0x4000);
+    heap.free(blocks[3], 0x1000);
+    let stats = stats(&heap);
+    assert_eq!(stats.largest_free, VRAM_SIZE);
+    assert_eq!(stats.allocations, 0);
+    assert_eq!(heap.alloc(VRAM_SIZE, 16), Some(0));
+}
+
+#[test]
+#[should_panic(expected = "already free")]
+fn double_free() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    let offset = heap.alloc(64, 16).unwrap();
+    heap.free(offset, 64);
+    heap.free(offset, 64);
+}
+
+#[test]
+#[should_panic(expected = "doesn't match")]
+fn free_with_wrong_size() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    let offset = heap.alloc(64, 16).unwrap();
+    heap.free(offset, 32);
+}
+
+#[test]
+fn compact_moves_relocatable_blocks() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    let a = heap.alloc_relocatable(0x1000, 16).unwrap();
+    let b = heap.alloc_relocatable(0x1000, 16).unwrap();
+    let c = heap.alloc_relocatable(0x800, 0x400).unwrap();
+    let d = heap.alloc_relocatable(0x100, 16).unwrap();
+    assert_eq!(
+        [a.get(), b.get(), c.get(), d.get()],
+        [0, 0x1000, 0x2000, 0x2800]
+    );
+    heap.free(a.get(), 0x1000);
+    heap.free(c.get(), 0x800);
+    assert_eq!(stats(&heap).largest_free, VRAM_SIZE - 0x2900);
+
+    let moves = heap.compact();
+    assert_eq!(
+        moves,
+        [
+            VramMove {
+                from: 0x1000,
+                to: 0,
+                size: 0x1000
+            },
+            VramMove {
+                from: 0x2800,
+                to: 0x1000,
+                size: 0x100
+            },
+        ]
+    );
+    assert_eq!([b.get(), d.get()], [0, 0x1000]);
+    let stats = stats(&heap);
+    assert_eq!(stats.largest_free, VRAM_SIZE - 0x1100);
+    assert_eq!(stats.allocations, 2);
+
+    // Blocks are freed at their new offsets, a second compaction has nothing to do
+    assert!(heap.compact().is_empty());
+    heap.free(b.get(), 0x1000);
+    heap.free(d.get(), 0x100);
+    assert_eq!(heap.stats().largest_free, VRAM_SIZE);
+}
+
+#[test]
+fn compact_keeps_pinned_blocks_and_alignment() {
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    let gap = heap.alloc(0x100, 16).unwrap();
+    let framebuffer = heap.alloc(FRAMEBUFFER, 16).unwrap();
+    let small = heap.alloc_relocatable(0x30, 16).unwrap();
+    let aligned = heap.alloc_relocatable(0x200, 0x100).unwrap();
+    // Aligned past the gap after the small block, which would pin the aligned block
+    let tail = heap.alloc(0x10, 0x100).unwrap();
+    heap.free(gap, 0x100);
+    assert_eq!(small.get(), 0x100 + FRAMEBUFFER);
+    assert_eq!(aligned.get(), 0x100 + FRAMEBUFFER + 0x100);
+
+    // Nothing moves across or over the pinned framebuffer, the aligned block keeps its
+    // alignment even though it could move by 0x30 bytes
+    assert!(heap.compact().is_empty());
+    assert_eq!(small.get(), 0x100 + FRAMEBUFFER);
+    assert_eq!(aligned.get() % 0x100, 0);
+
+    // Freeing the pinned block lets the others move down
+    heap.free(framebuffer, FRAMEBUFFER);
+    let moves = heap.compact();
+    assert_eq!(moves.len(), 2);
+    assert_eq!([small.get(), aligned.get()], [0, 0x100]);
+    assert!(moves.windows(2).all(|pair| pair[0].from < pair[1].from));
+    assert!(moves.iter().all(|block| block.to < block.from));
+    let stats = stats(&heap);
+    assert_eq!(stats.allocations, 3);
+    assert_eq!(stats.used, 0x30 + 0x200 + 0x10);
+    // The pinned tail splits the free space
+    assert_eq!(stats.largest_free, VRAM_SIZE - tail - 0x10);
+    assert_eq!(heap.alloc(0x1000, 16), Some(0x300));
+}
+
+#[test]
+fn compact_then_alloc() {
+    // Fragment the heap with relocatable blocks, then fit a large block after compacting
+    let mut heap = VramHeap::new(VRAM_SIZE);
+    let blocks: Vec<_> = (0..32)
+        .map(|_| heap.alloc_relocatable(VRAM_SIZE / 32, 16).unwrap())
+        .collect();
+    for block in blocks.iter().step_by(2) {
+        heap.free(block.get(), VRAM_SIZE / 32);
+    }
+    assert_eq!(heap.alloc(VRAM_SIZE / 2, 16), None);
+    assert_eq!(heap.compact().len(), 16);
+    assert_eq!(heap.alloc(VRAM_SIZE / 2, 16), Some(VRAM_SIZE / 2));
+    assert_eq!(stats(&heap).free, 0);
+}
@@ -1,5 +1,9 @@
+use crate::cache;
+use alloc::vec::Vec;
+use core::cell::Cell;
 use core::ffi::c_void;
 use core::marker::PhantomData;
+use core::ops::Range;
 
 pub unsafe trait Buffer {
     type Item;
@@ -19,8 +23,48 @@ pub unsafe trait Buffer {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Make CPU writes visible to the GE, called before the buffer is drawn
+    ///
+    /// Buffers in cached memory which can be modified have to write back the data cache.
+    fn sync_cache(&self) {}
+
+    /// Get a view of a range of elements of the buffer
+    fn view(&self, range: Range<usize>) -> BufferView<'_, Self::Item> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range out of bounds"
+        );
+        let item_size = core::mem::size_of::<Self::Item>();
+        // The view can't track modifications, but the buffer can't change while it exists
+        self.sync_cache();
+        BufferView {
+            ptr: unsafe { self.as_ptr().byte_add(range.start * item_size) },
+            size: range.len() * item_size,
+            _phantom: PhantomData,
+        }
+    }
 }
 
+/// Buffers are drawn through references, which keep the data alive until the frame ends
+unsafe impl<B: Buffer + ?Sized> Buffer for &B {
+    type Item = B::Item;
+
+    fn as_ptr(&self) -> *const c_void {
+        (**self).as_ptr()
+    }
+
+    fn byte_size(&self) -> usize {
+        (**self).byte_size()
+    }
+
+    fn sync_cache(&self) {
+        (**self).sync_cache()
+    }
+}
+
+/// Buffer allocated in the display list, valid until the end of the frame
+#[derive(Clone, Copy)]
 pub struct TransientBuffer<'frame, T: Clone + Copy> {
     ptr: *mut c_void,
     size: i32,
@@ -61,3 +105,74 @@ unsafe impl<'frame, T: Clone + Copy> Buffer for TransientBuffer<'frame, T> {
         self.size as usize
     }
 }
+
+/// Borrowed range of another [`Buffer`], see [`Buffer::view`]
+pub struct BufferView<'a, T> {
+    ptr: *const c_void,
+    size: usize,
+    _phantom: PhantomData<&'a T>,
+}
+
+unsafe impl<'a, T> Buffer for BufferView<'a, T> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const c_void {
+        self.ptr
+    }
+
+    fn byte_size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Buffer in main memory that persists across frames
+///
+/// Modifications are written back from the data cache when the buffer is drawn.
+pub struct OwnedBuffer<T: Clone + Copy> {
+    data: Vec<T>,
+    /// Data cache contains writes which aren't in memory yet
+    dirty: Cell<bool>,
+}
+
+impl<T: Clone + Copy> OwnedBuffer<T> {
+    pub fn new(data: Vec<T>) -> Self {
+        Self {
+            data,
+            dirty: Cell::new(true),
+        }
+    }
+
+    pub fn from_slice(data: &[T]) -> Self {
+        Self::new(data.to_vec())
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Get mutable access to the elements
+    ///
+    /// The buffer must not be modified while a frame that draws it is in progress
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.dirty.set(true);
+        &mut self.data
+    }
+}
+
+unsafe impl<T: Clone + Copy> Buffer for OwnedBuffer<T> {
+    type Item = T;
+
+    fn as_ptr(&self) -> *const c_void {
+        self.data.as_ptr() as *const c_void
+    }
+
+    fn byte_size(&self) -> usize {
+        core::mem::size_of_val(self.data.as_slice())
+    }
+
+    fn sync_cache(&self) {
+        if self.dirty.replace(false) {
+            cache::writeback(&self.data);
+        }
+    }
+}
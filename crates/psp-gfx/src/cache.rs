@@ -0,0 +1,173 @@
+//! Data cache maintenance
+//!
+//! The GE reads and writes memory directly, bypassing the CPU data cache. Data written by
+//! the CPU has to be written back before the GE uses it, and cached copies of memory
+//! written by the GE have to be invalidated before the CPU reads it. Alternatively memory
+//! can be accessed through its uncached mirror at `0x40000000`, see [`Uncached`].
+//!
+//! [`OwnedBuffer`](crate::buffer::OwnedBuffer) and [`Texture`](crate::texture::Texture)
+//! keep track of modifications and are written back when they are drawn or bound.
+
+use core::ops::Range;
+use psp::sys;
+
+/// Size of a data cache line in bytes
+pub const LINE_SIZE: u32 = 64;
+
+/// Offset of the uncached mirror of memory
+pub const UNCACHED_OFFSET: u32 = 0x4000_0000;
+
+/// Get the uncached mirror of an address
+pub const fn uncached_address(address: u32) -> u32 {
+    address | UNCACHED_OFFSET
+}
+
+/// Get the cached address of an uncached mirror
+pub const fn cached_address(address: u32) -> u32 {
+    address & !UNCACHED_OFFSET
+}
+
+/// Get the cache lines covering an address range, as an address range
+pub const fn line_range(range: Range<u32>) -> Range<u32> {
+    let start = range.start & !(LINE_SIZE - 1);
+    let end = if range.end > range.start {
+        range.end.next_multiple_of(LINE_SIZE)
+    } else {
+        start
+    };
+    start..end
+}
+
+/// Write back cached data of an address range to memory
+pub fn writeback_range(range: Range<u32>) {
+    if range.is_empty() {
+        return;
+    }
+    unsafe {
+        sys::sceKernelDcacheWritebackRange(range.start as *const _, range.end - range.start);
+    }
+}
+
+/// Discard cached data of an address range, so following reads see the memory contents
+///
+/// Cache lines which are only partially covered by the range are written back first, so
+/// neighbouring data isn't lost.
+pub fn invalidate_range(range: Range<u32>) {
+    if range.is_empty() {
+        return;
+    }
+    let lines = line_range(range.clone());
+    unsafe {
+        if lines == range {
+            sys::sceKernelDcacheInvalidateRange(range.start as *const _, range.end - range.start);
+        } else {
+            sys::sceKernelDcacheWritebackInvalidateRange(
+                lines.start as *const _,
+                lines.end - lines.start,
+            );
+        }
+    }
+}
+
+/// Write back cached data of a slice, e.g. vertices written by the CPU
+pub fn writeback<T>(data: &[T]) {
+    writeback_range(address_range(data));
+}
+
+/// Discard cached data of a slice, e.g. before reading pixels rendered by the GE
+pub fn invalidate<T>(data: &mut [T]) {
+    invalidate_range(address_range(data));
+}
+
+/// Write back the whole data cache
+pub fn writeback_all() {
+    unsafe {
+        sys::sceKernelDcacheWritebackAll();
+    }
+}
+
+/// Prepare the data cache for a block transfer from `src` to `dst` (address ranges)
+pub(crate) fn sync_transfer(src: Range<u32>, src_vram: bool, dst: Range<u32>, dst_vram: bool) {
+    if !src_vram {
+        writeback_range(src);
+    }
+    // Dirty lines would overwrite the copy when they are evicted
+    if !dst_vram {
+        invalidate_range(dst);
+    }
+}
+
+fn address_range<T>(data: &[T]) -> Range<u32> {
+    let start = data.as_ptr() as u32;
+    start..start + size_of_val(data) as u32
+}
+
+/// Pointer accessing memory through the uncached mirror
+///
+/// Reads and writes go to memory directly, so they are visible to the GE without
+/// maintenance. Every access is a separate memory transaction, which is slow for large
+/// amounts of data.
+#[derive(Debug)]
+pub struct Uncached<T> {
+    ptr: *mut T,
+}
+
+impl<T> Clone for Uncached<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Uncached<T> {}
+
+impl<T> Uncached<T> {
+    /// Create an uncached pointer to `count` values at a cached (or uncached) pointer
+    ///
+    /// The cached range is written back and invalidated, so stale cache lines can't
+    /// overwrite uncached writes later.
+    ///
+    /// # Safety
+    /// `ptr` must be valid for `count` values
+    pub unsafe fn new(ptr: *mut T, count: usize) -> Self {
+        let address = cached_address(ptr as u32);
+        let range = address..address + (count * size_of::<T>()) as u32;
+        let lines = line_range(range);
+        if !lines.is_empty() {
+            unsafe {
+                sys::sceKernelDcacheWritebackInvalidateRange(
+                    lines.start as *const _,
+                    lines.end - lines.start,
+                );
+            }
+        }
+        Self {
+            ptr: uncached_address(address) as *mut T,
+        }
+    }
+
+    pub fn as_ptr(self) -> *mut T {
+        self.ptr
+    }
+
+    /// Offset the pointer by `count` values
+    ///
+    /// # Safety
+    /// The result must stay in bounds of the range passed to [`Uncached::new`]
+    pub unsafe fn add(self, count: usize) -> Self {
+        Self {
+            ptr: unsafe { self.ptr.add(count) },
+        }
+    }
+
+    /// # Safety
+    /// The pointer must be in bounds and aligned
+    pub unsafe fn read(self) -> T {
+        unsafe { self.ptr.read_volatile() }
+    }
+
+    /// # Safety
+    /// The pointer must be in bounds and aligned
+    pub unsafe fn write(self, value: T) {
+        unsafe { self.ptr.write_volatile(value) }
+    }
+}
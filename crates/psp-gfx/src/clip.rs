@@ -0,0 +1,174 @@
+//! CPU clipping of 3D triangles
+//!
+//! The GE does not clip triangles against the near plane, and discards triangles with
+//! vertices outside of the 4096x4096 drawing area (the "guard band") entirely. This is
+//! usually noticeable as large ground planes vanishing when the camera gets close to
+//! them. [`clip_triangles`] splits triangles along these planes before submission,
+//! see `Frame::draw_array_clipped`.
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools.
+
+use alloc::vec::Vec;
+
+use crate::ge::sys::{GuPrimitive, ScePspFMatrix4, VertexType};
+use crate::vertex::Vertex;
+
+/// Vertex that can be split during clipping
+///
+/// Implemented by all layouts created with [`define_vertex_layout!`](crate::define_vertex_layout)
+pub trait ClipVertex: Vertex + Copy {
+    /// Get the position in model space
+    ///
+    /// (8-bit and 16-bit positions are normalized to `-1..1`, same as the GE does)
+    fn position(&self) -> [f32; 3];
+
+    /// Interpolate all attributes between two vertices
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+/// Extent of the guard band in normalized device coordinates
+///
+/// Vertices must map to screen coordinates in `0..4096` (relative to the drawing offset),
+/// with the default viewport that is `4096 / 480` horizontally.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GuardBand {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl GuardBand {
+    /// Guard band of the default full screen (480x272) viewport, with a one pixel margin
+    pub const DEFAULT: Self = Self::for_viewport(480, 272);
+
+    /// Get the guard band of a viewport centered in the drawing area
+    pub const fn for_viewport(width: u32, height: u32) -> Self {
+        Self {
+            x: 2047. / (width as f32 / 2.),
+            y: 2047. / (height as f32 / 2.),
+        }
+    }
+}
+
+impl Default for GuardBand {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Clip triangles against the near plane and the guard band
+///
+/// `primitive` must be [`GuPrimitive::Triangles`], [`GuPrimitive::TriangleStrip`] or
+/// [`GuPrimitive::TriangleFan`], the result is always a list of [`GuPrimitive::Triangles`].
+/// `clip_matrix` is the combined projection, view and model matrix. Triangles entirely
+/// outside of the clip volume are removed, winding order is preserved.
+///
+/// Panics for `TRANSFORM_2D` layouts, which are in screen space already.
+pub fn clip_triangles<V: ClipVertex>(
+    primitive: GuPrimitive,
+    vertices: &[V],
+    clip_matrix: &ScePspFMatrix4,
+    guard_band: GuardBand,
+) -> Vec<V> {
+    assert!(
+        !V::vtype().contains(VertexType::TRANSFORM_2D),
+        "only TRANSFORM_3D vertices can be clipped"
+    );
+    let mut output = Vec::with_capacity(vertices.len());
+    let mut polygon = Vec::new();
+    let mut scratch = Vec::new();
+    for_each_triangle(primitive, vertices.len(), |a, b, c| {
+        polygon.clear();
+        for idx in [a, b, c] {
+            let vertex = vertices[idx];
+            polygon.push((vertex, transform(clip_matrix, vertex.position())));
+        }
+        for plane in planes(guard_band) {
+            clip_polygon(&polygon, &mut scratch, plane);
+            core::mem::swap(&mut polygon, &mut scratch);
+            if polygon.len() < 3 {
+                return;
+            }
+        }
+        for idx in 1..(polygon.len() - 1) {
+            output.push(polygon[0].0);
+            output.push(polygon[idx].0);
+            output.push(polygon[idx + 1].0);
+        }
+    });
+    output
+}
+
+/// Call `f` with the vertex indices of each triangle, in the winding order of the first triangle
+fn for_each_triangle(primitive: GuPrimitive, len: usize, mut f: impl FnMut(usize, usize, usize)) {
+    match primitive {
+        GuPrimitive::Triangles => {
+            for idx in (0..len.saturating_sub(2)).step_by(3) {
+                f(idx, idx + 1, idx + 2);
+            }
+        }
+        GuPrimitive::TriangleStrip => {
+            for idx in 0..len.saturating_sub(2) {
+                if idx % 2 == 0 {
+                    f(idx, idx + 1, idx + 2);
+                } else {
+                    f(idx + 1, idx, idx + 2);
+                }
+            }
+        }
+        GuPrimitive::TriangleFan => {
+            for idx in 1..len.saturating_sub(1) {
+                f(0, idx, idx + 1);
+            }
+        }
+        _ => panic!("only triangle primitives can be clipped"),
+    }
+}
+
+/// Clip plane, `dot(plane, position) >= 0` is inside
+type Plane = [f32; 4];
+
+fn planes(guard_band: GuardBand) -> [Plane; 5] {
+    [
+        // Near plane (z >= -w)
+        [0., 0., 1., 1.],
+        // Guard band (-gx * w <= x <= gx * w, same for y)
+        [1., 0., 0., guard_band.x],
+        [-1., 0., 0., guard_band.x],
+        [0., 1., 0., guard_band.y],
+        [0., -1., 0., guard_band.y],
+    ]
+}
+
+/// Sutherland-Hodgman clipping of a convex polygon against a single plane
+fn clip_polygon<V: ClipVertex>(
+    input: &[(V, [f32; 4])],
+    output: &mut Vec<(V, [f32; 4])>,
+    plane: Plane,
+) {
+    output.clear();
+    let distance = |clip: &[f32; 4]| {
+        plane[0] * clip[0] + plane[1] * clip[1] + plane[2] * clip[2] + plane[3] * clip[3]
+    };
+    for (idx, current) in input.iter().enumerate() {
+        let next = &input[(idx + 1) % input.len()];
+        let (d0, d1) = (distance(&current.1), distance(&next.1));
+        if d0 >= 0. {
+            output.push(*current);
+        }
+        if (d0 >= 0.) != (d1 >= 0.) {
+            let t = d0 / (d0 - d1);
+            let clip = core::array::from_fn(|i| current.1[i] + (next.1[i] - current.1[i]) * t);
+            output.push((current.0.lerp(&next.0, t), clip));
+        }
+    }
+}
+
+/// Transform a position to clip space
+fn transform(m: &ScePspFMatrix4, [x, y, z]: [f32; 3]) -> [f32; 4] {
+    [
+        m.x.x * x + m.y.x * y + m.z.x * z + m.w.x,
+        m.x.y * x + m.y.y * y + m.z.y * z + m.w.y,
+        m.x.z * x + m.y.z * y + m.z.z * z + m.w.z,
+        m.x.w * x + m.y.w * y + m.z.w * z + m.w.w,
+    ]
+}
@@ -1,4 +1,10 @@
-#[derive(Clone, Copy, Default)]
+use bytemuck::{Pod, Zeroable};
+use core::ops::{Add, Mul, Sub};
+
+/// 32-bit color, stored in the native A8B8G8R8 format (`COLOR_8888`, [`TexturePixelFormat::Psm8888`])
+///
+/// [`TexturePixelFormat::Psm8888`]: psp::sys::TexturePixelFormat::Psm8888
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
 #[repr(transparent)]
 pub struct Color32(u32);
 
@@ -20,6 +26,14 @@ impl Color32 {
     pub const DARK_GRAY: Self = Self::from_rgba(0x404040ff);
     pub const TRANSPARENT: Self = Self::from_rgba(0x00000000);
 
+    /// Create a new [`Color32`] from components
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self(u32::from_le_bytes([r, g, b, a]))
+    }
+    /// Create a new opaque [`Color32`] from components
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 0xff)
+    }
     /// Create a new [`Color32`] from an integer in the R8G8B8A8 format
     pub const fn from_rgba(x: u32) -> Self {
         Self(x.swap_bytes())
@@ -38,7 +52,7 @@ impl Color32 {
         self.0.swap_bytes()
     }
     /// Get the color in the A8B8G8R8 format (Native PSP)
-    pub fn as_abgr(&self) -> u32 {
+    pub const fn as_abgr(&self) -> u32 {
         self.0
     }
 
@@ -58,4 +72,448 @@ impl Color32 {
     pub const fn a(&self) -> u8 {
         (self.0 >> 24) as u8
     }
+
+    /// Get the components as `[r, g, b, a]`
+    pub const fn to_array(&self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+
+    /// Replace the Alpha component of the color
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self((self.0 & 0x00ffffff) | ((a as u32) << 24))
+    }
+
+    /// Linearly interpolate between two colors, `t` is clamped to `0..=1`
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0., 1.);
+        let [r0, g0, b0, a0] = self.to_array();
+        let [r1, g1, b1, a1] = other.to_array();
+        let mix = |x: u8, y: u8| libm::roundf(x as f32 + (y as f32 - x as f32) * t) as u8;
+        Self::new(mix(r0, r1), mix(g0, g1), mix(b0, b1), mix(a0, a1))
+    }
+
+    /// Multiply the color components by alpha, for use with premultiplied alpha blending
+    pub const fn premultiply(self) -> Self {
+        let [r, g, b, a] = self.to_array();
+        Self::new(mul8(r, a), mul8(g, a), mul8(b, a), a)
+    }
+
+    /// Create a new opaque color from hue (degrees), saturation and value (`0..=1`)
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let (s, v) = (s.clamp(0., 1.), v.clamp(0., 1.));
+        let c = v * s;
+        Self::from_hue_chroma(h, c, v - c)
+    }
+
+    /// Get hue (degrees), saturation and value (`0..=1`) of the color, alpha is ignored
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (h, max, min) = self.hue_max_min();
+        let s = if max == 0. { 0. } else { (max - min) / max };
+        (h, s, max)
+    }
+
+    /// Create a new opaque color from hue (degrees), saturation and lightness (`0..=1`)
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let (s, l) = (s.clamp(0., 1.), l.clamp(0., 1.));
+        let c = (1. - (2. * l - 1.).abs()) * s;
+        Self::from_hue_chroma(h, c, l - c / 2.)
+    }
+
+    /// Get hue (degrees), saturation and lightness (`0..=1`) of the color, alpha is ignored
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (h, max, min) = self.hue_max_min();
+        let l = (max + min) / 2.;
+        let s = if max == min {
+            0.
+        } else {
+            // Rounding pushes fully saturated dark and light colors slightly above 1
+            ((max - min) / (1. - (2. * l - 1.).abs())).min(1.)
+        };
+        (h, s, l)
+    }
+
+    fn from_hue_chroma(h: f32, c: f32, m: f32) -> Self {
+        let h = libm::fmodf(libm::fmodf(h, 360.) + 360., 360.) / 60.;
+        let x = c * (1. - (libm::fmodf(h, 2.) - 1.).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.),
+            1 => (x, c, 0.),
+            2 => (0., c, x),
+            3 => (0., x, c),
+            4 => (x, 0., c),
+            _ => (c, 0., x),
+        };
+        let channel = |value: f32| libm::roundf((value + m).clamp(0., 1.) * 255.) as u8;
+        Self::rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// Get hue in degrees and the largest and smallest components in `0..=1`
+    fn hue_max_min(&self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.to_array().map(|x| x as f32 / 255.);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let h = if delta == 0. {
+            0.
+        } else if max == r {
+            60. * libm::fmodf((g - b) / delta + 6., 6.)
+        } else if max == g {
+            60. * ((b - r) / delta + 2.)
+        } else {
+            60. * ((r - g) / delta + 4.)
+        };
+        (h, max, min)
+    }
+}
+
+/// Multiply two 8 bit values in the `0..=1` range
+const fn mul8(x: u8, y: u8) -> u8 {
+    ((x as u32 * y as u32 + 127) / 255) as u8
+}
+
+/// Expand a `bits` wide channel to 8 bits
+const fn expand(value: u16, bits: u32) -> u8 {
+    let max = (1 << bits) - 1;
+    (((value as u32 & max) * 255 + max / 2) / max) as u8
+}
+
+/// Reduce an 8 bit channel to `bits` bits, rounding to the nearest value
+const fn quantize(value: u8, bits: u32) -> u16 {
+    ((value as u32 * ((1 << bits) - 1) + 127) / 255) as u16
+}
+
+macro_rules! packed_color {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            r: $r_shift:literal / $r_bits:literal,
+            g: $g_shift:literal / $g_bits:literal,
+            b: $b_shift:literal / $b_bits:literal,
+            $(a: $a_shift:literal / $a_bits:literal,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+        #[repr(transparent)]
+        pub struct $name(u16);
+
+        impl $name {
+            /// Create a new color from the raw packed value
+            pub const fn from_bits(bits: u16) -> Self {
+                Self(bits)
+            }
+            /// Get the raw packed value
+            pub const fn to_bits(&self) -> u16 {
+                self.0
+            }
+
+            /// Convert a [`Color32`], rounding each component to the nearest representable value
+            pub const fn from_color32(color: Color32) -> Self {
+                let [r, g, b, _a] = color.to_array();
+                Self(
+                    (quantize(r, $r_bits) << $r_shift)
+                        | (quantize(g, $g_bits) << $g_shift)
+                        | (quantize(b, $b_bits) << $b_shift)
+                        $(| (quantize(_a, $a_bits) << $a_shift))?,
+                )
+            }
+            /// Convert to a [`Color32`], this conversion is lossless
+            pub const fn to_color32(&self) -> Color32 {
+                Color32::new(self.r(), self.g(), self.b(), self.a())
+            }
+
+            /// Get the Red component of the color, expanded to 8 bits
+            pub const fn r(&self) -> u8 {
+                expand(self.0 >> $r_shift, $r_bits)
+            }
+            /// Get the Green component of the color, expanded to 8 bits
+            pub const fn g(&self) -> u8 {
+                expand(self.0 >> $g_shift, $g_bits)
+            }
+            /// Get the Blue component of the color, expanded to 8 bits
+            pub const fn b(&self) -> u8 {
+                expand(self.0 >> $b_shift, $b_bits)
+            }
+            /// Get the Alpha component of the color, expanded to 8 bits
+            pub const fn a(&self) -> u8 {
+                0xff $(& expand(self.0 >> $a_shift, $a_bits))?
+            }
+        }
+
+        impl From<ColorF32> for $name {
+            /// Encode a linear color to sRGB, quantizing directly from floating point
+            fn from(color: ColorF32) -> Self {
+                let [r, g, b, _a] = color.to_srgb_f32();
+                let quantize = |value: f32, bits: u32| {
+                    libm::roundf(value.clamp(0., 1.) * ((1 << bits) - 1) as f32) as u16
+                };
+                Self(
+                    (quantize(r, $r_bits) << $r_shift)
+                        | (quantize(g, $g_bits) << $g_shift)
+                        | (quantize(b, $b_bits) << $b_shift)
+                        $(| (quantize(_a, $a_bits) << $a_shift))?,
+                )
+            }
+        }
+
+        impl From<$name> for ColorF32 {
+            /// Decode an sRGB color to linear
+            fn from(color: $name) -> Self {
+                Self::from_srgb(color.to_color32())
+            }
+        }
+
+        impl From<Color32> for $name {
+            fn from(color: Color32) -> Self {
+                Self::from_color32(color)
+            }
+        }
+
+        impl From<$name> for Color32 {
+            fn from(color: $name) -> Self {
+                color.to_color32()
+            }
+        }
+    };
+}
+
+packed_color! {
+    /// 16-bit opaque color (`COLOR_5650`, [`TexturePixelFormat::Psm5650`])
+    ///
+    /// [`TexturePixelFormat::Psm5650`]: psp::sys::TexturePixelFormat::Psm5650
+    Color5650 {
+        r: 0 / 5,
+        g: 5 / 6,
+        b: 11 / 5,
+    }
+}
+
+packed_color! {
+    /// 16-bit color with 1-bit alpha (`COLOR_5551`, [`TexturePixelFormat::Psm5551`])
+    ///
+    /// [`TexturePixelFormat::Psm5551`]: psp::sys::TexturePixelFormat::Psm5551
+    Color5551 {
+        r: 0 / 5,
+        g: 5 / 5,
+        b: 10 / 5,
+        a: 15 / 1,
+    }
+}
+
+packed_color! {
+    /// 16-bit color with 4-bit components (`COLOR_4444`, [`TexturePixelFormat::Psm4444`])
+    ///
+    /// [`TexturePixelFormat::Psm4444`]: psp::sys::TexturePixelFormat::Psm4444
+    Color4444 {
+        r: 0 / 4,
+        g: 4 / 4,
+        b: 8 / 4,
+        a: 12 / 4,
+    }
+}
+
+/// Linear RGBA color with floating point components
+///
+/// Components are nominally in `0..=1`, but values outside of that range are preserved
+/// (e.g. for HDR lighting) until converted to an integer format. Conversions from and to
+/// integer colors assume they are sRGB encoded, alpha is always linear.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ColorF32 {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl ColorF32 {
+    pub const BLACK: Self = Self::new(0., 0., 0., 1.);
+    pub const WHITE: Self = Self::new(1., 1., 1., 1.);
+    pub const TRANSPARENT: Self = Self::new(0., 0., 0., 0.);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create a new opaque color
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.)
+    }
+
+    /// Decode an sRGB [`Color32`] to linear
+    pub fn from_srgb(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_array().map(|x| x as f32 / 255.);
+        Self::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+    }
+
+    /// Encode the color to sRGB [`Color32`], components are clamped to `0..=1`
+    pub fn to_srgb(&self) -> Color32 {
+        let [r, g, b, a] = self
+            .to_srgb_f32()
+            .map(|x| libm::roundf(x.clamp(0., 1.) * 255.) as u8);
+        Color32::new(r, g, b, a)
+    }
+
+    /// Convert a [`Color32`] without decoding sRGB, i.e. the components are only rescaled
+    pub fn from_color32_linear(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_array().map(|x| x as f32 / 255.);
+        Self::new(r, g, b, a)
+    }
+
+    /// Convert to [`Color32`] without encoding to sRGB, components are clamped to `0..=1`
+    pub fn to_color32_linear(&self) -> Color32 {
+        let [r, g, b, a] = self
+            .to_array()
+            .map(|x| libm::roundf(x.clamp(0., 1.) * 255.) as u8);
+        Color32::new(r, g, b, a)
+    }
+
+    pub const fn to_array(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// Replace the Alpha component of the color
+    pub const fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Clamp all components to `0..=1`
+    pub fn clamp(self) -> Self {
+        self.map_rgb(|x| x.clamp(0., 1.))
+            .with_alpha(self.a.clamp(0., 1.))
+    }
+
+    /// Linearly interpolate between two colors
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    /// Multiply the color components by alpha
+    pub fn premultiply(self) -> Self {
+        self.map_rgb(|x| x * self.a)
+    }
+
+    /// Relative luminance (Rec. 709 coefficients)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// Scale the color by `2^stops`
+    pub fn exposure(self, stops: f32) -> Self {
+        let scale = libm::exp2f(stops);
+        self.map_rgb(|x| x * scale)
+    }
+
+    /// Map HDR components to `0..1` using the Reinhard operator (`x / (1 + x)`)
+    pub fn tonemap_reinhard(self) -> Self {
+        // Infinite components would give `inf / inf`
+        self.map_rgb(|x| {
+            let x = x.min(f32::MAX);
+            x / (1. + x)
+        })
+    }
+
+    /// Map HDR components to `0..=1` using an approximation of the ACES filmic curve
+    pub fn tonemap_aces(self) -> Self {
+        self.map_rgb(|x| {
+            // The curve reaches 1 at about 7.24, larger values would overflow to `inf / inf`
+            let x = (x * 0.6).min(8.);
+            ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0., 1.)
+        })
+    }
+
+    /// Apply a function to the color components, alpha is kept as is
+    fn map_rgb(self, f: impl Fn(f32) -> f32) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b), self.a)
+    }
+
+    /// Get the sRGB encoded components (unclamped)
+    fn to_srgb_f32(self) -> [f32; 4] {
+        [
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        ]
+    }
+}
+
+impl From<Color32> for ColorF32 {
+    /// Decode an sRGB color to linear
+    fn from(color: Color32) -> Self {
+        Self::from_srgb(color)
+    }
+}
+
+impl From<ColorF32> for Color32 {
+    /// Encode a linear color to sRGB
+    fn from(color: ColorF32) -> Self {
+        color.to_srgb()
+    }
+}
+
+impl Add for ColorF32 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(
+            self.r + rhs.r,
+            self.g + rhs.g,
+            self.b + rhs.b,
+            self.a + rhs.a,
+        )
+    }
+}
+
+impl Sub for ColorF32 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(
+            self.r - rhs.r,
+            self.g - rhs.g,
+            self.b - rhs.b,
+            self.a - rhs.a,
+        )
+    }
+}
+
+impl Mul for ColorF32 {
+    type Output = Self;
+
+    /// Component-wise multiplication (modulation)
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.r * rhs.r,
+            self.g * rhs.g,
+            self.b * rhs.b,
+            self.a * rhs.a,
+        )
+    }
+}
+
+impl Mul<f32> for ColorF32 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.r * rhs, self.g * rhs, self.b * rhs, self.a * rhs)
+    }
+}
+
+/// Convert an sRGB encoded component to linear
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        libm::powf((value + 0.055) / 1.055, 2.4)
+    }
+}
+
+/// Convert a linear component to sRGB encoding
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * libm::powf(value, 1. / 2.4) - 0.055
+    }
 }
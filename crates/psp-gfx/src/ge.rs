@@ -0,0 +1,99 @@
+//! Encoder for GE display list commands
+//!
+//! Every command is a 32-bit word: the [`GeCommand`] in the top 8 bits and a 24-bit
+//! argument. Floats are passed as their upper 24 bits. The [`Encoder`] only generates
+//! words into a [`CommandSink`], so the output can be inspected without a GE, e.g. by
+//! encoding into a `Vec<u32>`. [`Frame`](crate::Frame) encodes into the current display
+//! list with [`GuList`]. [`disasm`] turns encoded words back into readable commands.
+
+pub mod disasm;
+pub mod dump;
+pub mod encoder;
+
+pub use disasm::{disasm, disasm_at};
+pub use encoder::*;
+
+pub(crate) use psp::sys;
+use sys::GeCommand;
+
+/// Sink writing into the display list started by `sceGuStart`
+///
+/// The `psp` crate keeps the write position of the list private, so the commands are
+/// collected and written as a sub-list: when the sink is dropped (or its buffer is full)
+/// they are copied with a `RET` into `sceGuGetMemory` and called with `sceGuCallList`,
+/// which advances the stall address, so the GE starts on them right away. A batch costs
+/// two sceGu calls and five words of overhead (`BASE`/`JUMP` of `sceGuGetMemory`, `RET`,
+/// `BASE`/`CALL`). Batches of up to [`GuList::INLINE_LIMIT`] commands that don't start
+/// a draw or a transfer are sent with `sceGuSendCommandi` instead, the GE reaches them
+/// with the next stall address update.
+///
+/// Nothing is written before the sink is dropped, so other sceGu functions must not be
+/// called while an encoder using it is alive.
+pub struct GuList {
+    commands: [(GeCommand, u32); Self::CAPACITY],
+    len: usize,
+}
+
+impl GuList {
+    /// Number of commands collected before they are written
+    pub const CAPACITY: usize = 64;
+    /// Largest batch sent with `sceGuSendCommandi`
+    pub const INLINE_LIMIT: usize = 2;
+
+    pub const fn new() -> Self {
+        Self {
+            commands: [(GeCommand::Nop, 0); Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Write the collected commands into the display list
+    pub fn flush(&mut self) {
+        let len = core::mem::take(&mut self.len);
+        let commands = &self.commands[..len];
+        let kicks = commands.iter().any(|(command, _)| {
+            matches!(
+                command,
+                GeCommand::Prim | GeCommand::Bezier | GeCommand::Spline | GeCommand::TransferStart
+            )
+        });
+        unsafe {
+            if !kicks && len <= Self::INLINE_LIMIT {
+                for &(command, argument) in commands {
+                    sys::sceGuSendCommandi(command, argument as i32);
+                }
+                return;
+            }
+            let list = sys::sceGuGetMemory((len as i32 + 1) * 4) as *mut u32;
+            let words = core::slice::from_raw_parts_mut(list, len + 1);
+            for (word, &(command, argument)) in words.iter_mut().zip(commands) {
+                *word = encoder::command(command, argument);
+            }
+            words[len] = encoder::command(GeCommand::Ret, 0);
+            crate::cache::writeback(words);
+            sys::sceGuCallList(list as *const _);
+        }
+    }
+}
+
+impl Default for GuList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandSink for GuList {
+    fn push(&mut self, command: GeCommand, argument: u32) {
+        if self.len == Self::CAPACITY {
+            self.flush();
+        }
+        self.commands[self.len] = (command, argument);
+        self.len += 1;
+    }
+}
+
+impl Drop for GuList {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -0,0 +1,511 @@
+//! Display list disassembler
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools.
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+/// Nesting limit of followed calls, also stops calls looping back into themselves
+const MAX_CALL_DEPTH: usize = 8;
+
+/// A disassembled command
+///
+/// Consecutive matrix data words are combined into one line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    /// Offset of the first word in bytes
+    pub offset: usize,
+    /// First word of the command
+    pub word: u32,
+    /// Number of words in this line
+    pub len: usize,
+    pub text: String,
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:06x}: {:08x}  {}", self.offset, self.word, self.text)
+    }
+}
+
+/// Iterator over the disassembled commands of a display list
+///
+/// Stops after the `END` command or at the end of the words.
+pub struct Disasm<'a> {
+    words: &'a [u32],
+    /// Address of the first word, used to follow jumps and calls within the list
+    address: Option<u32>,
+    pos: usize,
+    /// Return positions of the followed calls
+    returns: Vec<usize>,
+    base: u32,
+    done: bool,
+}
+
+/// Disassemble display list words
+pub fn disasm(words: &[u32]) -> Disasm<'_> {
+    Disasm {
+        words,
+        address: None,
+        pos: 0,
+        returns: Vec::new(),
+        base: 0,
+        done: false,
+    }
+}
+
+/// Disassemble display list words located at `address`
+///
+/// Forward jumps within the list are followed, which skips data embedded in the list
+/// (e.g. by `sceGuGetMemory`) instead of decoding it as commands. Calls of sub-lists
+/// embedded in the list (e.g. by `GuList` of psp-gfx) are followed up to
+/// their `RET`, the lines of the sub-list come right after the `CALL`.
+///
+/// ```ignore
+/// let address = unsafe { psp_gfx::BUFFER.0.as_ptr() } as u32;
+/// for line in psp_gfx::ge::disasm_at(frame.display_list(), address) {
+///     psp::dprintln!("{line}");
+/// }
+/// ```
+pub fn disasm_at(words: &[u32], address: u32) -> Disasm<'_> {
+    Disasm {
+        address: Some(address & 0x0fffffff),
+        ..disasm(words)
+    }
+}
+
+impl Iterator for Disasm<'_> {
+    type Item = Line;
+
+    fn next(&mut self) -> Option<Line> {
+        if self.done || self.pos >= self.words.len() {
+            return None;
+        }
+        let start = self.pos;
+        let word = self.words[start];
+        let opcode = (word >> 24) as u8;
+        let arg = word & 0xffffff;
+        self.pos += 1;
+
+        let (text, len) = if is_matrix_data(opcode) {
+            // Group the data words of a matrix upload, matrices have 4 rows of 3 values
+            // (4 for the projection matrix)
+            let mut values = String::new();
+            let columns = if opcode == 0x3f { 4 } else { 3 };
+            let mut count = 0;
+            loop {
+                let value = float24(self.words[self.pos - 1] & 0xffffff);
+                if count % columns == 0 {
+                    values.push_str(if count == 0 { "[" } else { "] [" });
+                } else {
+                    values.push(' ');
+                }
+                values.push_str(&format!("{value}"));
+                count += 1;
+                let next = self.words.get(self.pos);
+                if count == 4 * columns || next.is_none_or(|next| (next >> 24) as u8 != opcode) {
+                    break;
+                }
+                self.pos += 1;
+            }
+            (format!("{} {values}]", name(opcode)), self.pos - start)
+        } else {
+            (self.decode(opcode, arg), 1)
+        };
+
+        Some(Line {
+            offset: start * 4,
+            word,
+            len,
+            text,
+        })
+    }
+}
+
+impl Disasm<'_> {
+    fn address(&self, arg: u32) -> u32 {
+        ((self.base & 0xf0000) << 8) | arg
+    }
+
+    fn decode(&mut self, opcode: u8, arg: u32) -> String {
+        let name = name(opcode);
+        let on_off = |arg: u32| if arg & 1 != 0 { "on" } else { "off" };
+        match opcode {
+            0x00 | 0x0f | 0xcb | 0xcc => String::from(name),
+            0x08 => {
+                let target = self.address(arg);
+                let skip = self
+                    .address
+                    .map(|address| target.wrapping_sub(address) as usize / 4)
+                    .filter(|&pos| pos > self.pos && pos <= self.words.len());
+                match skip {
+                    Some(pos) => {
+                        let skipped = pos - self.pos;
+                        self.pos = pos;
+                        format!("{name} {target:08x} (skipped {skipped} words)")
+                    }
+                    None => format!("{name} {target:08x}"),
+                }
+            }
+            0x0a => {
+                let target = self.address(arg);
+                let call = self
+                    .address
+                    .map(|address| target.wrapping_sub(address) as usize / 4)
+                    .filter(|&pos| pos < self.words.len() && self.returns.len() < MAX_CALL_DEPTH);
+                if let Some(pos) = call {
+                    self.returns.push(self.pos);
+                    self.pos = pos;
+                }
+                format!("{name} {target:08x}")
+            }
+            0x0b => {
+                if let Some(pos) = self.returns.pop() {
+                    self.pos = pos;
+                }
+                String::from(name)
+            }
+            0x01 | 0x02 | 0x09 => format!("{name} {:08x}", self.address(arg)),
+            0x07 => format!("{name} count={}", arg & 0xffff),
+            0x04 => format!(
+                "{name} {} count={}",
+                primitive_name(arg >> 16),
+                arg & 0xffff
+            ),
+            0x05 => format!("{name} {}x{}", arg & 0xff, (arg >> 8) & 0xff),
+            0x06 => format!(
+                "{name} {}x{} u={} v={}",
+                arg & 0xff,
+                (arg >> 8) & 0xff,
+                spline_mode_name(arg >> 16),
+                spline_mode_name(arg >> 18)
+            ),
+            0x0c => {
+                self.done = true;
+                String::from(name)
+            }
+            0x0e => format!("{name} {:02x} {:04x}", arg >> 16, arg & 0xffff),
+            0x10 => {
+                self.base = arg;
+                format!("{name} {:02x}000000", (arg >> 16) & 0xf)
+            }
+            0x12 => format!("{name} {}", vertex_type(arg)),
+            0x17..=0x28 => format!("{name} {}", on_off(arg)),
+            0x2c..=0x33 | 0x42..=0x4b | 0x5b | 0x63..=0x8e | 0xcd | 0xce | 0xd0 => {
+                format!("{name} {}", float24(arg))
+            }
+            0x2a | 0x3a | 0x3c | 0x3e | 0x40 => format!("{name} {arg}"),
+            0x36 => format!("{name} {}x{}", arg & 0xff, (arg >> 8) & 0xff),
+            0x37 => {
+                let primitive = ["triangles", "lines", "points"];
+                format!("{name} {}", primitive.get(arg as usize & 3).unwrap_or(&"?"))
+            }
+            0x4c | 0x4d => format!("{name} {}", arg as f32 / 16.),
+            0x50 => format!("{name} {}", if arg & 1 != 0 { "gouraud" } else { "flat" }),
+            0x54..=0x57 | 0x5c | 0x8f..=0x9a | 0xca | 0xcf | 0xd9 | 0xe0 | 0xe1 | 0xe8 => {
+                format!("{name} {arg:06x}")
+            }
+            0x58 | 0x5d | 0xe9 => format!("{name} {:02x}", arg & 0xff),
+            0x9c | 0x9e | 0xa0..=0xa7 | 0xb0 | 0xb2 | 0xb4 => format!("{name} {arg:06x}"),
+            0x9d | 0x9f | 0xa8..=0xaf | 0xb1 | 0xb3 | 0xb5 => format!(
+                "{name} {:02x}000000 width={}",
+                (arg >> 16) & 0xf,
+                arg & 0xffff
+            ),
+            0xb8..=0xbf => format!("{name} {}x{}", 1 << (arg & 0xf), 1 << ((arg >> 8) & 0xf)),
+            0xc2 => format!(
+                "{name} levels={} swizzle={}",
+                ((arg >> 16) & 7) + 1,
+                on_off(arg)
+            ),
+            0xc3 => format!("{name} {}", pixel_format_name(arg)),
+            0xc6 => format!(
+                "{name} min={} mag={}",
+                filter_name(arg & 7),
+                filter_name((arg >> 8) & 7)
+            ),
+            0xc7 => format!(
+                "{name} u={} v={}",
+                wrap_name(arg & 1),
+                wrap_name((arg >> 8) & 1)
+            ),
+            0xc8 => {
+                let mode = ["auto", "const", "slope"];
+                let bias = ((arg >> 16) as u8 as i8) as f32 / 16.;
+                format!(
+                    "{name} {} bias={bias}",
+                    mode.get(arg as usize & 3).unwrap_or(&"?")
+                )
+            }
+            0xd2 => format!("{name} {}", pixel_format_name(arg & 3)),
+            0xd3 => format!("{name} {} flags={:x}", on_off(arg), (arg >> 8) & 7),
+            0xd4 | 0xd5 | 0x15 | 0x16 | 0xeb | 0xec | 0xee => {
+                format!("{name} {},{}", arg & 0x3ff, (arg >> 10) & 0x3ff)
+            }
+            0xd6 | 0xd7 => format!("{name} {}", arg & 0xffff),
+            0xde => {
+                let test = ["never", "always", "eq", "ne", "lt", "le", "gt", "ge"];
+                format!("{name} {}", test[arg as usize & 7])
+            }
+            0xe7 => format!("{name} {}", on_off(arg)),
+            _ => format!("{name} {arg:06x}"),
+        }
+    }
+}
+
+fn float24(arg: u32) -> f32 {
+    f32::from_bits(arg << 8)
+}
+
+fn is_matrix_data(opcode: u8) -> bool {
+    matches!(opcode, 0x2b | 0x3b | 0x3d | 0x3f | 0x41)
+}
+
+fn primitive_name(primitive: u32) -> &'static str {
+    match primitive & 7 {
+        0 => "points",
+        1 => "lines",
+        2 => "line_strip",
+        3 => "triangles",
+        4 => "triangle_strip",
+        5 => "triangle_fan",
+        6 => "sprites",
+        _ => "?",
+    }
+}
+
+fn spline_mode_name(mode: u32) -> &'static str {
+    ["fill_fill", "open_fill", "fill_open", "open_open"][mode as usize & 3]
+}
+
+fn pixel_format_name(format: u32) -> &'static str {
+    const NAMES: [&str; 11] = [
+        "5650", "5551", "4444", "8888", "t4", "t8", "t16", "t32", "dxt1", "dxt3", "dxt5",
+    ];
+    NAMES.get(format as usize).unwrap_or(&"?")
+}
+
+fn filter_name(filter: u32) -> &'static str {
+    match filter {
+        0 => "nearest",
+        1 => "linear",
+        4 => "nearest_mipmap_nearest",
+        5 => "linear_mipmap_nearest",
+        6 => "nearest_mipmap_linear",
+        7 => "linear_mipmap_linear",
+        _ => "?",
+    }
+}
+
+fn wrap_name(wrap: u32) -> &'static str {
+    if wrap == 0 { "repeat" } else { "clamp" }
+}
+
+/// Describe vertex type bits
+pub fn vertex_type(bits: u32) -> String {
+    const FORMAT: [&str; 4] = ["none", "8", "16", "f32"];
+    const COLOR: [&str; 8] = ["none", "?", "?", "?", "5650", "5551", "4444", "8888"];
+    const INDEX: [&str; 4] = ["none", "u8", "u16", "?"];
+    let mut text = format!(
+        "uv={} color={} normal={} pos={} weight={}",
+        FORMAT[bits as usize & 3],
+        COLOR[(bits as usize >> 2) & 7],
+        FORMAT[(bits as usize >> 5) & 3],
+        FORMAT[(bits as usize >> 7) & 3],
+        FORMAT[(bits as usize >> 9) & 3],
+    );
+    text.push_str(&format!(" index={}", INDEX[(bits as usize >> 11) & 3]));
+    let weights = ((bits >> 14) & 7) + 1;
+    let morphs = ((bits >> 18) & 7) + 1;
+    if bits & (3 << 9) != 0 {
+        text.push_str(&format!(" weights={weights}"));
+    }
+    if morphs > 1 {
+        text.push_str(&format!(" morphs={morphs}"));
+    }
+    text.push_str(if bits & (1 << 23) != 0 { " 2d" } else { " 3d" });
+    text
+}
+
+/// Get the mnemonic of a command
+pub fn name(opcode: u8) -> &'static str {
+    const LIGHT: [[&str; 4]; 12] = [
+        ["LXP0", "LXP1", "LXP2", "LXP3"],
+        ["LYP0", "LYP1", "LYP2", "LYP3"],
+        ["LZP0", "LZP1", "LZP2", "LZP3"],
+        ["LXD0", "LXD1", "LXD2", "LXD3"],
+        ["LYD0", "LYD1", "LYD2", "LYD3"],
+        ["LZD0", "LZD1", "LZD2", "LZD3"],
+        ["LCA0", "LCA1", "LCA2", "LCA3"],
+        ["LLA0", "LLA1", "LLA2", "LLA3"],
+        ["LQA0", "LQA1", "LQA2", "LQA3"],
+        ["LAC0", "LAC1", "LAC2", "LAC3"],
+        ["LDC0", "LDC1", "LDC2", "LDC3"],
+        ["LSC0", "LSC1", "LSC2", "LSC3"],
+    ];
+    match opcode {
+        0x00 => "NOP",
+        0x01 => "VADDR",
+        0x02 => "IADDR",
+        0x04 => "PRIM",
+        0x05 => "BEZIER",
+        0x06 => "SPLINE",
+        0x07 => "BBOX",
+        0x08 => "JUMP",
+        0x09 => "BJUMP",
+        0x0a => "CALL",
+        0x0b => "RET",
+        0x0c => "END",
+        0x0e => "SIGNAL",
+        0x0f => "FINISH",
+        0x10 => "BASE",
+        0x12 => "VTYPE",
+        0x13 => "OFFSET",
+        0x14 => "ORIGIN",
+        0x15 => "REGION1",
+        0x16 => "REGION2",
+        0x17 => "LTE",
+        0x18 => "LTE0",
+        0x19 => "LTE1",
+        0x1a => "LTE2",
+        0x1b => "LTE3",
+        0x1c => "CPE",
+        0x1d => "BCE",
+        0x1e => "TME",
+        0x1f => "FGE",
+        0x20 => "DTE",
+        0x21 => "ABE",
+        0x22 => "ATE",
+        0x23 => "ZTE",
+        0x24 => "STE",
+        0x25 => "AAE",
+        0x26 => "PCE",
+        0x27 => "CTE",
+        0x28 => "LOE",
+        0x2a => "BOFS",
+        0x2b => "BONE",
+        0x2c..=0x33 => {
+            ["MW0", "MW1", "MW2", "MW3", "MW4", "MW5", "MW6", "MW7"][opcode as usize - 0x2c]
+        }
+        0x36 => "PSUB",
+        0x37 => "PPRIM",
+        0x38 => "PFACE",
+        0x3a => "WMS",
+        0x3b => "WORLD",
+        0x3c => "VMS",
+        0x3d => "VIEW",
+        0x3e => "PMS",
+        0x3f => "PROJ",
+        0x40 => "TMS",
+        0x41 => "TMATRIX",
+        0x42 => "XSCALE",
+        0x43 => "YSCALE",
+        0x44 => "ZSCALE",
+        0x45 => "XPOS",
+        0x46 => "YPOS",
+        0x47 => "ZPOS",
+        0x48 => "USCALE",
+        0x49 => "VSCALE",
+        0x4a => "UOFFSET",
+        0x4b => "VOFFSET",
+        0x4c => "OFFSETX",
+        0x4d => "OFFSETY",
+        0x50 => "SHADE",
+        0x51 => "RNORM",
+        0x53 => "CMAT",
+        0x54 => "EMC",
+        0x55 => "AMC",
+        0x56 => "DMC",
+        0x57 => "SMC",
+        0x58 => "AMA",
+        0x5b => "SPOW",
+        0x5c => "ALC",
+        0x5d => "ALA",
+        0x5e => "LMODE",
+        0x5f..=0x62 => ["LT0", "LT1", "LT2", "LT3"][opcode as usize - 0x5f],
+        0x63..=0x86 => {
+            let idx = opcode as usize - 0x63;
+            let (group, component) = (idx / 12, idx % 12);
+            LIGHT[group * 3 + component % 3][component / 3]
+        }
+        0x87..=0x8a => ["SPOTEXP0", "SPOTEXP1", "SPOTEXP2", "SPOTEXP3"][opcode as usize - 0x87],
+        0x8b..=0x8e => ["SPOTCUT0", "SPOTCUT1", "SPOTCUT2", "SPOTCUT3"][opcode as usize - 0x8b],
+        0x8f..=0x9a => {
+            let idx = opcode as usize - 0x8f;
+            LIGHT[9 + idx % 3][idx / 3]
+        }
+        0x9b => "CULL",
+        0x9c => "FBP",
+        0x9d => "FBW",
+        0x9e => "ZBP",
+        0x9f => "ZBW",
+        0xa0..=0xa7 => [
+            "TBP0", "TBP1", "TBP2", "TBP3", "TBP4", "TBP5", "TBP6", "TBP7",
+        ][opcode as usize - 0xa0],
+        0xa8..=0xaf => [
+            "TBW0", "TBW1", "TBW2", "TBW3", "TBW4", "TBW5", "TBW6", "TBW7",
+        ][opcode as usize - 0xa8],
+        0xb0 => "CBP",
+        0xb1 => "CBW",
+        0xb2 => "TRXSBP",
+        0xb3 => "TRXSBW",
+        0xb4 => "TRXDBP",
+        0xb5 => "TRXDBW",
+        0xb8..=0xbf => [
+            "TSIZE0", "TSIZE1", "TSIZE2", "TSIZE3", "TSIZE4", "TSIZE5", "TSIZE6", "TSIZE7",
+        ][opcode as usize - 0xb8],
+        0xc0 => "TMAP",
+        0xc1 => "TSHADE",
+        0xc2 => "TMODE",
+        0xc3 => "TPSM",
+        0xc4 => "CLOAD",
+        0xc5 => "CLUT",
+        0xc6 => "TFLT",
+        0xc7 => "TWRAP",
+        0xc8 => "TBIAS",
+        0xc9 => "TFUNC",
+        0xca => "TEC",
+        0xcb => "TFLUSH",
+        0xcc => "TSYNC",
+        0xcd => "FFAR",
+        0xce => "FDIST",
+        0xcf => "FCOL",
+        0xd0 => "TSLOPE",
+        0xd2 => "PSM",
+        0xd3 => "CLEAR",
+        0xd4 => "SCISSOR1",
+        0xd5 => "SCISSOR2",
+        0xd6 => "NEARZ",
+        0xd7 => "FARZ",
+        0xd8 => "CTST",
+        0xd9 => "CREF",
+        0xda => "CMSK",
+        0xdb => "ATST",
+        0xdc => "STST",
+        0xdd => "SOP",
+        0xde => "ZTST",
+        0xdf => "ALPHA",
+        0xe0 => "SFIX",
+        0xe1 => "DFIX",
+        0xe2..=0xe5 => ["DTH0", "DTH1", "DTH2", "DTH3"][opcode as usize - 0xe2],
+        0xe6 => "LOP",
+        0xe7 => "ZMSK",
+        0xe8 => "PMSKC",
+        0xe9 => "PMSKA",
+        0xea => "TRXKICK",
+        0xeb => "TRXSPOS",
+        0xec => "TRXDPOS",
+        0xee => "TRXSIZE",
+        0xf0 => "VSCX",
+        0xf1 => "VSCY",
+        0xf2 => "VSCZ",
+        0xf3 => "VTCS",
+        0xf4 => "VTCT",
+        0xf5 => "VTCQ",
+        0xf6 => "VCV",
+        0xf7 => "VAP",
+        0xf8 => "VFC",
+        0xf9 => "VSCV",
+        _ => "UNKNOWN",
+    }
+}
@@ -0,0 +1,358 @@
+//! Recording of display lists in the GE dump format of PPSSPP
+//!
+//! A dump contains the GE state at the start of the list, the commands of the list and
+//! copies of all memory referenced by draws (vertices, indices, textures and CLUTs), so
+//! it can be replayed in the GE debugger of PPSSPP without the program that created it.
+//!
+//! Dumps are written as version 4 (the last version using snappy compression). The data
+//! is stored as snappy literals, i.e. uncompressed.
+//!
+//! Limitations: only jumps and calls within the list are followed, and block transfers
+//! (`TRXKICK`) are recorded as commands without their source memory.
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools.
+
+use alloc::vec::Vec;
+
+const MAGIC: &[u8; 8] = b"PPSSPPGE";
+const VERSION: u32 = 4;
+
+/// Nesting limit of followed calls
+const MAX_CALL_DEPTH: usize = 8;
+
+/// Size of the saved GE context (`sceGeSaveContext`) in words
+pub const CONTEXT_SIZE: usize = 512;
+
+/// Command types of the dump format
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum CommandType {
+    Init = 0,
+    Registers = 1,
+    Vertices = 2,
+    Indices = 3,
+    Clut = 4,
+    Display = 9,
+    Texture0 = 0x10,
+}
+
+/// Bits per pixel of the texture formats
+const TEXTURE_BITS: [u32; 11] = [16, 16, 16, 32, 4, 8, 16, 32, 4, 8, 8];
+
+/// Record a display list
+///
+/// - `list`: commands of the list, located at `list_address`
+/// - `context`: GE state at the start of the list, as saved by `sceGeSaveContext`
+/// - `read_memory`: fills the buffer with the memory at an address, returns `false` for
+///   invalid addresses
+pub fn record(
+    list: &[u32],
+    list_address: u32,
+    context: &[u32; CONTEXT_SIZE],
+    read_memory: impl Fn(u32, &mut [u8]) -> bool,
+) -> Vec<u8> {
+    let mut recorder = Recorder {
+        read_memory,
+        commands: Vec::new(),
+        pushbuf: Vec::new(),
+        registers: Vec::new(),
+        emitted: Vec::new(),
+    };
+    let init: Vec<u8> = context.iter().flat_map(|word| word.to_le_bytes()).collect();
+    recorder.push_data(CommandType::Init as u8, &init, 4);
+
+    let mut state = State::default();
+    let mut pos = 0;
+    let mut returns = Vec::new();
+    while let Some(&word) = list.get(pos) {
+        pos += 1;
+        let opcode = word >> 24;
+        let arg = word & 0xffffff;
+        match opcode {
+            // BASE, VADDR, IADDR, OFFSET, ORIGIN only prepare later commands, the replay
+            // emits its own addresses
+            0x10 => state.base = arg,
+            0x01 => state.vertex_address = state.address(arg),
+            0x02 => state.index_address = state.address(arg),
+            0x13 | 0x14 => (),
+            0x08 => {
+                let target = state.address(arg).wrapping_sub(list_address & 0x0fffffff) / 4;
+                if (target as usize) < list.len() {
+                    pos = target as usize;
+                }
+            }
+            // CALL, RET of sub-lists in the list (e.g. written by `GuList`)
+            0x0a => {
+                let target = state.address(arg).wrapping_sub(list_address & 0x0fffffff) / 4;
+                if (target as usize) < list.len() && returns.len() < MAX_CALL_DEPTH {
+                    returns.push(pos);
+                    pos = target as usize;
+                }
+            }
+            0x0b => {
+                if let Some(ret) = returns.pop() {
+                    pos = ret;
+                }
+            }
+            0x09 | 0x0e | 0x0f => (),
+            0x0c => break,
+            // PRIM, BEZIER, SPLINE
+            0x04..=0x06 => {
+                let count = match opcode {
+                    0x04 => arg & 0xffff,
+                    _ => (arg & 0xff) * ((arg >> 8) & 0xff),
+                };
+                recorder.flush_registers();
+                recorder.draw(&mut state, count);
+                recorder.registers.push(word);
+            }
+            // LOADCLUT
+            0xc4 => {
+                recorder.flush_registers();
+                let address = state.clut_address();
+                recorder.push_memory(CommandType::Clut as u8, address, (arg & 0x3f) * 32, 16);
+                recorder.registers.push(word);
+            }
+            _ => {
+                state.registers[opcode as usize] = arg;
+                recorder.registers.push(word);
+            }
+        }
+    }
+    recorder.flush_registers();
+
+    let display = [
+        0x04000000 | state.registers[0x9c],
+        state.registers[0x9d] & 0x7ff,
+        state.registers[0xd2] & 3,
+    ];
+    let display: Vec<u8> = display.iter().flat_map(|word| word.to_le_bytes()).collect();
+    recorder.push_data(CommandType::Display as u8, &display, 4);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    // Game ID (9 bytes) and padding
+    out.extend_from_slice(&[0; 12]);
+    out.extend_from_slice(&((recorder.commands.len() / 9) as u32).to_le_bytes());
+    out.extend_from_slice(&(recorder.pushbuf.len() as u32).to_le_bytes());
+    write_compressed(&mut out, &recorder.commands);
+    write_compressed(&mut out, &recorder.pushbuf);
+    out
+}
+
+/// GE registers needed to find the memory used by draws
+struct State {
+    registers: [u32; 256],
+    base: u32,
+    vertex_address: u32,
+    index_address: u32,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        let mut registers = [0; 256];
+        // Fallback display buffer, lists started by `sceGuStart` set it
+        registers[0x9d] = 512;
+        registers[0xd2] = 3;
+        Self {
+            registers,
+            base: 0,
+            vertex_address: 0,
+            index_address: 0,
+        }
+    }
+}
+
+impl State {
+    fn address(&self, arg: u32) -> u32 {
+        ((self.base & 0xf0000) << 8) | arg
+    }
+
+    fn clut_address(&self) -> u32 {
+        ((self.registers[0xb1] & 0xf0000) << 8) | self.registers[0xb0]
+    }
+
+    fn texture_address(&self, level: usize) -> u32 {
+        ((self.registers[0xa8 + level] & 0xf0000) << 8) | self.registers[0xa0 + level]
+    }
+
+    /// Get the size of a vertex and the size of an index
+    fn vertex_size(&self) -> (u32, u32) {
+        const FORMAT: [u32; 4] = [0, 1, 2, 4];
+        const COLOR: [u32; 8] = [0, 0, 0, 0, 2, 2, 2, 4];
+        let vtype = self.registers[0x12];
+        let weights = ((vtype >> 14) & 7) + 1;
+        let morphs = ((vtype >> 18) & 7) + 1;
+        let components = [
+            (FORMAT[(vtype as usize >> 9) & 3], weights),
+            (FORMAT[vtype as usize & 3], 2),
+            (COLOR[(vtype as usize >> 2) & 7], 1),
+            (FORMAT[(vtype as usize >> 5) & 3], 3),
+            (FORMAT[(vtype as usize >> 7) & 3], 3),
+        ];
+        let mut size = 0u32;
+        let mut align = 1u32;
+        for (component, count) in components {
+            if component != 0 {
+                size = size.next_multiple_of(component) + component * count;
+                align = align.max(component);
+            }
+        }
+        let index_size = FORMAT[(vtype as usize >> 11) & 3];
+        (size.next_multiple_of(align) * morphs, index_size)
+    }
+}
+
+struct Recorder<F> {
+    read_memory: F,
+    /// Packed commands (u8 type, u32 size, u32 pushbuf offset)
+    commands: Vec<u8>,
+    pushbuf: Vec<u8>,
+    registers: Vec<u32>,
+    /// Emitted memory (address, size, pushbuf offset), to store repeated data only once
+    emitted: Vec<(u32, u32, u32)>,
+}
+
+impl<F: Fn(u32, &mut [u8]) -> bool> Recorder<F> {
+    fn push_command(&mut self, ty: u8, size: u32, offset: u32) {
+        self.commands.push(ty);
+        self.commands.extend_from_slice(&size.to_le_bytes());
+        self.commands.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    fn push_data(&mut self, ty: u8, data: &[u8], align: usize) {
+        let offset = self.pushbuf.len().next_multiple_of(align);
+        self.pushbuf.resize(offset, 0);
+        self.pushbuf.extend_from_slice(data);
+        self.push_command(ty, data.len() as u32, offset as u32);
+    }
+
+    fn push_memory(&mut self, ty: u8, address: u32, size: u32, align: usize) {
+        if size == 0 {
+            return;
+        }
+        let mut data = alloc::vec![0; size as usize];
+        if !(self.read_memory)(address, &mut data) {
+            return;
+        }
+        let previous = self
+            .emitted
+            .iter()
+            .find(|&&(prev_address, prev_size, offset)| {
+                let offset = offset as usize;
+                prev_address == address
+                    && prev_size == size
+                    && self.pushbuf[offset..offset + size as usize] == data
+            });
+        match previous {
+            Some(&(_, _, offset)) => self.push_command(ty, size, offset),
+            None => {
+                let offset = self.pushbuf.len().next_multiple_of(align);
+                self.emitted.push((address, size, offset as u32));
+                self.push_data(ty, &data, align);
+            }
+        }
+    }
+
+    fn flush_registers(&mut self) {
+        if self.registers.is_empty() {
+            return;
+        }
+        let data: Vec<u8> = self
+            .registers
+            .drain(..)
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        self.push_data(CommandType::Registers as u8, &data, 4);
+    }
+
+    /// Emit the memory used by a draw of `count` vertices
+    fn draw(&mut self, state: &mut State, count: u32) {
+        let registers = &state.registers;
+        let vtype = registers[0x12];
+        let texturing = registers[0x1e] & 1 != 0 || vtype & 3 != 0;
+        if texturing {
+            let format = registers[0xc3] as usize & 0xf;
+            let max_level = (registers[0xc2] >> 16) & 7;
+            for level in 0..=max_level as usize {
+                let address = state.texture_address(level);
+                let size = registers[0xb8 + level];
+                let width = 1u32 << (size & 0xf);
+                let height = 1 << ((size >> 8) & 0xf);
+                let buffer_width = registers[0xa8 + level] & 0x7ff;
+                let extra_width = width.saturating_sub(buffer_width);
+                let bits = TEXTURE_BITS.get(format).copied().unwrap_or(32);
+                let bytes = bits * (buffer_width * height + extra_width) / 8;
+                // TEXTURE0..=TEXTURE7 are consecutive
+                let ty = CommandType::Texture0 as u8 + level as u8;
+                self.push_memory(ty, address, bytes, 16);
+            }
+        }
+
+        let (vertex_size, index_size) = state.vertex_size();
+        let mut vertex_count = count;
+        if index_size != 0 {
+            let mut indices = alloc::vec![0; (count * index_size) as usize];
+            if (self.read_memory)(state.index_address, &mut indices) {
+                vertex_count = indices
+                    .chunks_exact(index_size as usize)
+                    .map(|index| match index {
+                        [index] => *index as u32,
+                        [lo, hi] => u16::from_le_bytes([*lo, *hi]) as u32,
+                        _ => u32::from_le_bytes(index.try_into().unwrap()),
+                    })
+                    .max()
+                    .map_or(0, |max| max + 1);
+            }
+            self.push_memory(
+                CommandType::Indices as u8,
+                state.index_address,
+                count * index_size,
+                4,
+            );
+            state.index_address += count * index_size;
+        } else {
+            let bytes = count * vertex_size;
+            self.push_memory(CommandType::Vertices as u8, state.vertex_address, bytes, 4);
+            state.vertex_address += bytes;
+            return;
+        }
+        self.push_memory(
+            CommandType::Vertices as u8,
+            state.vertex_address,
+            vertex_count * vertex_size,
+            4,
+        );
+    }
+}
+
+/// Write a length prefixed snappy block containing `data` as literals
+fn write_compressed(out: &mut Vec<u8>, data: &[u8]) {
+    let mut block = Vec::with_capacity(data.len() + data.len() / 65536 * 3 + 8);
+    let mut len = data.len();
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            block.push(byte);
+            break;
+        }
+        block.push(byte | 0x80);
+    }
+    for chunk in data.chunks(65536) {
+        let len = chunk.len() - 1;
+        if len < 60 {
+            block.push((len << 2) as u8);
+        } else if len < 256 {
+            block.push(60 << 2);
+            block.push(len as u8);
+        } else {
+            block.push(61 << 2);
+            block.extend_from_slice(&(len as u16).to_le_bytes());
+        }
+        block.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&(block.len() as u32).to_le_bytes());
+    out.extend_from_slice(&block);
+}
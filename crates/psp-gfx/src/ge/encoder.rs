@@ -0,0 +1,394 @@
+//! Typed builder for GE command words
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools. The argument
+//! types are imported from `super::sys`, which is `psp::sys` in `psp-gfx` and a mirror of
+//! the same types in `psp-gfx-tools`.
+
+use alloc::vec::Vec;
+
+use super::sys::{
+    ColorFunc, FrontFaceDirection, GeCommand, GuPrimitive, GuTexWrapMode, LogicalOperation,
+    MatrixMode, MipmapLevel, PatchPrimitive, ScePspFMatrix4, ShadingModel, SplineMode, StencilFunc,
+    StencilOperation, TextureFilter, TextureLevelMode, TexturePixelFormat, VertexType,
+};
+
+/// Maximum amount of vertices (or indices) drawn by a single `PRIM` command
+pub const MAX_PRIM_COUNT: usize = 0xffff;
+
+/// Encode a command word, only the lower 24 bits of `argument` are used
+pub const fn command(command: GeCommand, argument: u32) -> u32 {
+    ((command as u32) << 24) | (argument & 0xffffff)
+}
+
+/// Convert a float to the 24-bit float format of command arguments
+pub const fn float24(value: f32) -> u32 {
+    value.to_bits() >> 8
+}
+
+/// Destination of encoded commands
+pub trait CommandSink {
+    fn push(&mut self, command: GeCommand, argument: u32);
+}
+
+impl CommandSink for Vec<u32> {
+    fn push(&mut self, command: GeCommand, argument: u32) {
+        Vec::push(self, self::command(command, argument));
+    }
+}
+
+/// Typed builder for GE commands
+pub struct Encoder<S: CommandSink> {
+    sink: S,
+}
+
+impl<S: CommandSink> Encoder<S> {
+    pub const fn new(sink: S) -> Self {
+        Self { sink }
+    }
+
+    /// Get the sink back
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+
+    /// Emit a command with a raw 24-bit argument
+    pub fn raw(&mut self, command: GeCommand, argument: u32) -> &mut Self {
+        self.sink.push(command, argument & 0xffffff);
+        self
+    }
+
+    /// Emit a command with a float argument
+    pub fn float(&mut self, command: GeCommand, value: f32) -> &mut Self {
+        self.raw(command, float24(value))
+    }
+
+    /// Emit a command with an enabled/disabled argument
+    pub fn enable(&mut self, command: GeCommand, enabled: bool) -> &mut Self {
+        self.raw(command, enabled as u32)
+    }
+
+    /// Set the vertex format of following draws (`VTYPE`)
+    pub fn vertex_type(&mut self, vertex_type: VertexType) -> &mut Self {
+        self.raw(GeCommand::VertexType, vertex_type.bits() as u32)
+    }
+
+    /// Set the vertex address of following draws (`BASE` and `VADDR`)
+    pub fn vertex_address(&mut self, address: u32) -> &mut Self {
+        self.raw(GeCommand::Base, (address >> 8) & 0xf0000)
+            .raw(GeCommand::Vaddr, address)
+    }
+
+    /// Set the index address of following draws (`BASE` and `IADDR`)
+    pub fn index_address(&mut self, address: u32) -> &mut Self {
+        self.raw(GeCommand::Base, (address >> 8) & 0xf0000)
+            .raw(GeCommand::Iaddr, address)
+    }
+
+    /// Draw `count` vertices (`PRIM`)
+    pub fn prim(&mut self, primitive: GuPrimitive, count: u16) -> &mut Self {
+        self.raw(GeCommand::Prim, ((primitive as u32) << 16) | count as u32)
+    }
+
+    /// Draw a bezier surface of `u_count * v_count` control points
+    pub fn bezier(&mut self, u_count: u8, v_count: u8) -> &mut Self {
+        self.raw(GeCommand::Bezier, ((v_count as u32) << 8) | u_count as u32)
+    }
+
+    /// Draw a spline surface of `u_count * v_count` control points
+    pub fn spline(
+        &mut self,
+        u_count: u8,
+        v_count: u8,
+        u_mode: SplineMode,
+        v_mode: SplineMode,
+    ) -> &mut Self {
+        let argument = ((v_mode as u32) << 18)
+            | ((u_mode as u32) << 16)
+            | ((v_count as u32) << 8)
+            | u_count as u32;
+        self.raw(GeCommand::Spline, argument)
+    }
+
+    pub fn patch_divide(&mut self, u: u8, v: u8) -> &mut Self {
+        self.raw(GeCommand::PatchDivision, ((v as u32) << 8) | u as u32)
+    }
+
+    pub fn patch_front_face(&mut self, direction: FrontFaceDirection) -> &mut Self {
+        self.raw(GeCommand::PatchFacing, direction as u32)
+    }
+
+    pub fn patch_primitive(&mut self, primitive: PatchPrimitive) -> &mut Self {
+        let argument = match primitive {
+            PatchPrimitive::TriangleStrip => 0,
+            PatchPrimitive::LineStrip => 1,
+            PatchPrimitive::Points => 2,
+        };
+        self.raw(GeCommand::PatchPrimitive, argument)
+    }
+
+    /// Set ambient (including alpha), diffuse and specular material colors to `abgr`
+    pub fn material_color(&mut self, abgr: u32) -> &mut Self {
+        self.raw(GeCommand::MaterialAmbient, abgr)
+            .raw(GeCommand::MaterialAlpha, abgr >> 24)
+            .raw(GeCommand::MaterialDiffuse, abgr)
+            .raw(GeCommand::MaterialSpecular, abgr)
+    }
+
+    pub fn shade_model(&mut self, model: ShadingModel) -> &mut Self {
+        let smooth = matches!(model, ShadingModel::Smooth);
+        self.raw(GeCommand::ShadeMode, smooth as u32)
+    }
+
+    /// Load a matrix, only the 4x3 part is used for all but the projection matrix
+    pub fn matrix(&mut self, mode: MatrixMode, matrix: &ScePspFMatrix4) -> &mut Self {
+        let (number, data) = match mode {
+            MatrixMode::Projection => (GeCommand::ProjMatrixNumber, GeCommand::ProjMatrixData),
+            MatrixMode::View => (GeCommand::ViewMatrixNumber, GeCommand::ViewMatrixData),
+            MatrixMode::Model => (GeCommand::WorldMatrixNumber, GeCommand::WorldMatrixData),
+            MatrixMode::Texture => (GeCommand::TGenMatrixNumber, GeCommand::TGenMatrixData),
+        };
+        let rows = [&matrix.x, &matrix.y, &matrix.z, &matrix.w];
+        self.raw(number, 0);
+        for row in rows {
+            self.float(data, row.x)
+                .float(data, row.y)
+                .float(data, row.z);
+            if let MatrixMode::Projection = mode {
+                self.float(data, row.w);
+            }
+        }
+        self
+    }
+
+    /// Set texture format and number of mip levels, flushes the texture cache
+    pub fn tex_mode(
+        &mut self,
+        format: TexturePixelFormat,
+        max_level: u8,
+        swizzle: bool,
+    ) -> &mut Self {
+        self.raw(
+            GeCommand::TexMode,
+            ((max_level as u32) << 16) | swizzle as u32,
+        )
+        .raw(GeCommand::TexFormat, format as u32)
+        .tex_flush()
+    }
+
+    /// Set address and size of a mip level, flushes the texture cache
+    ///
+    /// Width and height must be powers of two up to 512.
+    pub fn tex_image(
+        &mut self,
+        level: MipmapLevel,
+        address: u32,
+        width: u32,
+        height: u32,
+        buffer_width: u32,
+    ) -> &mut Self {
+        let level = level as usize;
+        let log2 = |size: u32| 31 - (size & 0x3ff).leading_zeros();
+        self.raw(TEX_ADDR[level], address)
+            .raw(
+                TEX_BUF_WIDTH[level],
+                ((address >> 8) & 0xf0000) | buffer_width,
+            )
+            .raw(TEX_SIZE[level], (log2(height) << 8) | log2(width))
+            .tex_flush()
+    }
+
+    pub fn tex_flush(&mut self) -> &mut Self {
+        self.raw(GeCommand::TexFlush, 0)
+    }
+
+    pub fn tex_filter(&mut self, min: TextureFilter, mag: TextureFilter) -> &mut Self {
+        self.raw(GeCommand::TexFilter, ((mag as u32) << 8) | min as u32)
+    }
+
+    pub fn tex_wrap(&mut self, u: GuTexWrapMode, v: GuTexWrapMode) -> &mut Self {
+        self.raw(GeCommand::TexWrap, ((v as u32) << 8) | u as u32)
+    }
+
+    pub fn tex_scale(&mut self, u: f32, v: f32) -> &mut Self {
+        self.float(GeCommand::TexScaleU, u)
+            .float(GeCommand::TexScaleV, v)
+    }
+
+    pub fn tex_offset(&mut self, u: f32, v: f32) -> &mut Self {
+        self.float(GeCommand::TexOffsetU, u)
+            .float(GeCommand::TexOffsetV, v)
+    }
+
+    /// Set mip level selection, `bias` is in levels (`-8.0..=8.0`, in 1/16 steps)
+    pub fn tex_level(&mut self, mode: TextureLevelMode, bias: f32) -> &mut Self {
+        let bias = ((bias * 16.) as i32).clamp(-128, 128) as u32;
+        self.raw(GeCommand::TexLevel, (bias << 16) | mode as u32)
+    }
+
+    pub fn tex_slope(&mut self, slope: f32) -> &mut Self {
+        self.float(GeCommand::TexLodSlope, slope)
+    }
+
+    /// Set constant color of the texture function (`0xBBGGRR`)
+    pub fn tex_env_color(&mut self, bgr: u32) -> &mut Self {
+        self.raw(GeCommand::TexEnvColor, bgr)
+    }
+
+    /// Map normalized device coordinates to the rectangle of `width * height` screen
+    /// coordinates around `center` (in the 4096x4096 drawing area)
+    pub fn viewport(&mut self, center: (f32, f32), width: f32, height: f32) -> &mut Self {
+        self.float(GeCommand::ViewportXScale, width / 2.)
+            .float(GeCommand::ViewportYScale, -height / 2.)
+            .float(GeCommand::ViewportXCenter, center.0)
+            .float(GeCommand::ViewportYCenter, center.1)
+    }
+
+    /// Set the screen coordinates of the top left pixel of the framebuffer
+    pub fn offset(&mut self, x: u32, y: u32) -> &mut Self {
+        self.raw(GeCommand::OffsetX, x << 4)
+            .raw(GeCommand::OffsetY, y << 4)
+    }
+
+    /// Set the stencil test, comparing `reference & mask` with `stencil & mask`
+    pub fn stencil_func(&mut self, func: StencilFunc, reference: u8, mask: u8) -> &mut Self {
+        self.raw(
+            GeCommand::StencilTest,
+            ((mask as u32) << 16) | ((reference as u32) << 8) | func as u32,
+        )
+    }
+
+    /// Set the stencil update for a failed stencil test, failed depth test and passed tests
+    pub fn stencil_op(
+        &mut self,
+        fail: StencilOperation,
+        depth_fail: StencilOperation,
+        pass: StencilOperation,
+    ) -> &mut Self {
+        self.raw(
+            GeCommand::StencilOp,
+            ((pass as u32) << 16) | ((depth_fail as u32) << 8) | fail as u32,
+        )
+    }
+
+    /// Set the bits which are not written, `0xAABBGGRR` (alpha is the stencil)
+    pub fn pixel_mask(&mut self, mask: u32) -> &mut Self {
+        self.raw(GeCommand::MaskRgb, mask)
+            .raw(GeCommand::MaskAlpha, mask >> 24)
+    }
+
+    pub fn depth_write(&mut self, enabled: bool) -> &mut Self {
+        self.raw(GeCommand::ZWriteDisable, !enabled as u32)
+    }
+
+    /// Set linear fog between the `near` and `far` distance (in view space)
+    pub fn fog(&mut self, near: f32, far: f32, bgr: u32) -> &mut Self {
+        let distance = far - near;
+        let scale = if distance != 0. { 1. / distance } else { 0. };
+        self.raw(GeCommand::FogColor, bgr)
+            .float(GeCommand::Fog1, far)
+            .float(GeCommand::Fog2, scale)
+    }
+
+    /// Set the color test, comparing `color & mask` with `reference & mask` (`0xBBGGRR`)
+    pub fn color_test(&mut self, func: ColorFunc, reference: u32, mask: u32) -> &mut Self {
+        self.raw(GeCommand::ColorTest, func as u32)
+            .raw(GeCommand::ColorRef, reference)
+            .raw(GeCommand::ColorTestmask, mask)
+    }
+
+    pub fn logic_op(&mut self, op: LogicalOperation) -> &mut Self {
+        self.raw(GeCommand::LogicOp, op as u32)
+    }
+
+    /// Set the dither matrix, values are added to the color before reducing its precision
+    /// (`-8..=7`)
+    pub fn dither(&mut self, matrix: &[[i8; 4]; 4]) -> &mut Self {
+        const ROWS: [GeCommand; 4] = [
+            GeCommand::Dith0,
+            GeCommand::Dith1,
+            GeCommand::Dith2,
+            GeCommand::Dith3,
+        ];
+        for (command, row) in ROWS.into_iter().zip(matrix) {
+            let argument = row.iter().enumerate().fold(0, |acc, (idx, &value)| {
+                acc | ((value as u32 & 0xf) << (idx * 4))
+            });
+            self.raw(command, argument);
+        }
+        self
+    }
+
+    /// Wait for block transfers before reading textures
+    pub fn tex_sync(&mut self) -> &mut Self {
+        self.raw(GeCommand::TexSync, 0)
+    }
+
+    /// Set source image and position of block transfers
+    pub fn transfer_source(
+        &mut self,
+        address: u32,
+        buffer_width: u32,
+        x: u32,
+        y: u32,
+    ) -> &mut Self {
+        self.raw(GeCommand::TransferSrc, address)
+            .raw(
+                GeCommand::TransferSrcW,
+                ((address >> 8) & 0xff0000) | buffer_width,
+            )
+            .raw(GeCommand::TransferSrcPos, (y << 10) | x)
+    }
+
+    /// Set destination image and position of block transfers
+    pub fn transfer_destination(
+        &mut self,
+        address: u32,
+        buffer_width: u32,
+        x: u32,
+        y: u32,
+    ) -> &mut Self {
+        self.raw(GeCommand::TransferDst, address)
+            .raw(
+                GeCommand::TransferDstW,
+                ((address >> 8) & 0xff0000) | buffer_width,
+            )
+            .raw(GeCommand::TransferDstPos, (y << 10) | x)
+    }
+
+    /// Copy a `width * height` rectangle of 16 or 32-bit pixels (`TRXSIZE` and `TRXKICK`)
+    pub fn transfer_start(&mut self, width: u32, height: u32, bits32: bool) -> &mut Self {
+        self.raw(GeCommand::TransferSize, ((height - 1) << 10) | (width - 1))
+            .raw(GeCommand::TransferStart, bits32 as u32)
+    }
+}
+
+const TEX_ADDR: [GeCommand; 8] = [
+    GeCommand::TexAddr0,
+    GeCommand::TexAddr1,
+    GeCommand::TexAddr2,
+    GeCommand::TexAddr3,
+    GeCommand::TexAddr4,
+    GeCommand::TexAddr5,
+    GeCommand::TexAddr6,
+    GeCommand::TexAddr7,
+];
+const TEX_BUF_WIDTH: [GeCommand; 8] = [
+    GeCommand::TexBufWidth0,
+    GeCommand::TexBufWidth1,
+    GeCommand::TexBufWidth2,
+    GeCommand::TexBufWidth3,
+    GeCommand::TexBufWidth4,
+    GeCommand::TexBufWidth5,
+    GeCommand::TexBufWidth6,
+    GeCommand::TexBufWidth7,
+];
+const TEX_SIZE: [GeCommand; 8] = [
+    GeCommand::TexSize0,
+    GeCommand::TexSize1,
+    GeCommand::TexSize2,
+    GeCommand::TexSize3,
+    GeCommand::TexSize4,
+    GeCommand::TexSize5,
+    GeCommand::TexSize6,
+    GeCommand::TexSize7,
+];
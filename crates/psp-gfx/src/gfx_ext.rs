@@ -1,15 +1,110 @@
-use psp::sys::GuPrimitive;
+use psp::sys::{GuPrimitive, ShadingModel};
 
-use crate::{Frame, define_vertex_layout, rect::Rect};
+use crate::{
+    Frame,
+    color::Color32,
+    define_vertex_layout,
+    rect::{Point, Rect},
+};
 
+pub mod shapes;
+
+use shapes::Shape;
+
+define_vertex_layout! {
+    ShapeVertex {
+        vertex: VERTEX_32BITF,
+        transform: TRANSFORM_2D,
+    }
+}
+
+define_vertex_layout! {
+    GradientVertex {
+        vertex: VERTEX_32BITF,
+        transform: TRANSFORM_2D,
+        color: COLOR_8888,
+    }
+}
+
+/// Immediate-mode 2D drawing helpers
+///
+/// All shapes are drawn using the current color, to set the color use [`Frame::set_color`].
+/// Gradients use per-vertex colors instead and are drawn with [`ShadingModel::Smooth`], the
+/// previous shading model is restored afterwards.
 pub trait GfxExt {
+    /// Draw a filled rectangle at the specified position
     fn gfx_rect(&self, rect: Rect);
+
+    /// Draw a rectangle outline, inside of the rectangle
+    fn gfx_rect_outline(&self, rect: Rect, thickness: f32);
+
+    /// Draw a filled rectangle with rounded corners
+    fn gfx_rounded_rect(&self, rect: Rect, radius: f32);
+
+    /// Draw an outline of a rectangle with rounded corners, inside of the rectangle
+    fn gfx_rounded_rect_outline(&self, rect: Rect, radius: f32, thickness: f32);
+
+    /// Draw a line (single pixel wide if `thickness <= 1`)
+    fn gfx_line(&self, from: Point<f32>, to: Point<f32>, thickness: f32);
+
+    /// Draw connected line segments, optionally connecting the last point to the first one
+    fn gfx_polyline(&self, points: &[Point<f32>], thickness: f32, closed: bool);
+
+    /// Draw a filled triangle
+    fn gfx_triangle(&self, a: Point<f32>, b: Point<f32>, c: Point<f32>);
+
+    /// Draw a filled convex polygon
+    fn gfx_polygon(&self, points: &[Point<f32>]);
+
+    /// Draw a filled circle
+    fn gfx_circle(&self, center: Point<f32>, radius: f32);
+
+    /// Draw a circle outline
+    fn gfx_circle_outline(&self, center: Point<f32>, radius: f32, thickness: f32);
+
+    /// Draw a filled ellipse
+    fn gfx_ellipse(&self, center: Point<f32>, radius_x: f32, radius_y: f32);
+
+    /// Draw an ellipse outline
+    fn gfx_ellipse_outline(&self, center: Point<f32>, radius_x: f32, radius_y: f32, thickness: f32);
+
+    /// Draw a circular arc outline (angles in radians, clockwise)
+    fn gfx_arc(
+        &self,
+        center: Point<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        thickness: f32,
+    );
+
+    /// Draw a filled circular sector (angles in radians, clockwise)
+    fn gfx_pie(&self, center: Point<f32>, radius: f32, start_angle: f32, end_angle: f32);
+
+    /// Draw a pre-tessellated shape
+    fn gfx_shape(&self, shape: &Shape);
+
+    /// Draw a rectangle with a horizontal gradient, from left to right
+    fn gfx_gradient_rect_h(&self, rect: Rect, left: Color32, right: Color32);
+
+    /// Draw a rectangle with a vertical gradient, from top to bottom
+    fn gfx_gradient_rect_v(&self, rect: Rect, top: Color32, bottom: Color32);
+
+    /// Draw a rectangle with a color for each corner, interpolated across the rectangle
+    fn gfx_gradient_rect(
+        &self,
+        rect: Rect,
+        top_left: Color32,
+        top_right: Color32,
+        bottom_left: Color32,
+        bottom_right: Color32,
+    );
+
+    /// Draw a filled circle with a radial gradient, from the center to the edge
+    fn gfx_gradient_circle(&self, center: Point<f32>, radius: f32, inner: Color32, outer: Color32);
 }
 
 impl<'gfx> GfxExt for Frame<'gfx> {
-    /// Draw a filled rectangle at the specified position
-    ///
-    /// To set the color use [`Frame::set_color`]
     fn gfx_rect(&self, rect: Rect) {
         define_vertex_layout! {
             Vertex {
@@ -17,10 +112,152 @@ impl<'gfx> GfxExt for Frame<'gfx> {
                 transform: TRANSFORM_2D,
             }
         };
-        let vertex_buf = self.get_memory(&[
-            Vertex::from_position2(rect.x as u16, rect.y as u16),
-            Vertex::from_position2((rect.x + rect.w) as u16, (rect.y + rect.h) as u16),
-        ]);
-        self.draw_array(GuPrimitive::Sprites, &vertex_buf);
+        let vertex_buf = self.get_memory(&rect.to_sprites_vertices(Vertex::from_position2));
+        self.draw_array(GuPrimitive::Sprites, vertex_buf);
+    }
+
+    fn gfx_rect_outline(&self, rect: Rect, thickness: f32) {
+        let (x, y, w, h) = rect_f32(rect);
+        self.gfx_shape(&shapes::rect_outline(x, y, w, h, thickness));
+    }
+
+    fn gfx_rounded_rect(&self, rect: Rect, radius: f32) {
+        let (x, y, w, h) = rect_f32(rect);
+        self.gfx_shape(&shapes::rounded_rect(x, y, w, h, radius));
+    }
+
+    fn gfx_rounded_rect_outline(&self, rect: Rect, radius: f32, thickness: f32) {
+        let (x, y, w, h) = rect_f32(rect);
+        self.gfx_shape(&shapes::rounded_rect_outline(x, y, w, h, radius, thickness));
     }
+
+    fn gfx_line(&self, from: Point<f32>, to: Point<f32>, thickness: f32) {
+        self.gfx_shape(&shapes::line(from, to, thickness));
+    }
+
+    fn gfx_polyline(&self, points: &[Point<f32>], thickness: f32, closed: bool) {
+        self.gfx_shape(&shapes::polyline(points, thickness, closed));
+    }
+
+    fn gfx_triangle(&self, a: Point<f32>, b: Point<f32>, c: Point<f32>) {
+        self.gfx_shape(&shapes::triangle(a, b, c));
+    }
+
+    fn gfx_polygon(&self, points: &[Point<f32>]) {
+        self.gfx_shape(&shapes::polygon(points));
+    }
+
+    fn gfx_circle(&self, center: Point<f32>, radius: f32) {
+        self.gfx_shape(&shapes::ellipse(center, radius, radius));
+    }
+
+    fn gfx_circle_outline(&self, center: Point<f32>, radius: f32, thickness: f32) {
+        self.gfx_shape(&shapes::ellipse_outline(center, radius, radius, thickness));
+    }
+
+    fn gfx_ellipse(&self, center: Point<f32>, radius_x: f32, radius_y: f32) {
+        self.gfx_shape(&shapes::ellipse(center, radius_x, radius_y));
+    }
+
+    fn gfx_ellipse_outline(
+        &self,
+        center: Point<f32>,
+        radius_x: f32,
+        radius_y: f32,
+        thickness: f32,
+    ) {
+        self.gfx_shape(&shapes::ellipse_outline(
+            center, radius_x, radius_y, thickness,
+        ));
+    }
+
+    fn gfx_arc(
+        &self,
+        center: Point<f32>,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        thickness: f32,
+    ) {
+        self.gfx_shape(&shapes::arc(
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            thickness,
+        ));
+    }
+
+    fn gfx_pie(&self, center: Point<f32>, radius: f32, start_angle: f32, end_angle: f32) {
+        self.gfx_shape(&shapes::pie(center, radius, start_angle, end_angle));
+    }
+
+    fn gfx_shape(&self, shape: &Shape) {
+        if shape.points.is_empty() {
+            return;
+        }
+        let vertices: alloc::vec::Vec<_> = shape
+            .points
+            .iter()
+            .map(|&Point { x, y }| ShapeVertex::from_position2(x, y))
+            .collect();
+        let vertex_buf = self.get_memory(&vertices);
+        self.draw_array(shape.primitive, vertex_buf);
+    }
+
+    fn gfx_gradient_rect_h(&self, rect: Rect, left: Color32, right: Color32) {
+        self.gfx_gradient_rect(rect, left, right, left, right);
+    }
+
+    fn gfx_gradient_rect_v(&self, rect: Rect, top: Color32, bottom: Color32) {
+        self.gfx_gradient_rect(rect, top, top, bottom, bottom);
+    }
+
+    fn gfx_gradient_rect(
+        &self,
+        rect: Rect,
+        top_left: Color32,
+        top_right: Color32,
+        bottom_left: Color32,
+        bottom_right: Color32,
+    ) {
+        let (x, y, w, h) = rect_f32(rect);
+        let vertices = [
+            GradientVertex::from_position2_color(x, y, top_left),
+            GradientVertex::from_position2_color(x + w, y, top_right),
+            GradientVertex::from_position2_color(x, y + h, bottom_left),
+            GradientVertex::from_position2_color(x + w, y + h, bottom_right),
+        ];
+        self.draw_smooth(GuPrimitive::TriangleStrip, &vertices);
+    }
+
+    fn gfx_gradient_circle(&self, center: Point<f32>, radius: f32, inner: Color32, outer: Color32) {
+        // The first point of the fan is the center
+        let shape = shapes::ellipse(center, radius, radius);
+        let vertices: alloc::vec::Vec<_> = shape
+            .points
+            .iter()
+            .enumerate()
+            .map(|(idx, &Point { x, y })| {
+                let color = if idx == 0 { inner } else { outer };
+                GradientVertex::from_position2_color(x, y, color)
+            })
+            .collect();
+        self.draw_smooth(shape.primitive, &vertices);
+    }
+}
+
+impl Frame<'_> {
+    /// Draw with smooth shading, restoring the previous shading model afterwards
+    fn draw_smooth(&self, primitive: GuPrimitive, vertices: &[GradientVertex]) {
+        let previous = self.shading_model();
+        self.set_shading_model(ShadingModel::Smooth);
+        let vertex_buf = self.get_memory(vertices);
+        self.draw_array(primitive, vertex_buf);
+        self.set_shading_model(previous);
+    }
+}
+
+fn rect_f32(rect: Rect) -> (f32, f32, f32, f32) {
+    (rect.x as f32, rect.y as f32, rect.w as f32, rect.h as f32)
 }
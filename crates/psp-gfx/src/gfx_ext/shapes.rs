@@ -0,0 +1,293 @@
+//! Tessellation of 2D shapes into screen-space vertices
+//!
+//! Angles are in radians, measured clockwise from the positive X axis (Y points down)
+
+use alloc::vec::Vec;
+use core::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{ge::sys::GuPrimitive, rect};
+
+type Point = rect::Point<f32>;
+
+/// Tessellated shape, ready to be drawn with a single draw call
+#[derive(Clone, Debug)]
+pub struct Shape {
+    pub primitive: GuPrimitive,
+    pub points: Vec<Point>,
+}
+
+impl Shape {
+    fn new(primitive: GuPrimitive, points: Vec<Point>) -> Self {
+        Self { primitive, points }
+    }
+}
+
+/// Get amount of segments used to approximate a full circle of the specified radius
+///
+/// Roughly one segment per 4 pixels of circumference, between 8 and 64 segments
+pub fn segments_for_radius(radius: f32) -> usize {
+    let segments = libm::ceilf(TAU * radius.abs() / 4.) as usize;
+    segments.clamp(8, 64)
+}
+
+/// Straight line between two points
+///
+/// Lines with `thickness <= 1` are drawn as single pixel lines
+pub fn line(from: Point, to: Point, thickness: f32) -> Shape {
+    if thickness <= 1. {
+        return Shape::new(GuPrimitive::Lines, alloc::vec![from, to]);
+    }
+    let Point { x: nx, y: ny } = normal(from, to, thickness / 2.);
+    Shape::new(
+        GuPrimitive::TriangleStrip,
+        alloc::vec![
+            from.offset(nx, ny),
+            from.offset(-nx, -ny),
+            to.offset(nx, ny),
+            to.offset(-nx, -ny),
+        ],
+    )
+}
+
+/// Connected line segments, optionally connecting the last point to the first one
+pub fn polyline(points: &[Point], thickness: f32, closed: bool) -> Shape {
+    let mut path = points.to_vec();
+    if closed && let Some(&first) = points.first() {
+        path.push(first);
+    }
+    if thickness <= 1. {
+        return Shape::new(GuPrimitive::LineStrip, path);
+    }
+    let mut triangles = Vec::with_capacity(path.len().saturating_sub(1) * 6);
+    for segment in path.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        let Point { x: nx, y: ny } = normal(from, to, thickness / 2.);
+        let quad = [
+            from.offset(nx, ny),
+            from.offset(-nx, -ny),
+            to.offset(nx, ny),
+            to.offset(-nx, -ny),
+        ];
+        triangles.extend_from_slice(&[quad[0], quad[1], quad[2], quad[2], quad[1], quad[3]]);
+    }
+    Shape::new(GuPrimitive::Triangles, triangles)
+}
+
+/// Filled triangle
+pub fn triangle(a: Point, b: Point, c: Point) -> Shape {
+    Shape::new(GuPrimitive::Triangles, alloc::vec![a, b, c])
+}
+
+/// Filled convex polygon
+pub fn polygon(points: &[Point]) -> Shape {
+    Shape::new(GuPrimitive::TriangleFan, points.to_vec())
+}
+
+/// Filled ellipse
+pub fn ellipse(center: Point, radius_x: f32, radius_y: f32) -> Shape {
+    pie_ellipse(center, radius_x, radius_y, 0., TAU)
+}
+
+/// Ellipse outline, centered on the edge of the ellipse
+pub fn ellipse_outline(center: Point, radius_x: f32, radius_y: f32, thickness: f32) -> Shape {
+    arc_ellipse(center, radius_x, radius_y, 0., TAU, thickness)
+}
+
+/// Circular arc outline, centered on the edge of the circle
+pub fn arc(center: Point, radius: f32, start_angle: f32, end_angle: f32, thickness: f32) -> Shape {
+    arc_ellipse(center, radius, radius, start_angle, end_angle, thickness)
+}
+
+/// Filled circular sector ("pie slice")
+pub fn pie(center: Point, radius: f32, start_angle: f32, end_angle: f32) -> Shape {
+    pie_ellipse(center, radius, radius, start_angle, end_angle)
+}
+
+/// Rectangle outline, drawn inside of the rectangle
+pub fn rect_outline(x: f32, y: f32, w: f32, h: f32, thickness: f32) -> Shape {
+    if thickness <= 1. {
+        return Shape::new(
+            GuPrimitive::LineStrip,
+            alloc::vec![
+                Point::new(x, y),
+                Point::new(x + w, y),
+                Point::new(x + w, y + h),
+                Point::new(x, y + h),
+                Point::new(x, y),
+            ],
+        );
+    }
+    let outer = [
+        Point::new(x, y),
+        Point::new(x + w, y),
+        Point::new(x + w, y + h),
+        Point::new(x, y + h),
+    ];
+    let t = thickness.min(w / 2.).min(h / 2.);
+    let inner = [
+        Point::new(x + t, y + t),
+        Point::new(x + w - t, y + t),
+        Point::new(x + w - t, y + h - t),
+        Point::new(x + t, y + h - t),
+    ];
+    ring(&outer, &inner, true)
+}
+
+/// Filled rectangle with rounded corners
+pub fn rounded_rect(x: f32, y: f32, w: f32, h: f32, radius: f32) -> Shape {
+    let mut points = alloc::vec![Point::new(x + w / 2., y + h / 2.)];
+    let perimeter = rounded_rect_perimeter(x, y, w, h, radius, corner_segments(radius));
+    points.extend_from_slice(&perimeter);
+    points.push(perimeter[0]);
+    Shape::new(GuPrimitive::TriangleFan, points)
+}
+
+/// Outline of a rectangle with rounded corners, drawn inside of the rectangle
+pub fn rounded_rect_outline(x: f32, y: f32, w: f32, h: f32, radius: f32, thickness: f32) -> Shape {
+    let segments = corner_segments(radius);
+    let outer = rounded_rect_perimeter(x, y, w, h, radius, segments);
+    if thickness <= 1. {
+        let mut points = outer.clone();
+        points.push(outer[0]);
+        return Shape::new(GuPrimitive::LineStrip, points);
+    }
+    let t = thickness.min(w / 2.).min(h / 2.);
+    let inner = rounded_rect_perimeter(
+        x + t,
+        y + t,
+        w - 2. * t,
+        h - 2. * t,
+        (radius - t).max(0.),
+        segments,
+    );
+    ring(&outer, &inner, true)
+}
+
+/// Triangle strip connecting two paths with the same amount of points
+fn ring(outer: &[Point], inner: &[Point], closed: bool) -> Shape {
+    debug_assert_eq!(outer.len(), inner.len());
+    let mut points = Vec::with_capacity(outer.len() * 2 + 2);
+    for (&outer, &inner) in outer.iter().zip(inner) {
+        points.push(outer);
+        points.push(inner);
+    }
+    if closed && !outer.is_empty() {
+        points.push(outer[0]);
+        points.push(inner[0]);
+    }
+    Shape::new(GuPrimitive::TriangleStrip, points)
+}
+
+fn arc_ellipse(
+    center: Point,
+    radius_x: f32,
+    radius_y: f32,
+    start_angle: f32,
+    end_angle: f32,
+    thickness: f32,
+) -> Shape {
+    let segments = arc_segments(radius_x.max(radius_y), start_angle, end_angle);
+    if thickness <= 1. {
+        return Shape::new(
+            GuPrimitive::LineStrip,
+            arc_points(center, radius_x, radius_y, start_angle, end_angle, segments),
+        );
+    }
+    let half = thickness / 2.;
+    let outer = arc_points(
+        center,
+        radius_x + half,
+        radius_y + half,
+        start_angle,
+        end_angle,
+        segments,
+    );
+    let inner = arc_points(
+        center,
+        (radius_x - half).max(0.),
+        (radius_y - half).max(0.),
+        start_angle,
+        end_angle,
+        segments,
+    );
+    ring(&outer, &inner, false)
+}
+
+fn pie_ellipse(
+    center: Point,
+    radius_x: f32,
+    radius_y: f32,
+    start_angle: f32,
+    end_angle: f32,
+) -> Shape {
+    let segments = arc_segments(radius_x.max(radius_y), start_angle, end_angle);
+    let mut points = alloc::vec![center];
+    points.extend(arc_points(
+        center,
+        radius_x,
+        radius_y,
+        start_angle,
+        end_angle,
+        segments,
+    ));
+    Shape::new(GuPrimitive::TriangleFan, points)
+}
+
+/// Points along an elliptic arc (`segments + 1` points, including both ends)
+fn arc_points(
+    center: Point,
+    radius_x: f32,
+    radius_y: f32,
+    start_angle: f32,
+    end_angle: f32,
+    segments: usize,
+) -> Vec<Point> {
+    (0..=segments)
+        .map(|idx| {
+            let angle = start_angle + (end_angle - start_angle) * idx as f32 / segments as f32;
+            center.offset(radius_x * libm::cosf(angle), radius_y * libm::sinf(angle))
+        })
+        .collect()
+}
+
+fn arc_segments(radius: f32, start_angle: f32, end_angle: f32) -> usize {
+    let fraction = ((end_angle - start_angle).abs() / TAU).min(1.);
+    (libm::ceilf(segments_for_radius(radius) as f32 * fraction) as usize).max(1)
+}
+
+fn corner_segments(radius: f32) -> usize {
+    (segments_for_radius(radius) / 4).max(1)
+}
+
+/// Perimeter of a rounded rectangle, clockwise starting at the top edge of the top right corner
+fn rounded_rect_perimeter(
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radius: f32,
+    segments: usize,
+) -> Vec<Point> {
+    let r = radius.min(w / 2.).min(h / 2.).max(0.);
+    let corners = [
+        (Point::new(x + w - r, y + r), -FRAC_PI_2),
+        (Point::new(x + w - r, y + h - r), 0.),
+        (Point::new(x + r, y + h - r), FRAC_PI_2),
+        (Point::new(x + r, y + r), PI),
+    ];
+    let mut points = Vec::with_capacity(4 * (segments + 1));
+    for (center, start) in corners {
+        points.extend(arc_points(center, r, r, start, start + FRAC_PI_2, segments));
+    }
+    points
+}
+
+/// Get a vector perpendicular to the line, with the specified length
+fn normal(from: Point, to: Point, length: f32) -> Point {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let len = libm::sqrtf(dx * dx + dy * dy);
+    if len == 0. {
+        return Point::new(0., 0.);
+    }
+    Point::new(-dy / len * length, dx / len * length)
+}
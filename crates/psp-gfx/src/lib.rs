@@ -4,51 +4,125 @@
 
 extern crate alloc;
 
-use core::mem::ManuallyDrop;
+use alloc::{boxed::Box, rc::Rc, string::String, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    mem::ManuallyDrop,
+    ops::Range,
+    sync::atomic::{AtomicU32, Ordering},
+};
 use psp::{
     Align16, BUF_WIDTH, SCREEN_HEIGHT, SCREEN_WIDTH,
     sys::{
-        self, DisplayPixelFormat, GuPrimitive, GuState, ShadingModel, TextureColorComponent,
-        TextureEffect, TexturePixelFormat,
+        self, DisplayPixelFormat, FrontFaceDirection, GuPrimitive, GuState, PatchPrimitive,
+        ScePspFMatrix4, ShadingModel, SplineMode, TextureColorComponent, TextureEffect,
+        TextureFilter, TextureLevelMode, TexturePixelFormat,
     },
-    vram_alloc::get_vram_allocator,
 };
 
 #[cfg(feature = "gfx_ext")]
 pub mod gfx_ext;
 
+#[doc(hidden)]
+pub use bytemuck;
+
 pub mod buffer;
+pub mod cache;
+pub mod clip;
 pub mod color;
+pub mod ge;
 pub mod index;
+pub mod mesh;
+pub mod model;
+pub mod pixel;
+pub mod quantize;
 pub mod rect;
+pub mod sampler;
+pub mod screenshot;
+#[cfg(feature = "gfx_ext")]
+pub mod sprite_batch;
+pub mod stencil;
+#[cfg(feature = "gfx_ext")]
+pub mod text;
+pub mod texture;
+pub mod transfer;
 pub mod vertex;
+pub mod viewport;
+pub mod vram;
 
 use buffer::{Buffer, TransientBuffer};
+use clip::ClipVertex;
 use color::Color32;
+use ge::{Encoder, GuList};
 use index::IndexItem;
-use rect::Rect;
+use rect::{Point, Rect};
+use sampler::{Sampler, TextureMapping};
+use screenshot::Framebuffer;
+use texture::{Texture, TextureLevel, VramTexture};
+use transfer::TransferImage;
 use vertex::Vertex;
+use viewport::Viewport;
+use vram::{VramBlock, VramHeap, VramStats};
 
 pub static mut BUFFER: Align16<[u32; 0x40000]> = Align16([0; 0x40000]);
 
+/// Number of frames started so far
+static FRAME_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Get index of the current (or last) frame
+///
+/// Used to determine if resources are still referenced by the display list
+#[cfg(feature = "ttf")]
+pub(crate) fn current_frame() -> u32 {
+    FRAME_COUNTER.load(Ordering::Relaxed)
+}
+
+/// Pixel format of the color buffers
+const DISPLAY_FORMAT: DisplayPixelFormat = DisplayPixelFormat::Psm8888;
+
+/// Start of VRAM
+const VRAM_ADDRESS: u32 = 0x0400_0000;
+
 pub struct PspGfx {
     pub(crate) fbp0: *mut u8,
     pub(crate) fbp1: *mut u8,
     pub(crate) zbp: *mut u8,
+    /// Buffer currently shown on the display (`fbp0` or `fbp1`)
+    displayed: *mut u8,
+    /// Viewport set by [`Frame::set_viewport`]
+    viewport: Cell<Viewport>,
+    /// Shading model set by [`Frame::set_shading_model`]
+    smooth_shading: Cell<bool>,
+    vram: Rc<RefCell<VramHeap>>,
+    /// RAM written by [`Frame::copy_image`] in this frame, invalidated once the GE is done
+    copy_targets: RefCell<Vec<Range<u32>>>,
+    /// Color and depth buffers
+    _buffers: [VramBlock; 3],
+    capture: Option<Capture>,
+}
+
+/// Pending capture of a frame, see [`PspGfx::capture_next_frame`]
+struct Capture {
+    path: String,
+    /// GE state at the start of the frame
+    context: Option<Box<sys::GeContext>>,
 }
 
 impl PspGfx {
     pub fn init() -> Self {
-        let allocator = get_vram_allocator().unwrap();
-        let fbp0 = allocator
-            .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
-            .as_mut_ptr_from_zero();
-        let fbp1 = allocator
-            .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm8888)
-            .as_mut_ptr_from_zero();
-        let zbp = allocator
-            .alloc_texture_pixels(BUF_WIDTH, SCREEN_HEIGHT, TexturePixelFormat::Psm4444)
-            .as_mut_ptr_from_zero();
+        let vram = Rc::new(RefCell::new(VramHeap::new(unsafe {
+            sys::sceGeEdramGetSize()
+        })));
+        let alloc_buffer = |format| {
+            let size = vram::image_size(BUF_WIDTH, SCREEN_HEIGHT, format);
+            VramBlock::alloc(&vram, size, vram::TEXTURE_ALIGN).unwrap()
+        };
+        let buffers = [
+            alloc_buffer(TexturePixelFormat::Psm8888),
+            alloc_buffer(TexturePixelFormat::Psm8888),
+            alloc_buffer(TexturePixelFormat::Psm4444),
+        ];
+        let [fbp0, fbp1, zbp] = buffers.each_ref().map(VramBlock::as_mut_ptr_from_zero);
 
         unsafe {
             sys::sceGuInit();
@@ -57,7 +131,7 @@ impl PspGfx {
                 psp::sys::GuContextType::Direct,
                 BUFFER.0.as_mut_ptr() as *mut _,
             );
-            sys::sceGuDrawBuffer(DisplayPixelFormat::Psm8888, fbp0 as _, BUF_WIDTH as i32);
+            sys::sceGuDrawBuffer(DISPLAY_FORMAT, fbp0 as _, BUF_WIDTH as i32);
             sys::sceGuDispBuffer(
                 SCREEN_WIDTH as i32,
                 SCREEN_HEIGHT as i32,
@@ -65,8 +139,13 @@ impl PspGfx {
                 BUF_WIDTH as i32,
             );
             sys::sceGuDepthBuffer(zbp as _, BUF_WIDTH as i32);
-            sys::sceGuOffset(2048 - (SCREEN_WIDTH / 2), 2048 - (SCREEN_HEIGHT / 2));
-            sys::sceGuViewport(2048, 2048, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+            let viewport = Viewport::FULL_SCREEN;
+            let (x, y) = viewport.offset();
+            Encoder::new(GuList::new()).offset(x, y).viewport(
+                viewport.center(),
+                SCREEN_WIDTH as f32,
+                SCREEN_HEIGHT as f32,
+            );
             sys::sceGuDepthRange(65535, 0);
             sys::sceGuScissor(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
             sys::sceGuEnable(GuState::ScissorTest);
@@ -76,43 +155,274 @@ impl PspGfx {
             sys::sceGuDisplay(true);
         }
 
-        Self { fbp0, fbp1, zbp }
+        Self {
+            fbp0,
+            fbp1,
+            zbp,
+            displayed: fbp1,
+            viewport: Cell::new(Viewport::FULL_SCREEN),
+            smooth_shading: Cell::new(false),
+            vram,
+            copy_targets: RefCell::new(Vec::new()),
+            _buffers: buffers,
+            capture: None,
+        }
+    }
+
+    /// Allocate `size` bytes of VRAM aligned to `align`, returns `None` if VRAM is full
+    pub fn alloc_vram(&self, size: u32, align: u32) -> Option<VramBlock> {
+        VramBlock::alloc(&self.vram, size, align)
+    }
+
+    /// Allocate `size` bytes of VRAM aligned to `align` which
+    /// [`PspGfx::defragment_vram`] may move, returns `None` if VRAM is full
+    pub fn alloc_vram_relocatable(&self, size: u32, align: u32) -> Option<VramBlock> {
+        VramBlock::alloc_relocatable(&self.vram, size, align)
+    }
+
+    /// Allocate VRAM for an image, e.g. a texture or render target
+    pub fn alloc_vram_image(
+        &self,
+        width: u32,
+        height: u32,
+        format: TexturePixelFormat,
+    ) -> Option<VramBlock> {
+        let size = vram::image_size(width, height, format);
+        self.alloc_vram(size, vram::TEXTURE_ALIGN)
+    }
+
+    /// Copy a texture to VRAM with GE block transfers
+    ///
+    /// Waits for the copy to finish. Returns `None` if there isn't enough free VRAM. The
+    /// texture is relocatable, it reads its address when it's bound.
+    pub fn upload_texture(&self, texture: &Texture) -> Option<VramTexture> {
+        let data = texture.bytes();
+        let block = self.alloc_vram_relocatable(data.len() as u32, vram::TEXTURE_ALIGN)?;
+        let src = data.as_ptr() as u32;
+        let dst = block.as_mut_ptr_direct_to_vram() as u32;
+        let len = data.len() as u32;
+        cache::sync_transfer(src..src + len, false, dst..dst + len, true);
+        unsafe {
+            sys::sceGuStart(
+                psp::sys::GuContextType::Direct,
+                BUFFER.0.as_mut_ptr() as *mut _,
+            );
+            transfer::encode_linear_copy(&mut Encoder::new(GuList::new()), src, dst, len);
+            sys::sceGuFinish();
+            sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+        }
+        Some(VramTexture::new(
+            texture.format(),
+            texture.levels().to_vec(),
+            block,
+        ))
+    }
+
+    pub fn vram_stats(&self) -> VramStats {
+        self.vram.borrow().stats()
+    }
+
+    /// Move relocatable VRAM blocks together, merging the free space between them
+    ///
+    /// Waits for the GE to finish first, no display list can reference the old addresses
+    /// as frames borrow `self`. Pinned blocks stay in place, relocatable blocks (e.g.
+    /// [`VramTexture`]s) have to read their offset again.
+    pub fn defragment_vram(&mut self) -> VramStats {
+        let moves = self.vram.borrow_mut().compact();
+        if !moves.is_empty() {
+            unsafe {
+                sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+                // Cached writes to the old addresses must not land after the copy
+                sys::sceKernelDcacheWritebackInvalidateAll();
+                let vram = cache::uncached_address(VRAM_ADDRESS) as *mut u8;
+                for vram::VramMove { from, to, size } in moves {
+                    core::ptr::copy(
+                        vram.add(from as usize),
+                        vram.add(to as usize),
+                        size as usize,
+                    );
+                }
+            }
+        }
+        self.vram_stats()
+    }
+
+    /// Copy the pixels of the buffer currently shown on the display
+    pub fn read_framebuffer(&self) -> Framebuffer {
+        let len = BUF_WIDTH as usize
+            * SCREEN_HEIGHT as usize
+            * screenshot::bytes_per_pixel(DISPLAY_FORMAT);
+        unsafe {
+            // Rendering into VRAM bypasses the data cache, so read it uncached
+            sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+            let pixels = core::slice::from_raw_parts(
+                cache::uncached_address(VRAM_ADDRESS + self.displayed as u32) as *const u8,
+                len,
+            );
+            Framebuffer::from_raw(
+                SCREEN_WIDTH,
+                SCREEN_HEIGHT,
+                BUF_WIDTH,
+                DISPLAY_FORMAT,
+                pixels,
+            )
+        }
+    }
+
+    /// Copy the depth buffer, rows are `SCREEN_WIDTH` values long
+    pub fn read_depth_buffer(&self) -> Vec<u16> {
+        let mut depth = Vec::with_capacity((SCREEN_WIDTH * SCREEN_HEIGHT) as usize);
+        unsafe {
+            sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+            let values = core::slice::from_raw_parts(
+                cache::uncached_address(VRAM_ADDRESS + self.zbp as u32) as *const u16,
+                (BUF_WIDTH * SCREEN_HEIGHT) as usize,
+            );
+            for row in values.chunks_exact(BUF_WIDTH as usize) {
+                depth.extend_from_slice(&row[..SCREEN_WIDTH as usize]);
+            }
+        }
+        depth
+    }
+
+    /// Save the buffer currently shown on the display, e.g. to `ms0:/PSP/PHOTO/shot.png`
+    ///
+    /// The format is selected by the extension (`.png` or `.bmp`). Returns `false` if
+    /// the file couldn't be written.
+    pub fn save_screenshot(&self, path: &str) -> bool {
+        let framebuffer = self.read_framebuffer();
+        let data = if path.to_ascii_lowercase().ends_with(".bmp") {
+            framebuffer.encode_bmp()
+        } else {
+            framebuffer.encode_png()
+        };
+        write_file(path, &data)
+    }
+
+    /// Record the next frame to a GE dump file, which can be replayed in PPSSPP
+    ///
+    /// The file is written when the frame is finished, see [`ge::dump`].
+    pub fn capture_next_frame(&mut self, path: &str) {
+        self.capture = Some(Capture {
+            path: String::from(path),
+            context: None,
+        });
     }
 
     pub fn start_frame<'a>(&'a mut self) -> Frame<'a> {
+        FRAME_COUNTER.fetch_add(1, Ordering::Relaxed);
+        if let Some(capture) = &mut self.capture {
+            // The GE is idle, the previous frame waited for its list to finish
+            let mut context = Box::new(sys::GeContext { context: [0; 512] });
+            unsafe {
+                sys::sceGeSaveContext(&mut *context);
+            }
+            capture.context = Some(context);
+        }
         unsafe {
             sys::sceGuStart(
                 psp::sys::GuContextType::Direct,
                 BUFFER.0.as_mut_ptr() as *mut _,
             );
         }
-        Frame { _gfx: self }
+        Frame { gfx: self }
+    }
+}
+
+/// Write a file, returns `false` on failure
+fn write_file(path: &str, data: &[u8]) -> bool {
+    let mut path = String::from(path);
+    path.push('\0');
+    unsafe {
+        let fd = sys::sceIoOpen(
+            path.as_ptr(),
+            sys::IoOpenFlags::WR_ONLY | sys::IoOpenFlags::CREAT | sys::IoOpenFlags::TRUNC,
+            0o777,
+        );
+        if fd.0 < 0 {
+            return false;
+        }
+        let written = sys::sceIoWrite(fd, data.as_ptr() as *const _, data.len());
+        sys::sceIoClose(fd);
+        written as usize == data.len()
     }
 }
 
 pub struct Frame<'gfx> {
-    _gfx: &'gfx mut PspGfx,
+    gfx: &'gfx mut PspGfx,
 }
 
 impl<'gfx> Frame<'gfx> {
-    fn finish_non_consuming(&self) {
+    /// Encode commands directly into the display list
+    fn ge(&self) -> Encoder<GuList> {
+        Encoder::new(GuList::new())
+    }
+
+    fn finish_non_consuming(&mut self) {
         unsafe {
             sys::sceGuFinish();
             sys::sceGuSync(sys::GuSyncMode::Finish, sys::GuSyncBehavior::Wait);
+        }
+        // The CPU may have cached the old contents while the copies were queued
+        for range in self.gfx.copy_targets.get_mut().drain(..) {
+            cache::invalidate_range(range);
+        }
+        if let Some(Capture {
+            path,
+            context: Some(context),
+        }) = self.gfx.capture.take()
+        {
+            self.write_capture(&path, &context);
+        }
+        unsafe {
             sys::sceDisplayWaitVblankStart();
             sys::sceGuSwapBuffers();
         }
+        let gfx = &mut *self.gfx;
+        gfx.displayed = if gfx.displayed == gfx.fbp0 {
+            gfx.fbp1
+        } else {
+            gfx.fbp0
+        };
     }
 
     /// Finish rendering
     ///
     /// Note that you don't have to call this as the `Frame` is terminated automatically when it's dropped
-    pub fn finish(self) {
+    pub fn finish(mut self) {
         self.finish_non_consuming();
         // XXX: this could *potentially* leak
         let _ = ManuallyDrop::new(self);
     }
 
+    /// Record the finished display list to a GE dump file
+    fn write_capture(&self, path: &str, context: &sys::GeContext) {
+        let list_address = unsafe { BUFFER.0.as_ptr() } as u32;
+        let dump = ge::dump::record(
+            self.display_list(),
+            list_address,
+            &context.context,
+            |address, buf| {
+                let address = address & 0x0fffffff;
+                let end = address as usize + buf.len();
+                // VRAM and user memory
+                let valid = (0x04000000..=0x04200000).contains(&end) && address >= 0x04000000
+                    || (0x08800000..=0x0a000000).contains(&end) && address >= 0x08800000;
+                if valid {
+                    unsafe {
+                        core::ptr::copy_nonoverlapping(
+                            address as *const u8,
+                            buf.as_mut_ptr(),
+                            buf.len(),
+                        );
+                    }
+                }
+                valid
+            },
+        );
+        write_file(path, &dump);
+    }
+
     /// Clear the color buffer with the specified color
     pub fn clear_color(&self, color: Color32) {
         unsafe {
@@ -151,68 +461,329 @@ impl<'gfx> Frame<'gfx> {
         }
     }
 
+    /// Bind a texture (including all of its mip levels) and enable texturing
+    ///
+    /// The sampler is applied as well, so no sampling state is inherited from previous draws
+    pub fn set_texture(&self, texture: &'gfx Texture, sampler: &Sampler) {
+        texture.sync_cache();
+        self.bind_texture(texture.format(), texture.levels(), sampler, |idx| {
+            texture.level_ptr(idx) as u32
+        });
+    }
+
+    /// Bind a texture stored in VRAM, see [`Frame::set_texture`]
+    pub fn set_vram_texture(&self, texture: &'gfx VramTexture, sampler: &Sampler) {
+        self.bind_texture(texture.format(), texture.levels(), sampler, |idx| {
+            texture.level_address(idx)
+        });
+    }
+
+    fn bind_texture(
+        &self,
+        format: TexturePixelFormat,
+        levels: &[TextureLevel],
+        sampler: &Sampler,
+        address: impl Fn(usize) -> u32,
+    ) {
+        self.set_sampler(sampler);
+        unsafe {
+            // Keeps the texture format of the sceGu context up to date
+            sys::sceGuTexMode(format, levels.len() as i32 - 1, 0, 0);
+        }
+        let mut ge = self.ge();
+        for (idx, level) in levels.iter().enumerate() {
+            ge.tex_image(
+                texture::mipmap_level(idx),
+                address(idx),
+                level.width,
+                level.height,
+                level.buffer_width,
+            );
+        }
+        // Writes the commands before the next sceGu call
+        drop(ge);
+        unsafe {
+            sys::sceGuEnable(GuState::Texture2D);
+        }
+    }
+
+    /// Set texture filtering, addressing and coordinate generation
+    pub fn set_sampler(&self, sampler: &Sampler) {
+        sampler.validate();
+        let (map_mode, light_u, light_v) = sampler.map_mode();
+        self.ge()
+            .tex_filter(sampler.min_filter.to_gu(), sampler.mag_filter.to_gu())
+            .tex_wrap(sampler.wrap_u.to_gu(), sampler.wrap_v.to_gu())
+            .tex_scale(sampler.scale.0, sampler.scale.1)
+            .tex_offset(sampler.offset.0, sampler.offset.1)
+            .tex_env_color(sampler.env_color.as_abgr());
+        // The map modes share a register, sceGu keeps track of both
+        unsafe {
+            if let TextureMapping::Projection(mode) = sampler.mapping {
+                sys::sceGuTexProjMapMode(mode);
+            }
+            sys::sceGuTexMapMode(map_mode, light_u, light_v);
+        }
+    }
+
+    /// Disable texturing
+    pub fn clear_texture(&self) {
+        unsafe {
+            sys::sceGuDisable(GuState::Texture2D);
+        }
+    }
+
+    /// Set texture filters used for minification and magnification
+    ///
+    /// Use one of the `*Mipmap*` filters for `min` to enable sampling from mip levels
+    pub fn set_texture_filter(&self, min: TextureFilter, mag: TextureFilter) {
+        self.ge().tex_filter(min, mag);
+    }
+
+    /// Set how the mip level is selected
+    ///
+    /// - [`TextureLevelMode::Auto`]: computed by the GE, `bias` is added to the level
+    /// - [`TextureLevelMode::Const`]: `bias` is used as the level
+    /// - [`TextureLevelMode::Slope`]: computed from the slope set by [`Frame::set_texture_slope`]
+    pub fn set_texture_level_mode(&self, mode: TextureLevelMode, bias: f32) {
+        self.ge().tex_level(mode, bias);
+    }
+
+    /// Set texture slope used by [`TextureLevelMode::Slope`]
+    pub fn set_texture_slope(&self, slope: f32) {
+        self.ge().tex_slope(slope);
+    }
+
     pub fn set_shading_model(&self, shading_model: ShadingModel) {
         // XXX: this seemingly only affects the current frame
-        unsafe {
-            sys::sceGuShadeModel(shading_model);
+        self.gfx
+            .smooth_shading
+            .set(matches!(shading_model, ShadingModel::Smooth));
+        self.ge().shade_model(shading_model);
+    }
+
+    /// Get the shading model set by [`Frame::set_shading_model`]
+    pub fn shading_model(&self) -> ShadingModel {
+        if self.gfx.smooth_shading.get() {
+            ShadingModel::Smooth
+        } else {
+            ShadingModel::Flat
         }
     }
 
     pub fn set_color(&self, color: Color32) {
+        self.ge().material_color(color.as_abgr());
+    }
+
+    pub fn set_scissor(&self, scissor: Rect) {
         unsafe {
-            sys::sceGuColor(color.as_abgr());
+            // sceGuScissor takes the bottom right corner (exclusive), not the size
+            sys::sceGuScissor(scissor.x, scissor.y, scissor.right(), scissor.bottom());
         }
     }
 
-    pub fn set_scissor(&self, scissor: Rect) {
+    /// Get the buffer which is drawn to in this frame
+    pub fn draw_target(&self) -> TransferImage {
+        let buffer = if self.gfx.displayed == self.gfx.fbp0 {
+            self.gfx.fbp1
+        } else {
+            self.gfx.fbp0
+        };
+        TransferImage::new(
+            VRAM_ADDRESS + buffer as u32,
+            BUF_WIDTH,
+            SCREEN_WIDTH,
+            SCREEN_HEIGHT,
+            true,
+        )
+    }
+
+    /// Copy a rectangle between images with a GE block transfer
+    ///
+    /// The copy is clipped to both images and runs in order with draws, e.g. to copy the
+    /// draw target into a [`VramTexture`] for post processing. A destination in RAM is
+    /// invalidated from the data cache when the frame is finished, read it afterwards.
+    pub fn copy_image(
+        &self,
+        src: &TransferImage,
+        src_rect: Rect,
+        dst: &TransferImage,
+        dst_pos: Point,
+    ) {
+        let Some((src_rect, dst_pos)) = transfer::clip(src, src_rect, dst, dst_pos) else {
+            return;
+        };
+        let dst_rect = Rect::from_point_size(dst_pos, src_rect.size());
+        let dst_range = dst.byte_range(dst_rect);
+        cache::sync_transfer(
+            src.byte_range(src_rect),
+            src.is_vram(),
+            dst_range.clone(),
+            dst.is_vram(),
+        );
+        if !dst.is_vram() {
+            self.gfx.copy_targets.borrow_mut().push(dst_range);
+        }
+        transfer::encode_copy(&mut self.ge(), src, src_rect, dst, dst_pos);
+    }
+
+    /// Get the commands written to the display list of this frame so far
+    ///
+    /// Use [`ge::disasm_at`] with the address of [`BUFFER`] to print them.
+    pub fn display_list(&self) -> &[u32] {
         unsafe {
-            sys::sceGuScissor(scissor.x, scissor.y, scissor.w, scissor.h);
+            let len = sys::sceGuCheckList() as usize / 4;
+            &BUFFER.0[..len]
         }
     }
 
     /// Get memory from sceGuGetMemory as a [`TransientBuffer`]
     ///
     /// (Safe alternative to [`UntypedBuffer::get_memory_static`])
-    pub fn get_memory<'frame, T: Clone + Copy>(
-        &'frame self,
-        data: &[T],
-    ) -> TransientBuffer<'frame, T> {
+    pub fn get_memory<T: Clone + Copy>(&self, data: &[T]) -> TransientBuffer<'gfx, T> {
         unsafe { TransientBuffer::get_memory_static(data) }
     }
 
-    pub fn draw_array<V: Buffer>(&self, primitive: GuPrimitive, vertex_buf: &V)
+    /// Draw vertices from a buffer
+    ///
+    /// The GE reads the buffer while the frame is in progress, so it's borrowed for `'gfx`.
+    /// Pass [`TransientBuffer`]s by value and other buffers by reference. A single draw can
+    /// contain at most [`ge::MAX_PRIM_COUNT`] vertices.
+    pub fn draw_array<V: Buffer + 'gfx>(&self, primitive: GuPrimitive, vertex_buf: V)
     where
         V::Item: Vertex,
     {
-        unsafe {
-            sys::sceGuDrawArray(
-                primitive,
-                V::Item::vtype(),
-                vertex_buf.len() as i32,
-                core::ptr::null(),
-                vertex_buf.as_ptr(),
-            );
-        }
+        assert!(
+            vertex_buf.len() <= ge::MAX_PRIM_COUNT,
+            "too many vertices in a single draw"
+        );
+        vertex_buf.sync_cache();
+        self.ge()
+            .vertex_type(V::Item::vtype())
+            .vertex_address(vertex_buf.as_ptr() as u32)
+            .prim(primitive, vertex_buf.len() as u16);
     }
 
-    pub fn draw_array_indexed<V: Buffer, I: Buffer>(
+    /// Draw indexed vertices, see [`Frame::draw_array`]
+    pub fn draw_array_indexed<V: Buffer + 'gfx, I: Buffer + 'gfx>(
         &self,
         primitive: GuPrimitive,
-        vertex_buf: &V,
-        index_buf: &I,
+        vertex_buf: V,
+        index_buf: I,
     ) where
         V::Item: Vertex,
         I::Item: IndexItem + Default,
     {
+        assert!(
+            index_buf.len() <= ge::MAX_PRIM_COUNT,
+            "too many indices in a single draw"
+        );
+        vertex_buf.sync_cache();
+        index_buf.sync_cache();
         // XXX: are indices pointing oob ub?
+        self.ge()
+            .vertex_type(V::Item::vtype() | I::Item::vtype())
+            .index_address(index_buf.as_ptr() as u32)
+            .vertex_address(vertex_buf.as_ptr() as u32)
+            .prim(primitive, index_buf.len() as u16);
+    }
+
+    /// Draw a bezier patch (requires `TRANSFORM_3D` vertices)
+    ///
+    /// `vertex_buf` contains `u_count * v_count` control points, row by row. Patches are
+    /// made of 4x4 control points sharing their edges, so both counts must be `3n + 1`.
+    pub fn draw_bezier<V: Buffer + 'gfx>(&self, vertex_buf: V, u_count: u32, v_count: u32)
+    where
+        V::Item: Vertex,
+    {
+        assert!(u_count >= 4 && u_count % 3 == 1, "invalid bezier u_count");
+        assert!(v_count >= 4 && v_count % 3 == 1, "invalid bezier v_count");
+        assert_patch::<V::Item>(vertex_buf.len(), u_count, v_count);
+        vertex_buf.sync_cache();
+        self.ge()
+            .vertex_type(V::Item::vtype())
+            .vertex_address(vertex_buf.as_ptr() as u32)
+            .bezier(u_count as u8, v_count as u8);
+    }
+
+    /// Draw a B-spline surface (requires `TRANSFORM_3D` vertices)
+    ///
+    /// `vertex_buf` contains `u_count * v_count` control points (at least 4 in each direction),
+    /// row by row. The modes select whether the surface starts/ends at the edge control points.
+    pub fn draw_spline<V: Buffer + 'gfx>(
+        &self,
+        vertex_buf: V,
+        u_count: u32,
+        v_count: u32,
+        u_mode: SplineMode,
+        v_mode: SplineMode,
+    ) where
+        V::Item: Vertex,
+    {
+        assert!(
+            u_count >= 4 && v_count >= 4,
+            "splines need at least 4x4 control points"
+        );
+        assert_patch::<V::Item>(vertex_buf.len(), u_count, v_count);
+        vertex_buf.sync_cache();
+        self.ge()
+            .vertex_type(V::Item::vtype())
+            .vertex_address(vertex_buf.as_ptr() as u32)
+            .spline(u_count as u8, v_count as u8, u_mode, v_mode);
+    }
+
+    /// Set amount of subdivisions of each bezier/spline segment (`1..=64`)
+    pub fn set_patch_divide(&self, u: u32, v: u32) {
+        assert!((1..=64).contains(&u) && (1..=64).contains(&v));
+        self.ge().patch_divide(u as u8, v as u8);
+    }
+
+    /// Set front face direction of bezier/spline patches, used for generated normals
+    pub fn set_patch_front_face(&self, direction: FrontFaceDirection) {
+        self.ge().patch_front_face(direction);
+    }
+
+    /// Set primitive used to draw bezier/spline patches
+    pub fn set_patch_primitive(&self, primitive: PatchPrimitive) {
+        self.ge().patch_primitive(primitive);
+    }
+
+    /// Draw 3D triangles, clipping them against the near plane and the guard band on the CPU
+    ///
+    /// `clip_matrix` must be the combined projection, view and model matrix used for the draw.
+    /// This is much slower than [`Frame::draw_array`], only use it for geometry that can
+    /// actually cross the near plane or extend far off-screen. See [`clip::clip_triangles`],
+    /// which panics for `TRANSFORM_2D` layouts.
+    pub fn draw_array_clipped<V: ClipVertex>(
+        &self,
+        primitive: GuPrimitive,
+        vertices: &[V],
+        clip_matrix: &ScePspFMatrix4,
+    ) {
+        let clipped = clip::clip_triangles(
+            primitive,
+            vertices,
+            clip_matrix,
+            self.viewport().guard_band(),
+        );
+        if clipped.is_empty() {
+            return;
+        }
+        // The limit is a multiple of 3, so triangles are never split
+        for triangles in clipped.chunks(ge::MAX_PRIM_COUNT) {
+            let vertex_buf = self.get_memory(triangles);
+            self.draw_array(GuPrimitive::Triangles, vertex_buf);
+        }
+    }
+
+    /// Enable or disable [`GuState::ClipPlanes`] (depth clamping of primitives crossing the near and far planes)
+    pub fn set_clip_planes(&self, enabled: bool) {
         unsafe {
-            sys::sceGuDrawArray(
-                primitive,
-                V::Item::vtype() | I::Item::vtype(),
-                index_buf.len() as i32,
-                index_buf.as_ptr(),
-                vertex_buf.as_ptr(),
-            );
+            if enabled {
+                sys::sceGuEnable(GuState::ClipPlanes);
+            } else {
+                sys::sceGuDisable(GuState::ClipPlanes);
+            }
         }
     }
 }
@@ -222,3 +793,18 @@ impl<'a> Drop for Frame<'a> {
         self.finish_non_consuming();
     }
 }
+
+/// Check the control points of a bezier/spline patch
+///
+/// The GE stores the counts in a byte each and can't generate patches from 2D vertices.
+fn assert_patch<V: Vertex>(len: usize, u_count: u32, v_count: u32) {
+    assert!(
+        u_count <= 0xff && v_count <= 0xff,
+        "patches have at most 255 control points in each direction"
+    );
+    assert!(
+        !V::vtype().contains(sys::VertexType::TRANSFORM_2D),
+        "patches require TRANSFORM_3D vertices"
+    );
+    assert_eq!(len, (u_count * v_count) as usize);
+}
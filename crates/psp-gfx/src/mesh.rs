@@ -0,0 +1,169 @@
+//! Meshes made of persistent vertex/index buffers and submeshes
+
+use alloc::vec::Vec;
+use core::ops::Range;
+use psp::sys::GuPrimitive;
+
+use crate::{
+    Frame,
+    buffer::{Buffer, OwnedBuffer},
+    color::Color32,
+    index::IndexItem,
+    sampler::Sampler,
+    texture::Texture,
+    vertex::Vertex,
+};
+
+/// Render state applied before drawing a submesh
+#[derive(Clone, Copy, Debug)]
+pub struct Material {
+    /// Color set with [`Frame::set_color`]
+    pub color: Color32,
+    /// Index into the texture slice passed to [`Frame::draw_mesh`], `None` disables texturing
+    pub texture: Option<usize>,
+    pub sampler: Sampler,
+}
+
+impl Material {
+    /// White, untextured material
+    pub const DEFAULT: Self = Self {
+        color: Color32::WHITE,
+        texture: None,
+        sampler: Sampler::NEAREST,
+    };
+
+    pub const fn with_color(self, color: Color32) -> Self {
+        Self { color, ..self }
+    }
+
+    pub const fn with_texture(self, texture: usize, sampler: Sampler) -> Self {
+        Self {
+            texture: Some(texture),
+            sampler,
+            ..self
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Part of a mesh drawn with a single material
+#[derive(Clone, Debug)]
+pub struct Submesh {
+    /// Range of indices (or vertices, for meshes without an index buffer)
+    pub range: Range<usize>,
+    pub material: Material,
+}
+
+/// Vertex buffer, optional index buffer and a list of submeshes sharing the same primitive
+pub struct Mesh<V: Vertex + Copy, I: IndexItem + Copy = u16> {
+    primitive: GuPrimitive,
+    vertices: OwnedBuffer<V>,
+    indices: Option<OwnedBuffer<I>>,
+    submeshes: Vec<Submesh>,
+}
+
+impl<V: Vertex + Copy, I: IndexItem + Copy> Mesh<V, I> {
+    /// Create a new mesh without indices or submeshes
+    pub fn new(primitive: GuPrimitive, vertices: Vec<V>) -> Self {
+        Self {
+            primitive,
+            vertices: OwnedBuffer::new(vertices),
+            indices: None,
+            submeshes: Vec::new(),
+        }
+    }
+
+    /// Set the index buffer, submesh ranges then refer to indices instead of vertices
+    pub fn with_indices(mut self, indices: Vec<I>) -> Self {
+        self.indices = Some(OwnedBuffer::new(indices));
+        self
+    }
+
+    /// Add a submesh
+    pub fn with_submesh(mut self, range: Range<usize>, material: Material) -> Self {
+        self.add_submesh(range, material);
+        self
+    }
+
+    /// Add a submesh
+    pub fn add_submesh(&mut self, range: Range<usize>, material: Material) {
+        assert!(range.end <= self.element_count(), "submesh out of bounds");
+        self.submeshes.push(Submesh { range, material });
+    }
+
+    pub fn primitive(&self) -> GuPrimitive {
+        self.primitive
+    }
+
+    pub fn vertices(&self) -> &OwnedBuffer<V> {
+        &self.vertices
+    }
+
+    pub fn vertices_mut(&mut self) -> &mut OwnedBuffer<V> {
+        &mut self.vertices
+    }
+
+    pub fn indices(&self) -> Option<&OwnedBuffer<I>> {
+        self.indices.as_ref()
+    }
+
+    pub fn submeshes(&self) -> &[Submesh] {
+        &self.submeshes
+    }
+
+    /// Get amount of elements drawn by the whole mesh (indices if present, vertices otherwise)
+    pub fn element_count(&self) -> usize {
+        match &self.indices {
+            Some(indices) => indices.len(),
+            None => self.vertices.len(),
+        }
+    }
+}
+
+impl<'gfx> Frame<'gfx> {
+    /// Draw all submeshes of a mesh, applying their materials
+    ///
+    /// Meshes without submeshes are drawn as a whole using the current render state.
+    /// `textures` are indexed by [`Material::texture`].
+    pub fn draw_mesh<V, I>(&self, mesh: &'gfx Mesh<V, I>, textures: &'gfx [Texture])
+    where
+        V: Vertex + Copy,
+        I: IndexItem + Copy + Default,
+    {
+        if mesh.submeshes.is_empty() {
+            self.draw_mesh_range(mesh, 0..mesh.element_count());
+            return;
+        }
+        for submesh in &mesh.submeshes {
+            let material = &submesh.material;
+            self.set_color(material.color);
+            match material.texture {
+                Some(texture) => self.set_texture(&textures[texture], &material.sampler),
+                None => self.clear_texture(),
+            }
+            self.draw_mesh_range(mesh, submesh.range.clone());
+        }
+    }
+
+    /// Draw a range of elements of a mesh using the current render state
+    pub fn draw_mesh_range<V, I>(&self, mesh: &'gfx Mesh<V, I>, range: Range<usize>)
+    where
+        V: Vertex + Copy,
+        I: IndexItem + Copy + Default,
+    {
+        if range.is_empty() {
+            return;
+        }
+        match &mesh.indices {
+            Some(indices) => {
+                self.draw_array_indexed(mesh.primitive, &mesh.vertices, indices.view(range))
+            }
+            None => self.draw_array(mesh.primitive, mesh.vertices.view(range)),
+        }
+    }
+}
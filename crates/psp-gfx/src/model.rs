@@ -0,0 +1,107 @@
+//! Runtime loader for models converted by the `psp-model` tool (`psp-gfx-tools` crate)
+//!
+//! The model format stores vertex data in the exact layout the GE expects, so loading
+//! is a copy into a [`Mesh`]. All values are little endian:
+//!
+//! ```text
+//! magic            b"PGFM"
+//! version          u32 (1)
+//! vertex_type      u32 (VertexType bits, without TRANSFORM_*)
+//! stride           u32 (size of one vertex in bytes)
+//! primitive        u32 (GuPrimitive)
+//! vertex_count     u32
+//! index_count      u32 (0 if the model is not indexed, indices are u16)
+//! submesh_count    u32
+//! texture_count    u32
+//! position_scale   [f32; 3]
+//! position_offset  [f32; 3]
+//! uv_scale         [f32; 2]
+//! uv_offset        [f32; 2]
+//! textures         texture_count * (u16 length, UTF-8 file name)
+//! submeshes        submesh_count * (u32 start, u32 count, u32 color (A8B8G8R8), i32 texture or -1)
+//! vertices         vertex_count * stride bytes, padded to 4 bytes
+//! indices          index_count * u16
+//! ```
+//!
+//! Quantized positions have to be decoded with [`Model::position_transform`] as the model
+//! matrix, quantized texture coordinates are decoded by the sampler set on each submesh.
+
+use alloc::string::String;
+use bytemuck::AnyBitPattern;
+use psp::sys::{GuPrimitive, ScePspFMatrix4, VertexType};
+
+use crate::{
+    mesh::{Material, Mesh},
+    quantize::scale_offset_matrix,
+    sampler::Sampler,
+    vertex::Vertex,
+};
+
+mod file;
+
+pub use file::ModelError;
+use file::ModelFile;
+
+/// Model data loaded from the binary model format
+pub struct Model {
+    file: ModelFile,
+}
+
+impl Model {
+    /// Parse a model file
+    pub fn parse(data: &[u8]) -> Result<Self, ModelError> {
+        ModelFile::parse(data).map(|file| Self { file })
+    }
+
+    /// Get vertex format of the model, without the transform flag
+    ///
+    /// Returned by reference, as `VertexType` of the `psp` crate isn't `Copy`.
+    pub fn vertex_type(&self) -> &VertexType {
+        &self.file.vertex_type
+    }
+
+    pub fn primitive(&self) -> GuPrimitive {
+        self.file.primitive
+    }
+
+    /// Get file names of the textures referenced by the materials, in material texture index order
+    pub fn texture_files(&self) -> &[String] {
+        &self.file.texture_files
+    }
+
+    /// Get the model matrix decoding quantized positions (scale, then translation)
+    ///
+    /// Multiply it into the model matrix before drawing, e.g. using `sceGumMultMatrix`.
+    pub fn position_transform(&self) -> ScePspFMatrix4 {
+        scale_offset_matrix(self.file.position_scale, self.file.position_offset)
+    }
+
+    /// Get texture coordinate scale and offset, also applied to the samplers of all submeshes
+    pub fn uv_transform(&self) -> ((f32, f32), (f32, f32)) {
+        (self.file.uv_scale, self.file.uv_offset)
+    }
+
+    /// Create a mesh from the model data
+    ///
+    /// `V` must have the same layout as the model, e.g. a layout defined with
+    /// [`define_vertex_layout!`](crate::define_vertex_layout) using the same formats and
+    /// `TRANSFORM_3D`.
+    pub fn into_mesh<V: Vertex + AnyBitPattern>(self) -> Result<Mesh<V>, ModelError> {
+        let file = self.file;
+        let vertices = file.vertices(V::vtype())?;
+        let sampler = Sampler::LINEAR.with_transform(file.uv_scale, file.uv_offset);
+        let mut mesh = Mesh::new(file.primitive, vertices);
+        if let Some(indices) = file.indices {
+            mesh = mesh.with_indices(indices);
+        }
+        for submesh in file.submeshes {
+            let material = Material {
+                color: submesh.color,
+                texture: submesh.texture,
+                sampler,
+            };
+            mesh.add_submesh(submesh.range, material);
+        }
+        Ok(mesh)
+    }
+}
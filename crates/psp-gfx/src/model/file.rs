@@ -0,0 +1,218 @@
+//! Parser of the binary model format described in [`psp_gfx::model`](super)
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools, which check
+//! it against their writer.
+
+use alloc::{string::String, vec::Vec};
+use bytemuck::AnyBitPattern;
+use core::ops::Range;
+
+use crate::color::Color32;
+use crate::ge::sys::{GuPrimitive, VertexType};
+
+pub const MAGIC: &[u8; 4] = b"PGFM";
+pub const VERSION: u32 = 1;
+
+/// Error returned when loading a model fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelError {
+    /// Data doesn't start with the model magic
+    InvalidMagic,
+    /// Model has an unsupported version
+    UnsupportedVersion(u32),
+    /// Data is truncated
+    UnexpectedEof,
+    /// A value is out of range
+    InvalidValue,
+    /// Texture file name is not valid UTF-8
+    InvalidUtf8,
+    /// The vertex layout of the model doesn't match the requested vertex type
+    LayoutMismatch,
+}
+
+/// Range of elements drawn with one material
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmeshRecord {
+    /// Range of indices, or of vertices if the model is not indexed
+    pub range: Range<usize>,
+    pub color: Color32,
+    /// Index into the texture file names
+    pub texture: Option<usize>,
+}
+
+/// Contents of a model file
+pub struct ModelFile {
+    /// Vertex format, without `TRANSFORM_*`
+    pub vertex_type: VertexType,
+    /// Size of one vertex in bytes
+    pub stride: usize,
+    pub primitive: GuPrimitive,
+    pub vertex_data: Vec<u8>,
+    pub indices: Option<Vec<u16>>,
+    pub submeshes: Vec<SubmeshRecord>,
+    pub texture_files: Vec<String>,
+    pub position_scale: [f32; 3],
+    pub position_offset: [f32; 3],
+    pub uv_scale: (f32, f32),
+    pub uv_offset: (f32, f32),
+}
+
+impl ModelFile {
+    /// Parse a model file, checking that all ranges and indices are in bounds
+    pub fn parse(data: &[u8]) -> Result<Self, ModelError> {
+        let mut reader = Reader(data);
+        if reader.take(4)? != MAGIC {
+            return Err(ModelError::InvalidMagic);
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(ModelError::UnsupportedVersion(version));
+        }
+        let vertex_type =
+            VertexType::from_bits(reader.u32()? as i32).ok_or(ModelError::InvalidValue)?;
+        let stride = reader.u32()? as usize;
+        if stride == 0 {
+            return Err(ModelError::InvalidValue);
+        }
+        let primitive = primitive_from_u32(reader.u32()?)?;
+        let vertex_count = reader.u32()? as usize;
+        let index_count = reader.u32()? as usize;
+        let submesh_count = reader.u32()? as usize;
+        let texture_count = reader.u32()? as usize;
+        let position_scale = [reader.f32()?, reader.f32()?, reader.f32()?];
+        let position_offset = [reader.f32()?, reader.f32()?, reader.f32()?];
+        let uv_scale = (reader.f32()?, reader.f32()?);
+        let uv_offset = (reader.f32()?, reader.f32()?);
+
+        reader.check_count(texture_count, 2)?;
+        let mut texture_files = Vec::with_capacity(texture_count);
+        for _ in 0..texture_count {
+            let len = reader.u16()? as usize;
+            let name =
+                core::str::from_utf8(reader.take(len)?).map_err(|_| ModelError::InvalidUtf8)?;
+            texture_files.push(name.into());
+        }
+
+        let element_count = if index_count > 0 {
+            index_count
+        } else {
+            vertex_count
+        };
+        reader.check_count(submesh_count, 16)?;
+        let mut submeshes = Vec::with_capacity(submesh_count);
+        for _ in 0..submesh_count {
+            let start = reader.u32()? as usize;
+            let count = reader.u32()? as usize;
+            let color = Color32::from_abgr(reader.u32()?);
+            let texture = match reader.u32()? as i32 {
+                -1 => None,
+                idx if (idx as usize) < texture_count => Some(idx as usize),
+                _ => return Err(ModelError::InvalidValue),
+            };
+            let end = start.checked_add(count).ok_or(ModelError::InvalidValue)?;
+            if end > element_count {
+                return Err(ModelError::InvalidValue);
+            }
+            submeshes.push(SubmeshRecord {
+                range: start..end,
+                color,
+                texture,
+            });
+        }
+
+        let vertex_size = vertex_count
+            .checked_mul(stride)
+            .ok_or(ModelError::InvalidValue)?;
+        let vertex_data = reader.take(vertex_size)?.to_vec();
+        reader.take((4 - vertex_size % 4) % 4)?;
+        let indices = if index_count > 0 {
+            reader.check_count(index_count, 2)?;
+            let mut indices = Vec::with_capacity(index_count);
+            for _ in 0..index_count {
+                let index = reader.u16()?;
+                if index as usize >= vertex_count {
+                    return Err(ModelError::InvalidValue);
+                }
+                indices.push(index);
+            }
+            Some(indices)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            vertex_type,
+            stride,
+            primitive,
+            vertex_data,
+            indices,
+            submeshes,
+            texture_files,
+            position_scale,
+            position_offset,
+            uv_scale,
+            uv_offset,
+        })
+    }
+
+    /// Read the vertices as `V`, which must have the vertex format `layout` (the transform
+    /// flag is ignored) and the size of a model vertex
+    pub fn vertices<V: AnyBitPattern>(&self, layout: VertexType) -> Result<Vec<V>, ModelError> {
+        let layout_bits = layout.bits() & !VertexType::TRANSFORM_2D.bits();
+        if layout_bits != self.vertex_type.bits() || size_of::<V>() != self.stride {
+            return Err(ModelError::LayoutMismatch);
+        }
+        Ok(self
+            .vertex_data
+            .chunks_exact(self.stride)
+            .map(bytemuck::pod_read_unaligned)
+            .collect())
+    }
+}
+
+fn primitive_from_u32(value: u32) -> Result<GuPrimitive, ModelError> {
+    Ok(match value {
+        0 => GuPrimitive::Points,
+        1 => GuPrimitive::Lines,
+        2 => GuPrimitive::LineStrip,
+        3 => GuPrimitive::Triangles,
+        4 => GuPrimitive::TriangleStrip,
+        5 => GuPrimitive::TriangleFan,
+        6 => GuPrimitive::Sprites,
+        _ => return Err(ModelError::InvalidValue),
+    })
+}
+
+/// Little endian reader
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ModelError> {
+        if self.0.len() < len {
+            return Err(ModelError::UnexpectedEof);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    /// Check that `count` items of `size` bytes are left, before allocating memory for them
+    fn check_count(&self, count: usize, size: usize) -> Result<(), ModelError> {
+        if count > self.0.len() / size {
+            return Err(ModelError::UnexpectedEof);
+        }
+        Ok(())
+    }
+
+    fn u16(&mut self) -> Result<u16, ModelError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ModelError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, ModelError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+}
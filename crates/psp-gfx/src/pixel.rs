@@ -0,0 +1,176 @@
+//! Fixed function pixel stages: fog, color test, logical operations, write masks and
+//! dithering
+//!
+//! Stages are enabled by passing their state to the `Frame::set_*` functions and disabled
+//! by passing `None`. The stencil test is in [`stencil`](crate::stencil).
+
+use psp::sys::{self, ColorFunc, GuState, LogicalOperation};
+
+use crate::{Frame, color::Color32};
+
+/// Linear fog, applied with [`Frame::set_fog`]
+///
+/// Only affects vertices transformed by the 3D pipeline, `TRANSFORM_2D` vertices are
+/// drawn without fog.
+#[derive(Clone, Copy, Debug)]
+pub struct Fog {
+    /// View space distance where the fog starts
+    pub near: f32,
+    /// View space distance where the fog fully covers the color
+    pub far: f32,
+    /// Fog color (alpha is ignored)
+    pub color: Color32,
+}
+
+impl Fog {
+    pub const fn new(near: f32, far: f32, color: Color32) -> Self {
+        Self { near, far, color }
+    }
+}
+
+/// Color test, applied with [`Frame::set_color_test`]
+///
+/// Pixels are discarded unless `func(color & mask, reference & mask)` is true, e.g. to cut
+/// out a color key.
+#[derive(Clone, Copy, Debug)]
+pub struct ColorTest {
+    pub func: ColorFunc,
+    /// Reference color (alpha is ignored)
+    pub reference: Color32,
+    /// Bits of the colors compared by the test (alpha is ignored)
+    pub mask: Color32,
+}
+
+impl ColorTest {
+    /// Discard pixels with the color `key`
+    pub const fn color_key(key: Color32) -> Self {
+        Self {
+            func: ColorFunc::NotEqual,
+            reference: key,
+            mask: Color32::WHITE,
+        }
+    }
+}
+
+/// Logical operation of the drawn color with the framebuffer color, replaces blending
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicOp {
+    Clear,
+    And,
+    AndReverse,
+    Copy,
+    AndInverted,
+    Noop,
+    Xor,
+    Or,
+    Nor,
+    Equiv,
+    Inverted,
+    OrReverse,
+    CopyInverted,
+    OrInverted,
+    Nand,
+    Set,
+}
+
+impl LogicOp {
+    pub(crate) fn to_gu(self) -> LogicalOperation {
+        match self {
+            Self::Clear => LogicalOperation::Clear,
+            Self::And => LogicalOperation::And,
+            Self::AndReverse => LogicalOperation::AndReverse,
+            Self::Copy => LogicalOperation::Copy,
+            Self::AndInverted => LogicalOperation::AndInverted,
+            Self::Noop => LogicalOperation::Noop,
+            Self::Xor => LogicalOperation::Xor,
+            Self::Or => LogicalOperation::Or,
+            Self::Nor => LogicalOperation::Nor,
+            Self::Equiv => LogicalOperation::Equiv,
+            Self::Inverted => LogicalOperation::Inverted,
+            Self::OrReverse => LogicalOperation::OrReverse,
+            Self::CopyInverted => LogicalOperation::CopyInverted,
+            Self::OrInverted => LogicalOperation::OrInverted,
+            Self::Nand => LogicalOperation::Nand,
+            Self::Set => LogicalOperation::Set,
+        }
+    }
+}
+
+/// 4x4 matrix of offsets (`-8..=7`) added to colors before they are reduced to 16 bits
+///
+/// Has no effect on 32-bit framebuffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dither(pub [[i8; 4]; 4]);
+
+impl Dither {
+    /// Ordered dithering with a 4x4 Bayer matrix
+    pub const BAYER: Self = Self([
+        [-4, 0, -3, 1],
+        [2, -2, 3, -1],
+        [-3, 1, -4, 0],
+        [3, -1, 2, -2],
+    ]);
+}
+
+impl<'gfx> Frame<'gfx> {
+    /// Enable fog with the specified state, or disable it with `None`
+    pub fn set_fog(&self, fog: Option<&Fog>) {
+        if let Some(fog) = fog {
+            self.ge()
+                .fog(fog.near, fog.far, fog.color.as_abgr() & 0xffffff);
+        }
+        set_state(GuState::Fog, fog.is_some());
+    }
+
+    /// Enable the color test with the specified state, or disable it with `None`
+    pub fn set_color_test(&self, test: Option<&ColorTest>) {
+        if let Some(test) = test {
+            self.ge().color_test(
+                test.func,
+                test.reference.as_abgr() & 0xffffff,
+                test.mask.as_abgr() & 0xffffff,
+            );
+        }
+        set_state(GuState::ColorTest, test.is_some());
+    }
+
+    /// Enable a logical operation, or disable it with `None`
+    pub fn set_logic_op(&self, op: Option<LogicOp>) {
+        if let Some(op) = op {
+            self.ge().logic_op(op.to_gu());
+        }
+        set_state(GuState::ColorLogicOp, op.is_some());
+    }
+
+    /// Set bits of the framebuffer which are not written (`0xAABBGGRR` as a [`Color32`])
+    ///
+    /// The alpha channel is the stencil buffer, see
+    /// [`Frame::set_write_mask`](crate::Frame::set_write_mask) to mask whole channels.
+    pub fn set_pixel_mask(&self, mask: Color32) {
+        self.ge().pixel_mask(mask.as_abgr());
+    }
+
+    /// Enable or disable depth buffer writes
+    pub fn set_depth_write(&self, enabled: bool) {
+        self.ge().depth_write(enabled);
+    }
+
+    /// Enable dithering with the specified matrix, or disable it with `None`
+    pub fn set_dither(&self, dither: Option<&Dither>) {
+        if let Some(dither) = dither {
+            self.ge().dither(&dither.0);
+        }
+        set_state(GuState::Dither, dither.is_some());
+    }
+}
+
+/// Enable or disable a state through sceGu, which keeps track of it
+pub(crate) fn set_state(state: GuState, enabled: bool) {
+    unsafe {
+        if enabled {
+            sys::sceGuEnable(state);
+        } else {
+            sys::sceGuDisable(state);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Conversion of float attributes to the fixed point vertex formats of the GE
+//!
+//! With `TRANSFORM_3D` the GE normalizes fixed point attributes: 8-bit values are divided
+//! by 128 and 16-bit values by 32768. Positions and normals are signed, texture
+//! coordinates unsigned. With `TRANSFORM_2D` the values are used as is.
+//!
+//! A [`Quantizer`] maps an arbitrary float range onto the fixed point range and provides
+//! the transform restoring the original values, either as a model matrix for positions or
+//! as a texture coordinate scale and offset for the [`Sampler`](crate::sampler::Sampler).
+
+use core::marker::PhantomData;
+
+use crate::ge::sys::{ScePspFMatrix4, ScePspFVector4};
+
+/// Fixed point type usable in vertex layouts
+pub trait Fixed: Copy {
+    /// Divisor applied by the GE in 3D mode
+    const DIVISOR: f32;
+    const MIN: Self;
+    const MAX: Self;
+
+    /// Convert the value to a float, as the GE uses it in 2D mode
+    fn to_f32(self) -> f32;
+
+    /// Convert a float to the nearest value, saturating at the bounds
+    fn from_f32(value: f32) -> Self;
+
+    /// Convert a normalized float (as decoded by the GE in 3D mode) to the nearest value
+    fn from_normalized(value: f32) -> Self {
+        Self::from_f32(value * Self::DIVISOR)
+    }
+
+    /// Get the value decoded by the GE in 3D mode
+    fn to_normalized(self) -> f32 {
+        self.to_f32() / Self::DIVISOR
+    }
+}
+
+macro_rules! impl_fixed {
+    ($($ty:ty => $divisor:expr),* $(,)?) => {
+        $(
+            impl Fixed for $ty {
+                const DIVISOR: f32 = $divisor;
+                const MIN: Self = <$ty>::MIN;
+                const MAX: Self = <$ty>::MAX;
+
+                fn to_f32(self) -> f32 {
+                    self as f32
+                }
+
+                fn from_f32(value: f32) -> Self {
+                    libm::roundf(value).clamp(<$ty>::MIN as f32, <$ty>::MAX as f32) as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed! {
+    i8 => 128.,
+    i16 => 32768.,
+    u8 => 128.,
+    u16 => 32768.,
+}
+
+/// Mapping from a float range to a fixed point type in 3D mode
+///
+/// Values are stored as `(value - offset) / scale`, the GE restores them with
+/// `decoded * scale + offset`. Values outside of the range the quantizer was created for
+/// saturate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantizer<T: Fixed, const N: usize> {
+    scale: [f32; N],
+    offset: [f32; N],
+    _fixed: PhantomData<T>,
+}
+
+impl<T: Fixed, const N: usize> Quantizer<T, N> {
+    /// Create a quantizer from a scale and offset
+    pub const fn new(scale: [f32; N], offset: [f32; N]) -> Self {
+        Self {
+            scale,
+            offset,
+            _fixed: PhantomData,
+        }
+    }
+
+    /// Create a quantizer covering the range between `min` and `max`
+    ///
+    /// Signed types center the range on zero, unsigned types start it at zero.
+    pub fn from_bounds(min: [f32; N], max: [f32; N]) -> Self {
+        let low = T::MIN.to_normalized();
+        let high = T::MAX.to_normalized();
+        let mut scale = [1.; N];
+        let mut offset = [0.; N];
+        for idx in 0..N {
+            let extent = (max[idx] - min[idx]).max(f32::EPSILON);
+            if low < 0. {
+                // Symmetric around zero, -MIN is one step larger than MAX
+                scale[idx] = extent / (2. * high);
+                offset[idx] = (min[idx] + max[idx]) / 2.;
+            } else {
+                scale[idx] = extent / high;
+                offset[idx] = min[idx];
+            }
+        }
+        Self::new(scale, offset)
+    }
+
+    /// Create a quantizer covering all `values`
+    pub fn from_values(values: impl IntoIterator<Item = [f32; N]>) -> Self {
+        let mut min = [f32::INFINITY; N];
+        let mut max = [f32::NEG_INFINITY; N];
+        let mut empty = true;
+        for value in values {
+            empty = false;
+            for idx in 0..N {
+                min[idx] = min[idx].min(value[idx]);
+                max[idx] = max[idx].max(value[idx]);
+            }
+        }
+        if empty {
+            return Self::from_bounds([0.; N], [0.; N]);
+        }
+        Self::from_bounds(min, max)
+    }
+
+    /// Get the scale restoring the original values
+    pub const fn scale(&self) -> [f32; N] {
+        self.scale
+    }
+
+    /// Get the offset restoring the original values
+    pub const fn offset(&self) -> [f32; N] {
+        self.offset
+    }
+
+    /// Quantize a value
+    pub fn quantize(&self, value: [f32; N]) -> [T; N] {
+        core::array::from_fn(|idx| {
+            T::from_normalized((value[idx] - self.offset[idx]) / self.scale[idx])
+        })
+    }
+
+    /// Restore a quantized value, as the GE does with the transform applied
+    pub fn dequantize(&self, value: [T; N]) -> [f32; N] {
+        core::array::from_fn(|idx| value[idx].to_normalized() * self.scale[idx] + self.offset[idx])
+    }
+
+    /// Get the largest error of a quantized value within the range of the quantizer
+    pub fn max_error(&self) -> [f32; N] {
+        core::array::from_fn(|idx| self.scale[idx] / T::DIVISOR / 2.)
+    }
+}
+
+impl<T: Fixed> Quantizer<T, 3> {
+    /// Get the model matrix restoring quantized positions
+    ///
+    /// Multiply it onto the model matrix of the object.
+    pub fn matrix(&self) -> ScePspFMatrix4 {
+        scale_offset_matrix(self.scale, self.offset)
+    }
+}
+
+impl<T: Fixed> Quantizer<T, 2> {
+    /// Get the texture coordinate scale and offset restoring quantized coordinates
+    ///
+    /// Use it with [`Sampler::with_transform`](crate::sampler::Sampler::with_transform).
+    pub fn uv_transform(&self) -> ((f32, f32), (f32, f32)) {
+        (
+            (self.scale[0], self.scale[1]),
+            (self.offset[0], self.offset[1]),
+        )
+    }
+}
+
+/// Quantize a unit normal to a signed fixed point type
+pub fn quantize_normal<T: Fixed>(normal: [f32; 3]) -> [T; 3] {
+    normal.map(|value| T::from_normalized(value.clamp(-1., 1.)))
+}
+
+/// Quantize a 2D (`TRANSFORM_2D`) value, which the GE uses without normalization
+pub fn quantize_raw<T: Fixed, const N: usize>(value: [f32; N]) -> [T; N] {
+    value.map(T::from_f32)
+}
+
+pub(crate) fn scale_offset_matrix(scale: [f32; 3], offset: [f32; 3]) -> ScePspFMatrix4 {
+    let [sx, sy, sz] = scale;
+    let [ox, oy, oz] = offset;
+    let column = |x, y, z, w| ScePspFVector4 { x, y, z, w };
+    ScePspFMatrix4 {
+        x: column(sx, 0., 0., 0.),
+        y: column(0., sy, 0., 0.),
+        z: column(0., 0., sz, 0.),
+        w: column(ox, oy, oz, 1.),
+    }
+}
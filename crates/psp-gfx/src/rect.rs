@@ -1,3 +1,83 @@
+/// Position in screen or texture space
+///
+/// Integer points address pixels, `Point<f32>` is used for subpixel positions such as the
+/// vertices of tessellated shapes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point<T = i32> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Point<i32> {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    /// Move the point by the specified amount
+    pub const fn offset(self, dx: i32, dy: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy)
+    }
+}
+
+impl Point<f32> {
+    /// Move the point by the specified amount
+    pub const fn offset(self, dx: f32, dy: f32) -> Self {
+        Self::new(self.x + dx, self.y + dy)
+    }
+}
+
+impl From<Point<i32>> for Point<f32> {
+    fn from(point: Point<i32>) -> Self {
+        Self::new(point.x as f32, point.y as f32)
+    }
+}
+
+/// Width and height of an area
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Size {
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Size {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    pub const fn new(w: i32, h: i32) -> Self {
+        Self { w, h }
+    }
+
+    /// Check if the area is empty (zero or negative width or height)
+    pub const fn is_empty(self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
+}
+
+/// Horizontal alignment inside of an area
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HorizontalAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical alignment inside of an area
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Axis aligned rectangle, `x`/`y` is the top left corner
+///
+/// The right and bottom edges are exclusive, a rectangle with zero width or height is empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Rect {
     pub x: i32,
     pub y: i32,
@@ -6,22 +86,191 @@ pub struct Rect {
 }
 
 impl Rect {
-    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+    pub const fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
         Self { x, y, w, h }
     }
 
-    // pub fn to_sprites_vertices(&self) -> [Vertex; 2] {
-    //     [
-    //         Vertex {
-    //             x: self.x as i16,
-    //             y: self.y as i16,
-    //             ..Default::default()
-    //         },
-    //         Vertex {
-    //             x: (self.x + self.w) as i16,
-    //             y: (self.y + self.h) as i16,
-    //             ..Default::default()
-    //         },
-    //     ]
-    // }
+    /// Create a new rectangle from the top left corner and size
+    pub const fn from_point_size(point: Point, size: Size) -> Self {
+        Self::new(point.x, point.y, size.w, size.h)
+    }
+
+    /// Create the smallest rectangle spanning both corners
+    pub const fn from_corners(a: Point, b: Point) -> Self {
+        let (left, right) = if a.x < b.x { (a.x, b.x) } else { (b.x, a.x) };
+        let (top, bottom) = if a.y < b.y { (a.y, b.y) } else { (b.y, a.y) };
+        Self::new(left, top, right - left, bottom - top)
+    }
+
+    pub const fn left(&self) -> i32 {
+        self.x
+    }
+
+    pub const fn top(&self) -> i32 {
+        self.y
+    }
+
+    /// Get the X coordinate of the right edge (exclusive)
+    pub const fn right(&self) -> i32 {
+        self.x + self.w
+    }
+
+    /// Get the Y coordinate of the bottom edge (exclusive)
+    pub const fn bottom(&self) -> i32 {
+        self.y + self.h
+    }
+
+    /// Get the top left corner
+    pub const fn position(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    pub const fn size(&self) -> Size {
+        Size::new(self.w, self.h)
+    }
+
+    /// Get the center of the rectangle, rounded towards the top left corner
+    pub const fn center(&self) -> Point {
+        Point::new(self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    /// Check if the rectangle is empty (zero or negative width or height)
+    pub const fn is_empty(&self) -> bool {
+        self.size().is_empty()
+    }
+
+    /// Check if the point is inside of the rectangle
+    pub const fn contains(&self, point: Point) -> bool {
+        point.x >= self.left()
+            && point.x < self.right()
+            && point.y >= self.top()
+            && point.y < self.bottom()
+    }
+
+    /// Check if the other rectangle is fully inside of this rectangle
+    pub const fn contains_rect(&self, other: &Rect) -> bool {
+        other.left() >= self.left()
+            && other.right() <= self.right()
+            && other.top() >= self.top()
+            && other.bottom() <= self.bottom()
+    }
+
+    /// Check if the rectangles overlap
+    pub const fn intersects(&self, other: &Rect) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.left() < other.right()
+            && other.left() < self.right()
+            && self.top() < other.bottom()
+            && other.top() < self.bottom()
+    }
+
+    /// Get the overlapping area of both rectangles, `None` if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let left = self.left().max(other.left());
+        let top = self.top().max(other.top());
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Some(Rect::new(left, top, right - left, bottom - top))
+    }
+
+    /// Get the smallest rectangle containing both rectangles
+    ///
+    /// Empty rectangles are ignored
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let left = self.left().min(other.left());
+        let top = self.top().min(other.top());
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(left, top, right - left, bottom - top)
+    }
+
+    /// Move the rectangle by the specified amount
+    pub const fn offset(&self, dx: i32, dy: i32) -> Rect {
+        Rect::new(self.x + dx, self.y + dy, self.w, self.h)
+    }
+
+    /// Shrink the rectangle on all sides, the size is clamped to zero
+    pub fn inset(&self, dx: i32, dy: i32) -> Rect {
+        let w = (self.w - 2 * dx).max(0);
+        let h = (self.h - 2 * dy).max(0);
+        Rect::new(self.x + dx, self.y + dy, w, h)
+    }
+
+    /// Grow the rectangle on all sides
+    pub fn outset(&self, dx: i32, dy: i32) -> Rect {
+        self.inset(-dx, -dy)
+    }
+
+    /// Split off `w` pixels from the left side, returns `(left, rest)`
+    ///
+    /// The split position is clamped to the rectangle, this applies to all `split_*` functions
+    pub fn split_left(&self, w: i32) -> (Rect, Rect) {
+        let w = w.clamp(0, self.w.max(0));
+        (
+            Rect::new(self.x, self.y, w, self.h),
+            Rect::new(self.x + w, self.y, self.w - w, self.h),
+        )
+    }
+
+    /// Split off `w` pixels from the right side, returns `(rest, right)`
+    pub fn split_right(&self, w: i32) -> (Rect, Rect) {
+        self.split_left(self.w - w.clamp(0, self.w.max(0)))
+    }
+
+    /// Split off `h` pixels from the top, returns `(top, rest)`
+    pub fn split_top(&self, h: i32) -> (Rect, Rect) {
+        let h = h.clamp(0, self.h.max(0));
+        (
+            Rect::new(self.x, self.y, self.w, h),
+            Rect::new(self.x, self.y + h, self.w, self.h - h),
+        )
+    }
+
+    /// Split off `h` pixels from the bottom, returns `(rest, bottom)`
+    pub fn split_bottom(&self, h: i32) -> (Rect, Rect) {
+        self.split_top(self.h - h.clamp(0, self.h.max(0)))
+    }
+
+    /// Place an area of the specified size inside of this rectangle
+    ///
+    /// The result may extend outside of the rectangle if `size` is larger
+    pub const fn align(
+        &self,
+        size: Size,
+        horizontal: HorizontalAlign,
+        vertical: VerticalAlign,
+    ) -> Rect {
+        let x = match horizontal {
+            HorizontalAlign::Left => self.x,
+            HorizontalAlign::Center => self.x + (self.w - size.w) / 2,
+            HorizontalAlign::Right => self.right() - size.w,
+        };
+        let y = match vertical {
+            VerticalAlign::Top => self.y,
+            VerticalAlign::Middle => self.y + (self.h - size.h) / 2,
+            VerticalAlign::Bottom => self.bottom() - size.h,
+        };
+        Rect::new(x, y, size.w, size.h)
+    }
+
+    /// Create the two vertices of a `GuPrimitive::Sprites` quad covering the rectangle
+    ///
+    /// `vertex` builds a vertex from a position, e.g. `Vertex::from_position2` of a layout
+    /// using `VERTEX_16BIT`
+    pub fn to_sprites_vertices<V>(&self, vertex: impl Fn(u16, u16) -> V) -> [V; 2] {
+        [
+            vertex(self.x as u16, self.y as u16),
+            vertex(self.right() as u16, self.bottom() as u16),
+        ]
+    }
 }
@@ -0,0 +1,210 @@
+use psp::sys::{GuTexWrapMode, TextureFilter, TextureMapMode, TextureProjectionMapMode};
+
+use crate::color::Color32;
+
+/// Texture filtering mode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl Filter {
+    /// Check if the filter samples from mip levels (only supported for minification)
+    pub const fn is_mipmap(self) -> bool {
+        !matches!(self, Self::Nearest | Self::Linear)
+    }
+
+    pub(crate) fn to_gu(self) -> TextureFilter {
+        match self {
+            Self::Nearest => TextureFilter::Nearest,
+            Self::Linear => TextureFilter::Linear,
+            Self::NearestMipmapNearest => TextureFilter::NearestMipmapNearest,
+            Self::LinearMipmapNearest => TextureFilter::LinearMipmapNearest,
+            Self::NearestMipmapLinear => TextureFilter::NearestMipmapLinear,
+            Self::LinearMipmapLinear => TextureFilter::LinearMipmapLinear,
+        }
+    }
+}
+
+/// Texture addressing mode for coordinates outside of the `0..1` range
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Wrap {
+    /// The texture repeats after crossing the border
+    #[default]
+    Repeat,
+    /// Texture clamps at the border
+    Clamp,
+}
+
+impl Wrap {
+    pub(crate) fn to_gu(self) -> GuTexWrapMode {
+        match self {
+            Self::Repeat => GuTexWrapMode::Repeat,
+            Self::Clamp => GuTexWrapMode::Clamp,
+        }
+    }
+}
+
+/// Source of the texture coordinates
+#[derive(Clone, Copy, Debug, Default)]
+pub enum TextureMapping {
+    /// Use texture coordinates of the vertices (scaled and offset by the [`Sampler`])
+    #[default]
+    Coords,
+    /// Generate texture coordinates by transforming the selected vertex attribute with the
+    /// texture matrix (projection mapping)
+    Projection(TextureProjectionMapMode),
+    /// Generate texture coordinates from the direction of the specified lights (0-3),
+    /// used for environment mapping
+    Environment { light_u: u32, light_v: u32 },
+}
+
+impl PartialEq for TextureMapping {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Coords, Self::Coords) => true,
+            // `TextureProjectionMapMode` doesn't implement `PartialEq`
+            (Self::Projection(a), Self::Projection(b)) => *a as u32 == *b as u32,
+            (
+                Self::Environment { light_u, light_v },
+                Self::Environment {
+                    light_u: other_u,
+                    light_v: other_v,
+                },
+            ) => light_u == other_u && light_v == other_v,
+            _ => false,
+        }
+    }
+}
+
+/// Texture sampling state, applied with [`Frame::set_sampler`](crate::Frame::set_sampler)
+///
+/// Scale and offset are only applied by the 3D T&L pipeline, vertices with
+/// `TRANSFORM_2D` are not affected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sampler {
+    /// Minification filter
+    pub min_filter: Filter,
+    /// Magnification filter, [`Filter::Nearest`] or [`Filter::Linear`]
+    pub mag_filter: Filter,
+    /// Addressing mode in the U direction
+    pub wrap_u: Wrap,
+    /// Addressing mode in the V direction
+    pub wrap_v: Wrap,
+    /// Scalar to multiply the U and V coordinates with
+    pub scale: (f32, f32),
+    /// Offset to add to the U and V coordinates
+    pub offset: (f32, f32),
+    /// Source of the texture coordinates
+    pub mapping: TextureMapping,
+    /// Constant color used by [`TextureEffect::Blend`](psp::sys::TextureEffect::Blend)
+    /// (alpha is ignored)
+    pub env_color: Color32,
+}
+
+impl Sampler {
+    /// Nearest neighbour filtering with repeating coordinates
+    pub const NEAREST: Self = Self {
+        min_filter: Filter::Nearest,
+        mag_filter: Filter::Nearest,
+        wrap_u: Wrap::Repeat,
+        wrap_v: Wrap::Repeat,
+        scale: (1., 1.),
+        offset: (0., 0.),
+        mapping: TextureMapping::Coords,
+        env_color: Color32::BLACK,
+    };
+
+    /// Bilinear filtering with repeating coordinates
+    pub const LINEAR: Self = Self {
+        min_filter: Filter::Linear,
+        mag_filter: Filter::Linear,
+        ..Self::NEAREST
+    };
+
+    /// Trilinear filtering with repeating coordinates
+    pub const TRILINEAR: Self = Self {
+        min_filter: Filter::LinearMipmapLinear,
+        mag_filter: Filter::Linear,
+        ..Self::NEAREST
+    };
+
+    /// Set filters used for minification and magnification
+    ///
+    /// Only `min_filter` can sample from mip levels.
+    pub const fn with_filter(self, min_filter: Filter, mag_filter: Filter) -> Self {
+        assert!(
+            !mag_filter.is_mipmap(),
+            "magnification filter can't use mip levels"
+        );
+        Self {
+            min_filter,
+            mag_filter,
+            ..self
+        }
+    }
+
+    /// Set addressing mode for both directions
+    pub const fn with_wrap(self, wrap_u: Wrap, wrap_v: Wrap) -> Self {
+        Self {
+            wrap_u,
+            wrap_v,
+            ..self
+        }
+    }
+
+    /// Set texture coordinate scale and offset
+    pub const fn with_transform(self, scale: (f32, f32), offset: (f32, f32)) -> Self {
+        Self {
+            scale,
+            offset,
+            ..self
+        }
+    }
+
+    /// Set source of texture coordinates
+    pub const fn with_mapping(self, mapping: TextureMapping) -> Self {
+        Self { mapping, ..self }
+    }
+
+    /// Set constant color used by the texture function
+    pub const fn with_env_color(self, env_color: Color32) -> Self {
+        Self { env_color, ..self }
+    }
+
+    /// Check the fields which can't be represented by the GE
+    pub(crate) fn validate(&self) {
+        assert!(
+            !self.mag_filter.is_mipmap(),
+            "magnification filter can't use mip levels"
+        );
+        if let TextureMapping::Environment { light_u, light_v } = self.mapping {
+            assert!(
+                light_u <= 3 && light_v <= 3,
+                "environment map lights must be in 0..=3"
+            );
+        }
+    }
+
+    pub(crate) fn map_mode(&self) -> (TextureMapMode, u32, u32) {
+        match self.mapping {
+            TextureMapping::Coords => (TextureMapMode::TextureCoords, 0, 0),
+            TextureMapping::Projection(_) => (TextureMapMode::TextureMatrix, 0, 0),
+            TextureMapping::Environment { light_u, light_v } => {
+                (TextureMapMode::EnvironmentMap, light_u, light_v)
+            }
+        }
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self::NEAREST
+    }
+}
@@ -0,0 +1,188 @@
+//! Framebuffer pixel conversion and BMP/PNG encoding
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools. Framebuffer
+//! alpha holds stencil values on the PSP, so images are written without alpha.
+
+use alloc::vec::Vec;
+
+use crate::color::{Color32, Color4444, Color5551, Color5650};
+use crate::ge::sys::DisplayPixelFormat;
+
+/// Get the size of a framebuffer pixel in bytes
+pub const fn bytes_per_pixel(format: DisplayPixelFormat) -> usize {
+    match format {
+        DisplayPixelFormat::Psm8888 => 4,
+        _ => 2,
+    }
+}
+
+/// Convert a framebuffer pixel to a [`Color32`]
+pub fn pixel_color(format: DisplayPixelFormat, pixel: &[u8]) -> Color32 {
+    let packed = || u16::from_le_bytes([pixel[0], pixel[1]]);
+    match format {
+        DisplayPixelFormat::Psm5650 => Color5650::from_bits(packed()).to_color32(),
+        DisplayPixelFormat::Psm5551 => Color5551::from_bits(packed()).to_color32(),
+        DisplayPixelFormat::Psm4444 => Color4444::from_bits(packed()).to_color32(),
+        DisplayPixelFormat::Psm8888 => Color32::new(pixel[0], pixel[1], pixel[2], pixel[3]),
+    }
+}
+
+/// Copy of framebuffer pixels, rows are tightly packed
+#[derive(Clone, Debug)]
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    pub format: DisplayPixelFormat,
+    pub data: Vec<u8>,
+}
+
+impl Framebuffer {
+    /// Copy pixels from a buffer with rows of `stride` pixels
+    pub fn from_raw(
+        width: u32,
+        height: u32,
+        stride: u32,
+        format: DisplayPixelFormat,
+        data: &[u8],
+    ) -> Self {
+        let bpp = bytes_per_pixel(format);
+        let row_len = width as usize * bpp;
+        let stride = stride as usize * bpp;
+        let mut pixels = Vec::with_capacity(row_len * height as usize);
+        for row in data.chunks(stride).take(height as usize) {
+            pixels.extend_from_slice(&row[..row_len]);
+        }
+        Self {
+            width,
+            height,
+            format,
+            data: pixels,
+        }
+    }
+
+    /// Iterate over the rows as RGB pixels, top to bottom
+    fn rgb_rows(&self) -> impl DoubleEndedIterator<Item = impl Iterator<Item = [u8; 3]> + '_> {
+        let bpp = bytes_per_pixel(self.format);
+        self.data
+            .chunks_exact(self.width as usize * bpp)
+            .map(move |row| {
+                row.chunks_exact(bpp).map(|pixel| {
+                    let [r, g, b, _] = pixel_color(self.format, pixel).to_array();
+                    [r, g, b]
+                })
+            })
+    }
+
+    /// Encode as a 24-bit BMP file
+    pub fn encode_bmp(&self) -> Vec<u8> {
+        let row_len = (self.width as usize * 3).next_multiple_of(4);
+        let image_size = row_len * self.height as usize;
+        let mut out = Vec::with_capacity(54 + image_size);
+        let u16 = |out: &mut Vec<u8>, value: u16| out.extend_from_slice(&value.to_le_bytes());
+        let u32 = |out: &mut Vec<u8>, value: u32| out.extend_from_slice(&value.to_le_bytes());
+
+        out.extend_from_slice(b"BM");
+        u32(&mut out, 54 + image_size as u32);
+        u32(&mut out, 0);
+        u32(&mut out, 54);
+        // BITMAPINFOHEADER
+        u32(&mut out, 40);
+        u32(&mut out, self.width);
+        u32(&mut out, self.height);
+        u16(&mut out, 1);
+        u16(&mut out, 24);
+        u32(&mut out, 0);
+        u32(&mut out, image_size as u32);
+        u32(&mut out, 2835);
+        u32(&mut out, 2835);
+        u32(&mut out, 0);
+        u32(&mut out, 0);
+
+        // Rows are stored bottom to top, in BGR order
+        for row in self.rgb_rows().rev() {
+            let start = out.len();
+            for [r, g, b] in row {
+                out.extend_from_slice(&[b, g, r]);
+            }
+            out.resize(start + row_len, 0);
+        }
+        out
+    }
+
+    /// Encode as a 24-bit PNG file (without compression)
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width as usize * 3 + 1) * self.height as usize);
+        for row in self.rgb_rows() {
+            // Filter type: none
+            raw.push(0);
+            for pixel in row {
+                raw.extend_from_slice(&pixel);
+            }
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8-bit RGB, deflate, no filter, no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_png_chunk(&mut out, b"IHDR", &header);
+        write_png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        write_png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn write_png_chunk(out: &mut Vec<u8>, ty: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(ty);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wrap data in a zlib stream of stored deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 65535 * 5 + 11);
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(65535).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
@@ -0,0 +1,217 @@
+use alloc::vec::Vec;
+use psp::sys::GuPrimitive;
+
+use crate::{
+    Frame, color::Color32, define_vertex_layout, ge::MAX_PRIM_COUNT, rect::Rect, sampler::Sampler,
+    texture::Texture,
+};
+
+/// Width of the vertical strips (in texels) large textured sprites are split into
+///
+/// Drawing narrow strips keeps the texture cache hit rate high, which makes blits much faster
+pub const DEFAULT_STRIP_WIDTH: u32 = 64;
+
+define_vertex_layout! {
+    SpriteVertex {
+        vertex: VERTEX_16BIT,
+        transform: TRANSFORM_2D,
+        texture: TEXTURE_16BIT,
+        color: COLOR_8888,
+    }
+}
+
+/// A single (optionally textured) quad drawn by the [`SpriteBatch`]
+#[derive(Clone, Copy)]
+pub struct Sprite {
+    /// Destination rectangle on the screen
+    pub dst: Rect,
+    /// Source rectangle in texels, `None` to use the whole texture
+    pub src: Option<Rect>,
+    /// Color the sprite is tinted with (or filled with, if untextured)
+    pub color: Color32,
+    /// Mirror the sprite horizontally
+    pub flip_x: bool,
+    /// Mirror the sprite vertically
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    /// Create a white sprite covering the whole texture
+    pub const fn new(dst: Rect) -> Self {
+        Self {
+            dst,
+            src: None,
+            color: Color32::WHITE,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// Set source rectangle in texels
+    pub const fn with_src(self, src: Rect) -> Self {
+        Self {
+            src: Some(src),
+            ..self
+        }
+    }
+
+    /// Set tint color
+    pub const fn with_color(self, color: Color32) -> Self {
+        Self { color, ..self }
+    }
+
+    /// Set horizontal and vertical mirroring
+    pub const fn with_flip(self, flip_x: bool, flip_y: bool) -> Self {
+        Self {
+            flip_x,
+            flip_y,
+            ..self
+        }
+    }
+}
+
+/// Accumulates sprites and draws them with as few draw calls as possible
+///
+/// Sprites are flushed in a single [`GuPrimitive::Sprites`] draw whenever the texture changes,
+/// [`SpriteBatch::flush`] is called, the batch is dropped or a draw would exceed
+/// [`MAX_PRIM_COUNT`] vertices.
+///
+/// Sprite tint is applied through the texture function, see [`Frame::set_texture_function`]
+pub struct SpriteBatch<'frame, 'gfx> {
+    frame: &'frame Frame<'gfx>,
+    texture: Option<&'gfx Texture>,
+    sampler: Sampler,
+    strip_width: Option<u32>,
+    vertices: Vec<SpriteVertex>,
+}
+
+impl<'frame, 'gfx> SpriteBatch<'frame, 'gfx> {
+    /// Create a new, untextured sprite batch
+    pub fn new(frame: &'frame Frame<'gfx>) -> Self {
+        Self {
+            frame,
+            texture: None,
+            sampler: Sampler::NEAREST,
+            strip_width: Some(DEFAULT_STRIP_WIDTH),
+            vertices: Vec::new(),
+        }
+    }
+
+    /// Set width of the strips textured sprites are split into, `None` to disable splitting
+    pub fn set_strip_width(&mut self, strip_width: Option<u32>) {
+        assert!(strip_width != Some(0), "strip width must not be zero");
+        self.strip_width = strip_width;
+    }
+
+    /// Set texture used for the following sprites, `None` to draw untextured sprites
+    ///
+    /// Flushes the batch if the texture or sampler changed
+    pub fn set_texture(&mut self, texture: Option<&'gfx Texture>, sampler: &Sampler) {
+        let same_texture = match (self.texture, texture) {
+            (Some(a), Some(b)) => core::ptr::eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same_texture || self.sampler != *sampler {
+            self.flush();
+        }
+        self.texture = texture;
+        self.sampler = *sampler;
+    }
+
+    /// Get amount of sprite vertices waiting to be drawn
+    pub fn pending(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Queue a sprite
+    pub fn draw(&mut self, sprite: Sprite) {
+        let Some(texture) = self.texture else {
+            self.push_quad(sprite.dst, (0, 0, 0, 0), sprite.color);
+            return;
+        };
+        let src = sprite.src.unwrap_or(Rect::new(
+            0,
+            0,
+            texture.width() as i32,
+            texture.height() as i32,
+        ));
+        let (v0, v1) = if sprite.flip_y {
+            (src.y + src.h, src.y)
+        } else {
+            (src.y, src.y + src.h)
+        };
+
+        let strip_width = self
+            .strip_width
+            .map(|width| width as i32)
+            .filter(|&width| src.w > width)
+            .unwrap_or(src.w.max(1));
+        let mut u = src.x;
+        while u < src.x + src.w {
+            let u_end = (u + strip_width).min(src.x + src.w);
+            // Map the strip from texture space into screen space
+            let (t0, t1) = if sprite.flip_x {
+                (src.x + src.w - u_end, src.x + src.w - u)
+            } else {
+                (u - src.x, u_end - src.x)
+            };
+            let x0 = sprite.dst.x + t0 * sprite.dst.w / src.w;
+            let x1 = sprite.dst.x + t1 * sprite.dst.w / src.w;
+            let (u0, u1) = if sprite.flip_x {
+                (u_end, u)
+            } else {
+                (u, u_end)
+            };
+            self.push_quad(
+                Rect::new(x0, sprite.dst.y, x1 - x0, sprite.dst.h),
+                (u0, v0, u1, v1),
+                sprite.color,
+            );
+            u = u_end;
+        }
+    }
+
+    /// Queue a filled rectangle using the current texture
+    pub fn draw_rect(&mut self, rect: Rect, color: Color32) {
+        self.draw(Sprite::new(rect).with_color(color));
+    }
+
+    /// Draw all queued sprites
+    pub fn flush(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        match self.texture {
+            Some(texture) => self.frame.set_texture(texture, &self.sampler),
+            None => self.frame.clear_texture(),
+        }
+        let vertex_buf = self.frame.get_memory(&self.vertices);
+        self.frame.draw_array(GuPrimitive::Sprites, vertex_buf);
+        self.vertices.clear();
+    }
+
+    fn push_quad(&mut self, dst: Rect, (u0, v0, u1, v1): (i32, i32, i32, i32), color: Color32) {
+        if self.vertices.len() + 2 > MAX_PRIM_COUNT {
+            self.flush();
+        }
+        self.vertices.push(SpriteVertex {
+            u: u0 as u16,
+            v: v0 as u16,
+            color,
+            ..SpriteVertex::from_position2(dst.x as u16, dst.y as u16)
+        });
+        self.vertices.push(SpriteVertex {
+            u: u1 as u16,
+            v: v1 as u16,
+            color,
+            ..SpriteVertex::from_position2((dst.x + dst.w) as u16, (dst.y + dst.h) as u16)
+        });
+    }
+}
+
+impl Drop for SpriteBatch<'_, '_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -0,0 +1,166 @@
+//! Stencil test and masking
+//!
+//! The stencil buffer is the alpha channel of the framebuffer (8 bits with the 8888 format
+//! used by [`PspGfx`](crate::PspGfx)). Stencil operations write to it, so framebuffer alpha
+//! can't be used for anything else while stenciling.
+
+use psp::sys::{self, ClearBuffer, GuState, StencilFunc, StencilOperation};
+
+use crate::{Frame, pixel};
+
+/// Update of the stencil value of a pixel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StencilOp {
+    #[default]
+    Keep,
+    Zero,
+    /// Replace with the reference value
+    Replace,
+    Invert,
+    /// Increment, clamped to 255
+    Increment,
+    /// Decrement, clamped to 0
+    Decrement,
+}
+
+impl StencilOp {
+    pub(crate) fn to_gu(self) -> StencilOperation {
+        match self {
+            Self::Keep => StencilOperation::Keep,
+            Self::Zero => StencilOperation::Zero,
+            Self::Replace => StencilOperation::Replace,
+            Self::Invert => StencilOperation::Invert,
+            Self::Increment => StencilOperation::Incr,
+            Self::Decrement => StencilOperation::Decr,
+        }
+    }
+}
+
+/// Stencil test state, applied with [`Frame::set_stencil`]
+#[derive(Clone, Copy, Debug)]
+pub struct Stencil {
+    /// Test passes if `func(reference & mask, stencil & mask)` is true
+    pub func: StencilFunc,
+    pub reference: u8,
+    pub mask: u8,
+    /// Update if the stencil test fails
+    pub fail: StencilOp,
+    /// Update if the stencil test passes, but the depth test fails
+    pub depth_fail: StencilOp,
+    /// Update if both tests pass
+    pub pass: StencilOp,
+}
+
+impl Stencil {
+    /// Test always passes, the stencil is kept
+    pub const ALWAYS: Self = Self {
+        func: StencilFunc::Always,
+        reference: 0,
+        mask: 0xff,
+        fail: StencilOp::Keep,
+        depth_fail: StencilOp::Keep,
+        pass: StencilOp::Keep,
+    };
+
+    /// Write `value` to every drawn pixel (regardless of depth)
+    pub const fn write(value: u8) -> Self {
+        Self {
+            reference: value,
+            depth_fail: StencilOp::Replace,
+            pass: StencilOp::Replace,
+            ..Self::ALWAYS
+        }
+    }
+
+    /// Only draw where the stencil equals `value`
+    pub const fn equal(value: u8) -> Self {
+        Self {
+            func: StencilFunc::Equal,
+            reference: value,
+            ..Self::ALWAYS
+        }
+    }
+
+    /// Only draw where the stencil doesn't equal `value`
+    pub const fn not_equal(value: u8) -> Self {
+        Self {
+            func: StencilFunc::NotEqual,
+            reference: value,
+            ..Self::ALWAYS
+        }
+    }
+
+    /// Set bits of the stencil used by the test
+    pub const fn with_mask(self, mask: u8) -> Self {
+        Self { mask, ..self }
+    }
+
+    /// Set stencil updates
+    pub const fn with_ops(self, fail: StencilOp, depth_fail: StencilOp, pass: StencilOp) -> Self {
+        Self {
+            fail,
+            depth_fail,
+            pass,
+            ..self
+        }
+    }
+}
+
+impl Default for Stencil {
+    fn default() -> Self {
+        Self::ALWAYS
+    }
+}
+
+impl<'gfx> Frame<'gfx> {
+    /// Enable the stencil test with the specified state, or disable it with `None`
+    pub fn set_stencil(&self, stencil: Option<&Stencil>) {
+        if let Some(stencil) = stencil {
+            self.ge()
+                .stencil_func(stencil.func, stencil.reference, stencil.mask)
+                .stencil_op(
+                    stencil.fail.to_gu(),
+                    stencil.depth_fail.to_gu(),
+                    stencil.pass.to_gu(),
+                );
+        }
+        pixel::set_state(GuState::StencilTest, stencil.is_some());
+    }
+
+    /// Clear the stencil buffer with the specified value
+    pub fn clear_stencil(&self, value: u8) {
+        unsafe {
+            sys::sceGuClearStencil(value as u32);
+            sys::sceGuClear(ClearBuffer::STENCIL_BUFFER_BIT);
+        }
+    }
+
+    /// Enable or disable writes to the color channels and the stencil
+    pub fn set_write_mask(&self, color: bool, stencil: bool) {
+        let rgb = if color { 0 } else { 0xffffff };
+        let alpha = if stencil { 0 } else { 0xff };
+        self.ge().pixel_mask((alpha << 24) | rgb);
+    }
+
+    /// Draw `content` clipped to the shape drawn by `mask`
+    ///
+    /// `mask` only writes the stencil (color and depth writes are disabled), so any
+    /// primitive works as a mask, e.g. a circle for a minimap. The stencil buffer is
+    /// cleared first, so masks can't be nested. Afterwards the stencil test is disabled
+    /// and depth writes are enabled.
+    pub fn with_mask(&self, mask: impl FnOnce(&Self), content: impl FnOnce(&Self)) {
+        self.clear_stencil(0);
+        self.set_write_mask(false, true);
+        self.set_depth_write(false);
+        self.set_stencil(Some(&Stencil::write(1)));
+        mask(self);
+
+        self.set_write_mask(true, false);
+        self.set_depth_write(true);
+        self.set_stencil(Some(&Stencil::equal(1)));
+        content(self);
+
+        self.set_write_mask(true, true);
+        self.set_stencil(None);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    Frame,
+    color::Color32,
+    rect::Rect,
+    sampler::Sampler,
+    sprite_batch::{Sprite, SpriteBatch},
+    texture::Texture,
+};
+
+pub mod bmfont;
+pub mod debug_font;
+mod layout;
+#[cfg(feature = "ttf")]
+pub mod ttf;
+
+pub use crate::rect::{HorizontalAlign, VerticalAlign};
+pub use bmfont::BmFont;
+pub use debug_font::DebugFont;
+use layout::font_glyph;
+pub use layout::{Glyph, Line, layout, measure};
+#[cfg(feature = "ttf")]
+pub use ttf::{AtlasFull, TtfFont};
+
+/// Font backed by one or more atlas textures
+pub trait Font {
+    /// Distance between two consecutive lines in pixels
+    fn line_height(&self) -> i32;
+
+    /// Get a glyph for the specified character
+    fn glyph(&self, ch: char) -> Option<Glyph>;
+
+    /// Get horizontal adjustment applied between two consecutive characters
+    fn kerning(&self, _left: char, _right: char) -> i32 {
+        0
+    }
+
+    /// Get atlas page texture, `None` if the page is not loaded
+    fn page(&self, page: usize) -> Option<&Texture>;
+
+    /// Get sampler used to draw the atlas pages
+    fn sampler(&self) -> Sampler {
+        Sampler::NEAREST
+    }
+}
+
+/// Text layout and color options
+#[derive(Clone, Copy)]
+pub struct TextOptions {
+    /// Color of the text
+    pub color: Color32,
+    /// Horizontal alignment of each line inside of the rectangle
+    pub align: HorizontalAlign,
+    /// Vertical alignment of the text block inside of the rectangle
+    pub vertical_align: VerticalAlign,
+    /// Wrap lines that don't fit into the rectangle width
+    pub wrap: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            color: Color32::WHITE,
+            align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            wrap: true,
+        }
+    }
+}
+
+/// Queue text into a sprite batch, laid out inside of `rect`
+///
+/// `color` is called for each glyph with its byte offset in `text`, allowing per-glyph colors
+pub fn queue_text<'gfx, F: Font + ?Sized>(
+    batch: &mut SpriteBatch<'_, 'gfx>,
+    font: &'gfx F,
+    rect: Rect,
+    text: &str,
+    options: &TextOptions,
+    mut color: impl FnMut(usize, char) -> Color32,
+) {
+    let lines = layout(font, text, options.wrap.then_some(rect.w));
+    let line_height = font.line_height();
+    let block_height = lines.len() as i32 * line_height;
+    let mut y = rect.y
+        + match options.vertical_align {
+            VerticalAlign::Top => 0,
+            VerticalAlign::Middle => (rect.h - block_height) / 2,
+            VerticalAlign::Bottom => rect.h - block_height,
+        };
+    let sampler = font.sampler();
+    for line in lines {
+        let mut x = rect.x
+            + match options.align {
+                HorizontalAlign::Left => 0,
+                HorizontalAlign::Center => (rect.w - line.width) / 2,
+                HorizontalAlign::Right => rect.w - line.width,
+            };
+        let mut prev = None;
+        for (idx, ch) in text[line.range.clone()].char_indices() {
+            if let Some(prev) = prev {
+                x += font.kerning(prev, ch);
+            }
+            prev = Some(ch);
+            let Some(glyph) = font_glyph(font, ch) else {
+                continue;
+            };
+            let page = font.page(glyph.page);
+            if let Some(page) = page.filter(|_| glyph.src.w > 0 && glyph.src.h > 0) {
+                batch.set_texture(Some(page), &sampler);
+                batch.draw(
+                    Sprite::new(Rect::new(
+                        x + glyph.offset_x,
+                        y + glyph.offset_y,
+                        glyph.src.w,
+                        glyph.src.h,
+                    ))
+                    .with_src(glyph.src)
+                    .with_color(color(line.range.start + idx, ch)),
+                );
+            }
+            x += glyph.advance;
+        }
+        y += line_height;
+    }
+}
+
+impl<'gfx> Frame<'gfx> {
+    /// Draw a single line of text with the top left corner at the specified position
+    ///
+    /// Glyph edges are transparent, enable alpha blending or alpha test before drawing text
+    pub fn draw_text<F: Font + ?Sized>(
+        &self,
+        font: &'gfx F,
+        x: i32,
+        y: i32,
+        text: &str,
+        color: Color32,
+    ) {
+        let options = TextOptions {
+            color,
+            wrap: false,
+            ..Default::default()
+        };
+        self.draw_text_in_rect(font, Rect::new(x, y, 0, 0), text, &options);
+    }
+
+    /// Draw text laid out inside of a rectangle
+    pub fn draw_text_in_rect<F: Font + ?Sized>(
+        &self,
+        font: &'gfx F,
+        rect: Rect,
+        text: &str,
+        options: &TextOptions,
+    ) {
+        self.draw_text_with(font, rect, text, options, |_, _| options.color);
+    }
+
+    /// Draw text laid out inside of a rectangle, with the color of each glyph chosen by `color`
+    ///
+    /// See [`queue_text`] for details
+    pub fn draw_text_with<F: Font + ?Sized>(
+        &self,
+        font: &'gfx F,
+        rect: Rect,
+        text: &str,
+        options: &TextOptions,
+        color: impl FnMut(usize, char) -> Color32,
+    ) {
+        let mut batch = SpriteBatch::new(self);
+        batch.set_strip_width(None);
+        queue_text(&mut batch, font, rect, text, options, color);
+    }
+}
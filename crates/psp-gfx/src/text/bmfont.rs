@@ -0,0 +1,77 @@
+//! AngelCode BMFont (`.fnt`) loader
+//!
+//! Both the text and the binary (version 3) descriptor formats are supported.
+//! Atlas pages are not loaded automatically, decode the files listed in
+//! [`BmFont::page_files`] and pass them to [`BmFont::set_pages`].
+
+use alloc::{string::String, vec::Vec};
+
+use super::{Font, Glyph};
+use crate::{sampler::Sampler, texture::Texture};
+
+mod file;
+
+pub use file::BmFontError;
+use file::BmFontFile;
+
+/// Bitmap font described by an AngelCode BMFont descriptor
+pub struct BmFont {
+    file: BmFontFile,
+    pages: Vec<Texture>,
+    sampler: Sampler,
+}
+
+impl BmFont {
+    /// Parse a font descriptor in either the text or binary format
+    pub fn parse(data: &[u8]) -> Result<Self, BmFontError> {
+        Ok(Self {
+            file: BmFontFile::parse(data)?,
+            pages: Vec::new(),
+            sampler: Sampler::NEAREST,
+        })
+    }
+
+    /// Get file names of the atlas pages, in page order
+    pub fn page_files(&self) -> &[String] {
+        &self.file.page_files
+    }
+
+    /// Set atlas page textures, in page order
+    ///
+    /// Glyphs on pages without a texture are not drawn.
+    pub fn set_pages(&mut self, pages: Vec<Texture>) {
+        self.pages = pages;
+    }
+
+    /// Set sampler used to draw the atlas pages
+    pub fn set_sampler(&mut self, sampler: Sampler) {
+        self.sampler = sampler;
+    }
+
+    /// Distance from the top of the line to the baseline
+    pub fn base(&self) -> i32 {
+        self.file.base
+    }
+}
+
+impl Font for BmFont {
+    fn line_height(&self) -> i32 {
+        self.file.line_height
+    }
+
+    fn glyph(&self, ch: char) -> Option<Glyph> {
+        self.file.glyph(ch)
+    }
+
+    fn kerning(&self, left: char, right: char) -> i32 {
+        self.file.kerning(left, right)
+    }
+
+    fn page(&self, page: usize) -> Option<&Texture> {
+        self.pages.get(page)
+    }
+
+    fn sampler(&self) -> Sampler {
+        self.sampler
+    }
+}
@@ -0,0 +1,287 @@
+//! Parser of AngelCode BMFont descriptors
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use crate::{rect::Rect, text::Glyph};
+
+/// Error returned when parsing a font descriptor fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BmFontError {
+    /// Descriptor is truncated
+    UnexpectedEof,
+    /// Binary descriptor has an unsupported version
+    UnsupportedVersion(u8),
+    /// A value could not be parsed
+    InvalidValue,
+    /// Descriptor contains text that is not valid UTF-8
+    InvalidUtf8,
+    /// The `common` block is missing
+    MissingCommon,
+    /// A glyph references a page that is not listed in the descriptor
+    MissingPage(usize),
+}
+
+/// Metrics and page names of a font descriptor
+pub struct BmFontFile {
+    /// Distance between two consecutive lines
+    pub line_height: i32,
+    /// Distance from the top of the line to the baseline
+    pub base: i32,
+    /// File names of the atlas pages, in page order
+    pub page_files: Vec<String>,
+    pub glyphs: BTreeMap<u32, Glyph>,
+    /// Glyph drawn for characters without a glyph (`char id=-1`)
+    pub missing: Option<Glyph>,
+    pub kerning: BTreeMap<(u32, u32), i32>,
+}
+
+impl BmFontFile {
+    /// Parse a font descriptor in either the text or binary format
+    pub fn parse(data: &[u8]) -> Result<Self, BmFontError> {
+        let mut font = Self {
+            line_height: 0,
+            base: 0,
+            page_files: Vec::new(),
+            glyphs: BTreeMap::new(),
+            missing: None,
+            kerning: BTreeMap::new(),
+        };
+        if let Some(data) = data.strip_prefix(b"BMF") {
+            font.parse_binary(data)?;
+        } else {
+            let text = core::str::from_utf8(data).map_err(|_| BmFontError::InvalidUtf8)?;
+            font.parse_text(text)?;
+        }
+        if font.line_height == 0 {
+            return Err(BmFontError::MissingCommon);
+        }
+        if let Some(glyph) = font
+            .glyphs
+            .values()
+            .chain(&font.missing)
+            .find(|glyph| glyph.page >= font.page_files.len())
+        {
+            return Err(BmFontError::MissingPage(glyph.page));
+        }
+        Ok(font)
+    }
+
+    /// Get the glyph of a character, or the missing glyph of the font
+    pub fn glyph(&self, ch: char) -> Option<Glyph> {
+        self.glyphs
+            .get(&(ch as u32))
+            .or(self.missing.as_ref())
+            .copied()
+    }
+
+    /// Get the horizontal adjustment between two consecutive characters
+    pub fn kerning(&self, left: char, right: char) -> i32 {
+        self.kerning
+            .get(&(left as u32, right as u32))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn parse_text(&mut self, text: &str) -> Result<(), BmFontError> {
+        for line in text.lines() {
+            let mut tokens = Tokenizer(line.trim());
+            let Some(tag) = tokens.next() else {
+                continue;
+            };
+            let mut attrs = BTreeMap::new();
+            for token in tokens {
+                if let Some((key, value)) = token.split_once('=') {
+                    attrs.insert(key, value.trim_matches('"'));
+                }
+            }
+            let int = |key: &str| -> Result<i32, BmFontError> {
+                attrs.get(key).map_or(Ok(0), |value| {
+                    value.parse().map_err(|_| BmFontError::InvalidValue)
+                })
+            };
+            let uint = |key: &str| -> Result<u32, BmFontError> {
+                attrs.get(key).map_or(Ok(0), |value| {
+                    value.parse().map_err(|_| BmFontError::InvalidValue)
+                })
+            };
+            // Page indices are stored as a byte in the binary format
+            let page = |key: &str| -> Result<usize, BmFontError> {
+                u8::try_from(uint(key)?)
+                    .map(usize::from)
+                    .map_err(|_| BmFontError::InvalidValue)
+            };
+            // Characters are stored as `u32` in the binary format, `-1` is the glyph drawn
+            // for characters missing from the font
+            let char_id = |key: &str| -> Result<Option<u32>, BmFontError> {
+                match int(key)? {
+                    -1 => Ok(None),
+                    id => u32::try_from(id)
+                        .map(Some)
+                        .map_err(|_| BmFontError::InvalidValue),
+                }
+            };
+            match tag {
+                "common" => {
+                    self.line_height = int("lineHeight")?;
+                    self.base = int("base")?;
+                }
+                "page" => {
+                    let id = page("id")?;
+                    let file = attrs.get("file").copied().unwrap_or_default();
+                    if self.page_files.len() <= id {
+                        self.page_files.resize(id + 1, String::new());
+                    }
+                    self.page_files[id] = file.into();
+                }
+                "char" => {
+                    let glyph = Glyph {
+                        page: page("page")?,
+                        src: Rect::new(int("x")?, int("y")?, int("width")?, int("height")?),
+                        offset_x: int("xoffset")?,
+                        offset_y: int("yoffset")?,
+                        advance: int("xadvance")?,
+                    };
+                    match char_id("id")? {
+                        Some(id) => {
+                            self.glyphs.insert(id, glyph);
+                        }
+                        None => self.missing = Some(glyph),
+                    }
+                }
+                "kerning" => {
+                    // Pairs with the missing glyph never match a character
+                    if let (Some(first), Some(second)) = (char_id("first")?, char_id("second")?) {
+                        self.kerning.insert((first, second), int("amount")?);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_binary(&mut self, data: &[u8]) -> Result<(), BmFontError> {
+        let mut reader = Reader(data);
+        let version = reader.u8()?;
+        if version != 3 {
+            return Err(BmFontError::UnsupportedVersion(version));
+        }
+        while !reader.0.is_empty() {
+            let block_type = reader.u8()?;
+            let size = reader.u32()? as usize;
+            let mut block = Reader(reader.take(size)?);
+            match block_type {
+                // common
+                2 => {
+                    self.line_height = block.u16()? as i32;
+                    self.base = block.u16()? as i32;
+                }
+                // pages
+                3 => {
+                    for name in block.0.split(|&byte| byte == 0) {
+                        if name.is_empty() {
+                            continue;
+                        }
+                        let name =
+                            core::str::from_utf8(name).map_err(|_| BmFontError::InvalidUtf8)?;
+                        self.page_files.push(name.into());
+                    }
+                }
+                // chars
+                4 => {
+                    while !block.0.is_empty() {
+                        let id = block.u32()?;
+                        let x = block.u16()? as i32;
+                        let y = block.u16()? as i32;
+                        let width = block.u16()? as i32;
+                        let height = block.u16()? as i32;
+                        let offset_x = block.u16()? as i16 as i32;
+                        let offset_y = block.u16()? as i16 as i32;
+                        let advance = block.u16()? as i16 as i32;
+                        let page = block.u8()? as usize;
+                        let _channel = block.u8()?;
+                        let glyph = Glyph {
+                            page,
+                            src: Rect::new(x, y, width, height),
+                            offset_x,
+                            offset_y,
+                            advance,
+                        };
+                        if id == u32::MAX {
+                            self.missing = Some(glyph);
+                        } else {
+                            self.glyphs.insert(id, glyph);
+                        }
+                    }
+                }
+                // kerning pairs
+                5 => {
+                    while !block.0.is_empty() {
+                        let first = block.u32()?;
+                        let second = block.u32()?;
+                        let amount = block.u16()? as i16 as i32;
+                        self.kerning.insert((first, second), amount);
+                    }
+                }
+                // info and unknown blocks
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a line of the text format into whitespace separated tokens, keeping quoted strings intact
+struct Tokenizer<'a>(&'a str);
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.0.trim_start();
+        if text.is_empty() {
+            return None;
+        }
+        let mut quoted = false;
+        let end = text
+            .char_indices()
+            .find(|&(_, ch)| {
+                if ch == '"' {
+                    quoted = !quoted;
+                }
+                ch.is_whitespace() && !quoted
+            })
+            .map(|(idx, _)| idx)
+            .unwrap_or(text.len());
+        self.0 = &text[end..];
+        Some(&text[..end])
+    }
+}
+
+/// Little endian reader for the binary format
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BmFontError> {
+        if self.0.len() < len {
+            return Err(BmFontError::UnexpectedEof);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, BmFontError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BmFontError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, BmFontError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
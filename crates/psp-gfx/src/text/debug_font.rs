@@ -0,0 +1,93 @@
+//! Built-in 8x8 debug font
+
+use alloc::vec;
+use psp::sys::TexturePixelFormat;
+
+use super::{Font, Glyph};
+use crate::{rect::Rect, texture::Texture};
+
+/// 8x8 font covering printable ASCII characters (`0x20..0x80`), one byte per row, LSB first
+///
+/// (Same font as the one used by `sceGuDebugPrint`)
+static FONT_DATA: [u8; 768] = *include_bytes!("debugfont.bin");
+
+const FIRST_CHAR: u32 = 0x20;
+const GLYPH_COUNT: u32 = 96;
+const GLYPH_SIZE: u32 = 8;
+const GLYPHS_PER_ROW: u32 = 16;
+const ATLAS_WIDTH: u32 = GLYPHS_PER_ROW * GLYPH_SIZE;
+const ATLAS_HEIGHT: u32 = 64;
+
+/// Monospace 8x8 bitmap font embedded in the crate
+///
+/// The glyphs are rasterized into a white [`TexturePixelFormat::Psm4444`] atlas with a
+/// transparent background, so the text color is controlled by the sprite tint.
+pub struct DebugFont {
+    atlas: Texture,
+}
+
+impl DebugFont {
+    /// Rasterize the font into a new atlas texture
+    pub fn new() -> Self {
+        let mut pixels = vec![0u8; (ATLAS_WIDTH * ATLAS_HEIGHT * 2) as usize];
+        for glyph in 0..GLYPH_COUNT {
+            let (glyph_x, glyph_y) = glyph_position(glyph);
+            for row in 0..GLYPH_SIZE {
+                let bits = FONT_DATA[(glyph * GLYPH_SIZE + row) as usize];
+                for col in 0..GLYPH_SIZE {
+                    if bits & (1 << col) == 0 {
+                        continue;
+                    }
+                    let idx = ((glyph_y + row) * ATLAS_WIDTH + glyph_x + col) as usize;
+                    pixels[(idx * 2)..(idx * 2 + 2)].copy_from_slice(&0xffffu16.to_le_bytes());
+                }
+            }
+        }
+        Self {
+            atlas: Texture::new(
+                TexturePixelFormat::Psm4444,
+                ATLAS_WIDTH,
+                ATLAS_HEIGHT,
+                &pixels,
+            ),
+        }
+    }
+}
+
+impl Default for DebugFont {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Font for DebugFont {
+    fn line_height(&self) -> i32 {
+        GLYPH_SIZE as i32
+    }
+
+    fn glyph(&self, ch: char) -> Option<Glyph> {
+        let glyph = (ch as u32).checked_sub(FIRST_CHAR)?;
+        if glyph >= GLYPH_COUNT {
+            return None;
+        }
+        let (x, y) = glyph_position(glyph);
+        Some(Glyph {
+            page: 0,
+            src: Rect::new(x as i32, y as i32, GLYPH_SIZE as i32, GLYPH_SIZE as i32),
+            offset_x: 0,
+            offset_y: 0,
+            advance: GLYPH_SIZE as i32,
+        })
+    }
+
+    fn page(&self, _page: usize) -> Option<&Texture> {
+        Some(&self.atlas)
+    }
+}
+
+fn glyph_position(glyph: u32) -> (u32, u32) {
+    (
+        (glyph % GLYPHS_PER_ROW) * GLYPH_SIZE,
+        (glyph / GLYPHS_PER_ROW) * GLYPH_SIZE,
+    )
+}
@@ -0,0 +1,128 @@
+//! Line breaking and measuring of text
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools, which provide
+//! their own font metrics trait without the atlas pages.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::Font;
+use crate::rect::Rect;
+
+/// Location and metrics of a single glyph inside of a font atlas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    /// Index of the atlas page the glyph is stored in
+    pub page: usize,
+    /// Source rectangle of the glyph in the atlas page (in texels)
+    pub src: Rect,
+    /// Horizontal offset from the pen position to the glyph
+    pub offset_x: i32,
+    /// Vertical offset from the top of the line to the glyph
+    pub offset_y: i32,
+    /// Horizontal distance to move the pen by after drawing the glyph
+    pub advance: i32,
+}
+
+/// Single line of laid out text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    /// Byte range of the line in the source text
+    pub range: Range<usize>,
+    /// Width of the line in pixels
+    pub width: i32,
+}
+
+/// Split text into lines, wrapping at whitespace if `max_width` is specified
+///
+/// Words longer than `max_width` are split at character boundaries
+pub fn layout<F: Font + ?Sized>(font: &F, text: &str, max_width: Option<i32>) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut base = 0;
+    for paragraph in text.split('\n') {
+        wrap_paragraph(font, paragraph, base, max_width, &mut lines);
+        base += paragraph.len() + 1;
+    }
+    lines
+}
+
+/// Measure size of the text in pixels
+pub fn measure<F: Font + ?Sized>(font: &F, text: &str, max_width: Option<i32>) -> (i32, i32) {
+    let lines = layout(font, text, max_width);
+    let width = lines
+        .iter()
+        .map(|line| line.width)
+        .max()
+        .unwrap_or_default();
+    (width, lines.len() as i32 * font.line_height())
+}
+
+/// Get glyph, falling back to `?` for missing characters
+pub(crate) fn font_glyph<F: Font + ?Sized>(font: &F, ch: char) -> Option<Glyph> {
+    font.glyph(ch).or_else(|| font.glyph('?'))
+}
+
+fn measure_line<F: Font + ?Sized>(font: &F, text: &str) -> i32 {
+    let mut width = 0;
+    let mut prev = None;
+    for ch in text.chars() {
+        width += advance(font, prev, ch);
+        prev = Some(ch);
+    }
+    width
+}
+
+fn advance<F: Font + ?Sized>(font: &F, prev: Option<char>, ch: char) -> i32 {
+    let kerning = prev.map(|prev| font.kerning(prev, ch)).unwrap_or_default();
+    kerning
+        + font_glyph(font, ch)
+            .map(|glyph| glyph.advance)
+            .unwrap_or_default()
+}
+
+fn wrap_paragraph<F: Font + ?Sized>(
+    font: &F,
+    text: &str,
+    base: usize,
+    max_width: Option<i32>,
+    lines: &mut Vec<Line>,
+) {
+    let mut push_line = |range: Range<usize>| {
+        let line = text[range.clone()].trim_end();
+        lines.push(Line {
+            range: (base + range.start)..(base + range.start + line.len()),
+            width: measure_line(font, line),
+        });
+    };
+
+    let mut start = 0;
+    let mut width = 0;
+    let mut prev = None;
+    let mut last_space = None;
+    for (idx, ch) in text.char_indices() {
+        let mut char_advance = advance(font, prev, ch);
+        if let Some(max_width) = max_width
+            && width + char_advance > max_width
+            && idx > start
+            && !ch.is_whitespace()
+        {
+            // Break at the last whitespace, or in the middle of the word if there is none
+            let (end, next_start) = match last_space {
+                Some(space) => space,
+                None => (idx, idx),
+            };
+            push_line(start..end);
+            start = next_start;
+            width = measure_line(font, &text[start..idx]);
+            prev = text[start..idx].chars().next_back();
+            last_space = None;
+            char_advance = advance(font, prev, ch);
+        }
+        if ch.is_whitespace() {
+            last_space = Some((idx, idx + ch.len_utf8()));
+        }
+        width += char_advance;
+        prev = Some(ch);
+    }
+    push_line(start..text.len());
+}
@@ -0,0 +1,236 @@
+//! TrueType/OpenType font support (requires the `ttf` feature)
+//!
+//! Glyphs are rasterized on demand into a fixed grid of cells in an atlas texture.
+//! When the atlas is full, the least recently used glyph is evicted. Glyphs drawn
+//! during the current frame are never evicted, as the display list may still
+//! reference them. If all cells are used by the current frame, [`TtfFont::try_glyph`]
+//! returns [`AtlasFull`] and drawn text treats the glyph as missing, use a larger atlas
+//! in that case.
+
+use ab_glyph::{Font as _, FontVec, GlyphId, InvalidFont, PxScale, ScaleFont as _};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cell::RefCell;
+use psp::sys::TexturePixelFormat;
+
+use super::{Font, Glyph};
+use crate::{current_frame, rect::Rect, texture::Texture};
+
+/// Default atlas width and height in pixels
+pub const DEFAULT_ATLAS_SIZE: u32 = 256;
+
+/// Error returned when a glyph doesn't fit into the atlas
+///
+/// All cells hold glyphs drawn during the current frame, which can't be evicted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasFull;
+
+/// Vector font rasterized into a dynamic glyph cache
+pub struct TtfFont {
+    font: FontVec,
+    scale: PxScale,
+    ascent: f32,
+    line_height: i32,
+    cell_size: u32,
+    cells_per_row: u32,
+    /// Glyphs are rasterized with [`Texture::write_region`] while references returned by
+    /// [`Font::page`] are alive
+    atlas: Texture,
+    cache: RefCell<GlyphCache>,
+}
+
+struct GlyphCache {
+    /// Cached glyphs and the cell they're stored in
+    glyphs: BTreeMap<char, CachedGlyph>,
+    /// Occupancy of each atlas cell
+    cells: Vec<bool>,
+}
+
+#[derive(Clone, Copy)]
+struct CachedGlyph {
+    glyph: Glyph,
+    cell: usize,
+    last_used: u32,
+}
+
+impl TtfFont {
+    /// Load a font from TTF/OTF data, rasterized at the specified pixel height
+    pub fn new(data: Vec<u8>, pixel_height: f32) -> Result<Self, InvalidFont> {
+        Self::with_atlas_size(data, pixel_height, DEFAULT_ATLAS_SIZE, DEFAULT_ATLAS_SIZE)
+    }
+
+    /// Load a font, using a glyph atlas of the specified size (must be a power of two)
+    pub fn with_atlas_size(
+        data: Vec<u8>,
+        pixel_height: f32,
+        atlas_width: u32,
+        atlas_height: u32,
+    ) -> Result<Self, InvalidFont> {
+        let font = FontVec::try_from_vec(data)?;
+        let scale = PxScale::from(pixel_height);
+        let scaled = font.as_scaled(scale);
+        let ascent = scaled.ascent();
+        let line_height = libm::ceilf(scaled.height() + scaled.line_gap()) as i32;
+        // One pixel of padding prevents bleeding between neighbouring glyphs
+        let cell_size = libm::ceilf(scaled.height()) as u32 + 1;
+        assert!(
+            cell_size <= atlas_width && cell_size <= atlas_height,
+            "glyph atlas is too small for the font size"
+        );
+        let cells_per_row = atlas_width / cell_size;
+        let cell_count = (cells_per_row * (atlas_height / cell_size)) as usize;
+
+        let atlas = Texture::empty(TexturePixelFormat::Psm4444, atlas_width, atlas_height, 1);
+        Ok(Self {
+            font,
+            scale,
+            ascent,
+            line_height,
+            cell_size,
+            cells_per_row,
+            atlas,
+            cache: RefCell::new(GlyphCache {
+                glyphs: BTreeMap::new(),
+                cells: alloc::vec![false; cell_count],
+            }),
+        })
+    }
+
+    /// Rasterize glyphs ahead of time, e.g. all characters of a string that will be drawn soon
+    pub fn preload(&self, text: &str) -> Result<(), AtlasFull> {
+        for ch in text.chars() {
+            self.try_glyph(ch)?;
+        }
+        Ok(())
+    }
+
+    /// Get a glyph, rasterizing it if it's not cached yet
+    ///
+    /// Returns `Ok(None)` if the font has no glyph for the character.
+    pub fn try_glyph(&self, ch: char) -> Result<Option<Glyph>, AtlasFull> {
+        let frame = current_frame();
+        let mut cache = self.cache.borrow_mut();
+        if let Some(cached) = cache.glyphs.get_mut(&ch) {
+            cached.last_used = frame;
+            return Ok(Some(cached.glyph));
+        }
+        let Some(id) = self.glyph_id(ch) else {
+            return Ok(None);
+        };
+        let cell = Self::allocate_cell(&mut cache, frame).ok_or(AtlasFull)?;
+        let glyph = self.rasterize(id, cell);
+        cache.cells[cell] = true;
+        cache.glyphs.insert(
+            ch,
+            CachedGlyph {
+                glyph,
+                cell,
+                last_used: frame,
+            },
+        );
+        Ok(Some(glyph))
+    }
+
+    /// Remove all glyphs from the cache
+    pub fn clear_cache(&mut self) {
+        let cache = self.cache.get_mut();
+        cache.glyphs.clear();
+        cache.cells.fill(false);
+    }
+
+    fn glyph_id(&self, ch: char) -> Option<GlyphId> {
+        let id = self.font.glyph_id(ch);
+        (id.0 != 0 || ch == '\0').then_some(id)
+    }
+
+    /// Find a free cell, or evict the least recently used glyph
+    fn allocate_cell(cache: &mut GlyphCache, frame: u32) -> Option<usize> {
+        if let Some(cell) = cache.cells.iter().position(|&used| !used) {
+            return Some(cell);
+        }
+        let (&ch, &victim) = cache
+            .glyphs
+            .iter()
+            .filter(|(_, cached)| cached.last_used != frame)
+            .min_by_key(|(_, cached)| cached.last_used)?;
+        cache.glyphs.remove(&ch);
+        cache.cells[victim.cell] = false;
+        Some(victim.cell)
+    }
+
+    /// Rasterize a glyph into an atlas cell
+    fn rasterize(&self, id: GlyphId, cell: usize) -> Glyph {
+        let scaled = self.font.as_scaled(self.scale);
+        let cell_x = (cell as u32 % self.cells_per_row) * self.cell_size;
+        let cell_y = (cell as u32 / self.cells_per_row) * self.cell_size;
+        let advance = libm::roundf(scaled.h_advance(id)) as i32;
+
+        // The cell is rasterized here and written into the atlas at once
+        let size = self.cell_size;
+        let mut pixels = alloc::vec![0; (size * size * 2) as usize];
+        let cell_rect = Rect::new(cell_x as i32, cell_y as i32, size as i32, size as i32);
+        let write_cell = |pixels: &[u8]| {
+            // SAFETY: the cell isn't used by any glyph drawn during the current frame, and
+            // the atlas data is only borrowed as a slice while the atlas is bound
+            unsafe { self.atlas.write_region(0, cell_rect, pixels) }
+        };
+
+        let glyph = id.with_scale_and_position(self.scale, ab_glyph::point(0., self.ascent));
+        let Some(outline) = self.font.outline_glyph(glyph) else {
+            write_cell(&pixels);
+            // Whitespace and other glyphs without an outline
+            return Glyph {
+                page: 0,
+                src: Rect::new(cell_x as i32, cell_y as i32, 0, 0),
+                offset_x: 0,
+                offset_y: 0,
+                advance,
+            };
+        };
+        let bounds = outline.px_bounds();
+        let limit = self.cell_size - 1;
+        outline.draw(|x, y, coverage| {
+            if x < limit && y < limit {
+                let alpha = libm::roundf(coverage.clamp(0., 1.) * 15.) as u16;
+                let idx = ((y * size + x) * 2) as usize;
+                pixels[idx..idx + 2].copy_from_slice(&(0x0fff | (alpha << 12)).to_le_bytes());
+            }
+        });
+        write_cell(&pixels);
+        Glyph {
+            page: 0,
+            src: Rect::new(
+                cell_x as i32,
+                cell_y as i32,
+                (bounds.width() as u32).min(limit) as i32,
+                (bounds.height() as u32).min(limit) as i32,
+            ),
+            offset_x: bounds.min.x as i32,
+            offset_y: bounds.min.y as i32,
+            advance,
+        }
+    }
+}
+
+impl Font for TtfFont {
+    fn line_height(&self) -> i32 {
+        self.line_height
+    }
+
+    fn glyph(&self, ch: char) -> Option<Glyph> {
+        // A full atlas is handled like a missing glyph
+        self.try_glyph(ch).ok().flatten()
+    }
+
+    fn kerning(&self, left: char, right: char) -> i32 {
+        match (self.glyph_id(left), self.glyph_id(right)) {
+            (Some(left), Some(right)) => {
+                libm::roundf(self.font.as_scaled(self.scale).kern(left, right)) as i32
+            }
+            _ => 0,
+        }
+    }
+
+    fn page(&self, _page: usize) -> Option<&Texture> {
+        Some(&self.atlas)
+    }
+}
@@ -0,0 +1,359 @@
+use crate::cache;
+use crate::rect::Rect;
+use crate::transfer::TransferImage;
+use crate::vram::VramBlock;
+use alloc::vec::Vec;
+use bytemuck::{Pod, Zeroable};
+use core::cell::{Cell, UnsafeCell};
+use psp::sys::{MipmapLevel, TexturePixelFormat};
+
+mod dxt;
+mod layout;
+pub mod mipmap;
+
+pub use layout::*;
+pub use mipmap::MipmapFilter;
+
+/// Maximum amount of mip levels supported by the GE (including the base level)
+pub const MAX_MIP_LEVELS: usize = 8;
+
+/// Maximum width/height of a texture supported by the GE
+pub const MAX_TEXTURE_SIZE: u32 = 512;
+
+/// 16 byte aligned storage unit, used to satisfy the GE alignment requirements
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C, align(16))]
+struct Block([u8; 16]);
+
+/// Texture object stored in main memory, with up to [`MAX_MIP_LEVELS`] mip levels
+///
+/// Modifications are written back from the data cache when the texture is bound.
+pub struct Texture {
+    format: TexturePixelFormat,
+    levels: Vec<TextureLevel>,
+    /// Written through shared references by [`Texture::write_region`]
+    data: Vec<UnsafeCell<Block>>,
+    /// Data cache contains writes which aren't in memory yet
+    dirty: Cell<bool>,
+}
+
+impl Texture {
+    /// Create a new texture from pixel data with tightly packed rows
+    ///
+    /// `width` and `height` must be powers of two, no larger than [`MAX_TEXTURE_SIZE`]
+    pub fn new(format: TexturePixelFormat, width: u32, height: u32, pixels: &[u8]) -> Self {
+        Self::with_mip_levels(format, width, height, &[pixels])
+    }
+
+    /// Create a new texture from pixel data of each mip level, starting with the base level
+    ///
+    /// Each level is half the size of the previous one (but no smaller than 1x1)
+    pub fn with_mip_levels(
+        format: TexturePixelFormat,
+        width: u32,
+        height: u32,
+        levels: &[&[u8]],
+    ) -> Self {
+        assert!(!levels.is_empty(), "texture must have at least one level");
+        let mut texture = Self::empty(format, width, height, levels.len());
+        for (idx, pixels) in levels.iter().enumerate() {
+            texture.write_level(idx, pixels);
+        }
+        texture
+    }
+
+    /// Create a new zero-filled texture with the specified amount of mip levels
+    pub fn empty(format: TexturePixelFormat, width: u32, height: u32, level_count: usize) -> Self {
+        assert!(
+            width.is_power_of_two() && height.is_power_of_two(),
+            "texture size must be a power of two"
+        );
+        assert!(
+            width <= MAX_TEXTURE_SIZE && height <= MAX_TEXTURE_SIZE,
+            "texture size must not exceed {MAX_TEXTURE_SIZE}x{MAX_TEXTURE_SIZE}"
+        );
+        assert!(
+            (1..=MAX_MIP_LEVELS).contains(&level_count),
+            "texture must have between 1 and {MAX_MIP_LEVELS} levels"
+        );
+        let levels = layout_levels(format, width, height, level_count);
+        let total_size = levels
+            .last()
+            .map(|level| level.offset + level.byte_size(format))
+            .unwrap_or_default();
+        Self {
+            format,
+            levels,
+            data: bytemuck::zeroed_vec(total_size.div_ceil(size_of::<Block>())),
+            dirty: Cell::new(true),
+        }
+    }
+
+    /// Get pixel format of the texture
+    pub fn format(&self) -> TexturePixelFormat {
+        self.format
+    }
+
+    /// Get width of the base level in pixels
+    pub fn width(&self) -> u32 {
+        self.levels[0].width
+    }
+
+    /// Get height of the base level in pixels
+    pub fn height(&self) -> u32 {
+        self.levels[0].height
+    }
+
+    /// Get amount of mip levels (including the base level)
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Get layout of all mip levels
+    pub fn levels(&self) -> &[TextureLevel] {
+        &self.levels
+    }
+
+    /// Get raw data of a mip level (rows are `buffer_width` pixels long)
+    pub fn level_data(&self, level: usize) -> &[u8] {
+        let level = self.levels[level];
+        &self.bytes()[level.offset..(level.offset + level.byte_size(self.format))]
+    }
+
+    /// Get mutable raw data of a mip level (rows are `buffer_width` pixels long)
+    pub fn level_data_mut(&mut self, level: usize) -> &mut [u8] {
+        let level = self.levels[level];
+        let size = level.byte_size(self.format);
+        &mut self.bytes_mut()[level.offset..(level.offset + size)]
+    }
+
+    /// Overwrite a mip level with pixel data with tightly packed rows
+    pub fn write_level(&mut self, level: usize, pixels: &[u8]) {
+        let format = self.format;
+        let TextureLevel {
+            width,
+            height,
+            buffer_width,
+            ..
+        } = self.levels[level];
+        let row_size = level_byte_size(format, width, block_rows(format, 1));
+        let stride = level_byte_size(format, buffer_width, block_rows(format, 1));
+        let rows = (height / block_rows(format, 1)).max(1) as usize;
+        assert_eq!(
+            pixels.len(),
+            row_size * rows,
+            "pixel data size does not match level size"
+        );
+        let dst = self.level_data_mut(level);
+        for (dst_row, src_row) in dst.chunks_mut(stride).zip(pixels.chunks(row_size)) {
+            dst_row[..row_size].copy_from_slice(src_row);
+        }
+    }
+
+    /// Overwrite a rectangle of a mip level through a shared reference, e.g. glyphs
+    /// rasterized into an atlas which sprites are drawn from
+    ///
+    /// `pixels` holds the rows of the rectangle, tightly packed. Compressed formats and
+    /// formats with less than 8 bits per pixel aren't supported.
+    ///
+    /// # Safety
+    ///
+    /// No slice returned by [`Texture::level_data`] may be alive, and no queued draw may
+    /// read the rectangle.
+    pub unsafe fn write_region(&self, level: usize, rect: Rect, pixels: &[u8]) {
+        let bits = bits_per_pixel(self.format);
+        assert!(
+            !is_compressed(self.format) && bits >= 8,
+            "regions can't be written in compressed or 4-bit formats"
+        );
+        let level = self.levels[level];
+        assert!(
+            rect.x >= 0
+                && rect.y >= 0
+                && rect.right() <= level.width as i32
+                && rect.bottom() <= level.height as i32,
+            "region is outside of the level"
+        );
+        let pixel_size = bits as usize / 8;
+        let row_size = rect.w.max(0) as usize * pixel_size;
+        assert_eq!(
+            pixels.len(),
+            row_size * rect.h.max(0) as usize,
+            "pixel data size does not match region size"
+        );
+        if rect.is_empty() {
+            return;
+        }
+        let data = UnsafeCell::raw_get(self.data.as_ptr()).cast::<u8>();
+        for (row, src) in pixels.chunks_exact(row_size).enumerate() {
+            let y = rect.y as usize + row;
+            let offset =
+                level.offset + (y * level.buffer_width as usize + rect.x as usize) * pixel_size;
+            // SAFETY: the row is inside of the level, and the caller guarantees that no
+            // reference to the data is alive
+            unsafe {
+                core::ptr::copy_nonoverlapping(src.as_ptr(), data.add(offset), row_size);
+            }
+        }
+        self.dirty.set(true);
+    }
+
+    /// Regenerate all mip levels below the base level using the specified filter
+    ///
+    /// Indexed formats are downsampled using nearest-neighbour sampling, as their indices can't be
+    /// averaged. Compressed (DXT) formats are decoded, downsampled and compressed again, which
+    /// loses some quality.
+    pub fn generate_mipmaps(&mut self, filter: MipmapFilter) {
+        let format = self.format;
+        for level in 1..self.levels.len() {
+            let src = self.levels[level - 1];
+            let dst = self.levels[level];
+            let (head, tail) = self.bytes_mut().split_at_mut(dst.offset);
+            mipmap::downsample(
+                format,
+                filter,
+                &head[src.offset..(src.offset + src.byte_size(format))],
+                src,
+                &mut tail[..dst.byte_size(format)],
+                dst,
+            );
+        }
+    }
+
+    /// Get raw pointer to the data of a mip level
+    pub(crate) fn level_ptr(&self, level: usize) -> *const u8 {
+        self.level_data(level).as_ptr()
+    }
+
+    /// Write back modifications from the data cache, so the GE can read them
+    pub(crate) fn sync_cache(&self) {
+        if self.dirty.replace(false) {
+            cache::writeback(&self.data);
+        }
+    }
+
+    /// Get the data of all mip levels
+    pub(crate) fn bytes(&self) -> &[u8] {
+        let len = size_of_val(self.data.as_slice());
+        // SAFETY: `UnsafeCell<Block>` has the layout of `Block`, writes through shared
+        // references only happen in `write_region`, whose caller ensures no slice is alive
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr().cast(), len) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self.dirty.set(true);
+        let len = size_of_val(self.data.as_slice());
+        // SAFETY: same layout as in `bytes`, the data is borrowed exclusively
+        unsafe { core::slice::from_raw_parts_mut(self.data.as_mut_ptr().cast(), len) }
+    }
+}
+
+/// Texture stored in VRAM, created by [`PspGfx::upload_texture`](crate::PspGfx::upload_texture)
+///
+/// Uses the same level layout as the [`Texture`] it was uploaded from. The VRAM is freed
+/// when the texture is dropped.
+pub struct VramTexture {
+    format: TexturePixelFormat,
+    levels: Vec<TextureLevel>,
+    block: VramBlock,
+}
+
+impl VramTexture {
+    pub(crate) fn new(
+        format: TexturePixelFormat,
+        levels: Vec<TextureLevel>,
+        block: VramBlock,
+    ) -> Self {
+        Self {
+            format,
+            levels,
+            block,
+        }
+    }
+
+    /// Get pixel format of the texture
+    pub fn format(&self) -> TexturePixelFormat {
+        self.format
+    }
+
+    /// Get width of the base level in pixels
+    pub fn width(&self) -> u32 {
+        self.levels[0].width
+    }
+
+    /// Get height of the base level in pixels
+    pub fn height(&self) -> u32 {
+        self.levels[0].height
+    }
+
+    /// Get amount of mip levels (including the base level)
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Get layout of all mip levels
+    pub fn levels(&self) -> &[TextureLevel] {
+        &self.levels
+    }
+
+    /// Get the VRAM block holding all levels
+    pub fn block(&self) -> &VramBlock {
+        &self.block
+    }
+
+    /// Get the address of a mip level in VRAM
+    pub fn level_address(&self, level: usize) -> u32 {
+        self.block.as_mut_ptr_direct_to_vram() as u32 + self.levels[level].offset as u32
+    }
+
+    /// Get a mip level as a block transfer source/destination
+    ///
+    /// Returns `None` for formats which aren't 16 or 32 bits per pixel.
+    pub fn level_image(&self, level: usize) -> Option<TransferImage> {
+        let bits32 = match bits_per_pixel(self.format) {
+            16 => false,
+            32 => true,
+            _ => return None,
+        };
+        let TextureLevel {
+            width,
+            height,
+            buffer_width,
+            ..
+        } = self.levels[level];
+        // Levels narrower than 8 pixels can't be addressed
+        buffer_width.is_multiple_of(8).then(|| {
+            TransferImage::new(
+                self.level_address(level),
+                buffer_width,
+                width,
+                height,
+                bits32,
+            )
+        })
+    }
+}
+
+/// Convert a level index into a [`MipmapLevel`]
+pub(crate) fn mipmap_level(level: usize) -> MipmapLevel {
+    match level {
+        0 => MipmapLevel::None,
+        1 => MipmapLevel::Level1,
+        2 => MipmapLevel::Level2,
+        3 => MipmapLevel::Level3,
+        4 => MipmapLevel::Level4,
+        5 => MipmapLevel::Level5,
+        6 => MipmapLevel::Level6,
+        7 => MipmapLevel::Level7,
+        _ => panic!("mip level out of range"),
+    }
+}
+
+/// Compressed formats store rows in 4x4 blocks
+const fn block_rows(format: TexturePixelFormat, rows: u32) -> u32 {
+    if is_compressed(format) {
+        rows * 4
+    } else {
+        rows
+    }
+}
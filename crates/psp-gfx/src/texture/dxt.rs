@@ -0,0 +1,222 @@
+//! Decoding and encoding of DXT compressed 4x4 pixel blocks
+//!
+//! The PSP layout differs from the one used on PC GPUs: the color indices come before the
+//! two 5650 endpoint colors, and DXT3/DXT5 blocks store the color block before the alpha
+//! block. Pixels of a block are stored row by row.
+//!
+//! The encoder uses the extreme colors along the principal axis of the block colors as
+//! endpoints. It's fast and simple, but loses more quality than offline compressors.
+//!
+//! Only depends on `core`, so it's shared with the host tools.
+
+use crate::color::{Color32, Color5650};
+use crate::ge::sys::TexturePixelFormat;
+
+/// Pixels of a 4x4 block as RGBA, row by row
+pub type Block = [[u8; 4]; 16];
+
+/// Get size of a compressed block in bytes
+pub const fn block_size(format: TexturePixelFormat) -> usize {
+    match format {
+        TexturePixelFormat::PsmDxt1 => 8,
+        _ => 16,
+    }
+}
+
+/// Decode a compressed block
+pub fn decode_block(format: TexturePixelFormat, data: &[u8]) -> Block {
+    let dxt1 = matches!(format, TexturePixelFormat::PsmDxt1);
+    let mut pixels = decode_colors(data, !dxt1);
+    match format {
+        TexturePixelFormat::PsmDxt3 => {
+            let alpha = u64::from_le_bytes(data[8..16].try_into().unwrap());
+            for (idx, pixel) in pixels.iter_mut().enumerate() {
+                pixel[3] = ((alpha >> (idx * 4)) & 0xf) as u8 * 17;
+            }
+        }
+        TexturePixelFormat::PsmDxt5 => {
+            let palette = alpha_palette(data[14], data[15]);
+            let indices = alpha_indices(data);
+            for (idx, pixel) in pixels.iter_mut().enumerate() {
+                pixel[3] = palette[((indices >> (idx * 3)) & 7) as usize];
+            }
+        }
+        _ => {}
+    }
+    pixels
+}
+
+/// Encode pixels into a compressed block
+///
+/// DXT1 blocks containing pixels with alpha below 128 store them as transparent black.
+pub fn encode_block(format: TexturePixelFormat, pixels: &Block, data: &mut [u8]) {
+    match format {
+        TexturePixelFormat::PsmDxt1 => encode_colors(pixels, true, data),
+        TexturePixelFormat::PsmDxt3 => {
+            encode_colors(pixels, false, data);
+            let alpha = pixels.iter().enumerate().fold(0u64, |acc, (idx, pixel)| {
+                let value = (pixel[3] as u64 * 15 + 127) / 255;
+                acc | (value << (idx * 4))
+            });
+            data[8..16].copy_from_slice(&alpha.to_le_bytes());
+        }
+        TexturePixelFormat::PsmDxt5 => {
+            encode_colors(pixels, false, data);
+            encode_alpha(pixels, data);
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Decode the color block, `four_colors` ignores the 1-bit alpha mode of DXT1
+fn decode_colors(data: &[u8], four_colors: bool) -> Block {
+    let indices = u32::from_le_bytes(data[0..4].try_into().unwrap());
+    let c0 = u16::from_le_bytes([data[4], data[5]]);
+    let c1 = u16::from_le_bytes([data[6], data[7]]);
+    let palette = color_palette(c0, c1, four_colors || c0 > c1);
+    core::array::from_fn(|idx| palette[((indices >> (idx * 2)) & 3) as usize])
+}
+
+fn color_palette(c0: u16, c1: u16, four_colors: bool) -> [[u8; 4]; 4] {
+    let a = Color5650::from_bits(c0).to_color32().to_array();
+    let b = Color5650::from_bits(c1).to_color32().to_array();
+    let mix = |wa: u32, wb: u32| {
+        core::array::from_fn(|ch| ((a[ch] as u32 * wa + b[ch] as u32 * wb) / (wa + wb)) as u8)
+    };
+    if four_colors {
+        [a, b, mix(2, 1), mix(1, 2)]
+    } else {
+        [a, b, mix(1, 1), [0; 4]]
+    }
+}
+
+fn encode_colors(pixels: &Block, punch_through: bool, data: &mut [u8]) {
+    let transparent = |pixel: &[u8; 4]| punch_through && pixel[3] < 128;
+    let (max, min) = color_endpoints(pixels.iter().filter(|pixel| !transparent(pixel)));
+    let endpoint =
+        |[r, g, b]: [u8; 3]| Color5650::from_color32(Color32::new(r, g, b, 255)).to_bits();
+    let (mut c0, mut c1) = (endpoint(max), endpoint(min));
+
+    // The order of the endpoints selects the mode, `c0 <= c1` has a transparent entry
+    let has_transparent = pixels.iter().any(transparent);
+    if has_transparent == (c0 > c1) {
+        core::mem::swap(&mut c0, &mut c1);
+    }
+    let four_colors = !punch_through || c0 > c1;
+    let palette = color_palette(c0, c1, four_colors);
+    let candidates = if four_colors { 4 } else { 3 };
+
+    let indices = pixels.iter().enumerate().fold(0u32, |acc, (idx, pixel)| {
+        let index = if transparent(pixel) {
+            3
+        } else {
+            nearest(&palette[..candidates], |entry| {
+                (0..3)
+                    .map(|ch| (entry[ch] as i32 - pixel[ch] as i32).pow(2) as u32)
+                    .sum()
+            })
+        };
+        acc | ((index as u32) << (idx * 2))
+    });
+    data[0..4].copy_from_slice(&indices.to_le_bytes());
+    data[4..6].copy_from_slice(&c0.to_le_bytes());
+    data[6..8].copy_from_slice(&c1.to_le_bytes());
+}
+
+/// Get the colors at both ends of the principal axis of the pixel colors
+///
+/// The axis is approximated by a few power iterations of the covariance matrix.
+fn color_endpoints<'a>(pixels: impl Iterator<Item = &'a [u8; 4]> + Clone) -> ([u8; 3], [u8; 3]) {
+    let rgb = |pixel: &[u8; 4]| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32];
+    let count = pixels.clone().count();
+    if count == 0 {
+        return ([0; 3], [0; 3]);
+    }
+    let mut mean = [0.; 3];
+    for pixel in pixels.clone() {
+        for (acc, value) in mean.iter_mut().zip(rgb(pixel)) {
+            *acc += value / count as f32;
+        }
+    }
+    let mut covariance = [[0.; 3]; 3];
+    for pixel in pixels.clone() {
+        let delta = core::array::from_fn::<f32, 3, _>(|ch| rgb(pixel)[ch] - mean[ch]);
+        for (row, &a) in covariance.iter_mut().zip(&delta) {
+            for (value, &b) in row.iter_mut().zip(&delta) {
+                *value += a * b;
+            }
+        }
+    }
+    let mut axis = [1., 1., 1.];
+    for _ in 0..8 {
+        let next: [f32; 3] =
+            core::array::from_fn(|row| (0..3).map(|col| covariance[row][col] * axis[col]).sum());
+        let length = next.iter().fold(0f32, |acc, value| acc.max(value.abs()));
+        if length == 0. {
+            break;
+        }
+        axis = next.map(|value| value / length);
+    }
+    let project = |pixel: &&[u8; 4]| {
+        let value: f32 = (0..3)
+            .map(|ch| (rgb(pixel)[ch] - mean[ch]) * axis[ch])
+            .sum();
+        // Keys must be totally ordered, the projection is at most a few thousand
+        (value * 16.) as i32
+    };
+    let max = pixels.clone().max_by_key(project).unwrap();
+    let min = pixels.min_by_key(project).unwrap();
+    ([max[0], max[1], max[2]], [min[0], min[1], min[2]])
+}
+
+/// Get the 8 alpha values of a DXT5 block
+fn alpha_palette(a0: u8, a1: u8) -> [u8; 8] {
+    let (a0, a1) = (a0 as u32, a1 as u32);
+    if a0 > a1 {
+        core::array::from_fn(|idx| match idx {
+            0 => a0 as u8,
+            1 => a1 as u8,
+            _ => ((a0 * (8 - idx as u32) + a1 * (idx as u32 - 1)) / 7) as u8,
+        })
+    } else {
+        core::array::from_fn(|idx| match idx {
+            0 => a0 as u8,
+            1 => a1 as u8,
+            6 => 0,
+            7 => 255,
+            _ => ((a0 * (6 - idx as u32) + a1 * (idx as u32 - 1)) / 5) as u8,
+        })
+    }
+}
+
+/// Get the 48 bits of 3-bit alpha indices, stored as a 32-bit and a 16-bit word
+fn alpha_indices(data: &[u8]) -> u64 {
+    let low = u32::from_le_bytes(data[8..12].try_into().unwrap()) as u64;
+    let high = u16::from_le_bytes([data[12], data[13]]) as u64;
+    (high << 32) | low
+}
+
+fn encode_alpha(pixels: &Block, data: &mut [u8]) {
+    let a0 = pixels.iter().map(|pixel| pixel[3]).max().unwrap();
+    let a1 = pixels.iter().map(|pixel| pixel[3]).min().unwrap();
+    // Equal endpoints select the 6 value mode, where index 0 is still `a0`
+    let palette = alpha_palette(a0, a1);
+    let indices = pixels.iter().enumerate().fold(0u64, |acc, (idx, pixel)| {
+        let index = nearest(&palette, |&entry| entry.abs_diff(pixel[3]) as u32);
+        acc | ((index as u64) << (idx * 3))
+    });
+    data[8..12].copy_from_slice(&(indices as u32).to_le_bytes());
+    data[12..14].copy_from_slice(&((indices >> 32) as u16).to_le_bytes());
+    data[14] = a0;
+    data[15] = a1;
+}
+
+/// Get the index of the palette entry with the smallest error
+fn nearest<T>(palette: &[T], error: impl Fn(&T) -> u32) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| error(entry))
+        .map(|(idx, _)| idx)
+        .unwrap()
+}
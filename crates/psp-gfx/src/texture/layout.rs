@@ -0,0 +1,103 @@
+//! Layout of texture data in memory
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools.
+
+use alloc::vec::Vec;
+
+use crate::ge::sys::TexturePixelFormat;
+
+/// Alignment of each level in bytes, required by the GE
+const LEVEL_ALIGN: usize = 16;
+
+/// Size and location of a single mip level inside of the texture data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureLevel {
+    /// Width of the level in pixels
+    pub width: u32,
+    /// Height of the level in pixels
+    pub height: u32,
+    /// Row stride of the level in pixels (block aligned)
+    pub buffer_width: u32,
+    /// Offset of the level in bytes from the start of the texture data
+    pub offset: usize,
+}
+
+impl TextureLevel {
+    /// Get size of the level in bytes
+    pub fn byte_size(&self, format: TexturePixelFormat) -> usize {
+        level_byte_size(format, self.buffer_width, self.height)
+    }
+}
+
+/// Get bits per pixel of a texture pixel format
+pub const fn bits_per_pixel(format: TexturePixelFormat) -> u32 {
+    match format {
+        TexturePixelFormat::PsmT4 | TexturePixelFormat::PsmDxt1 => 4,
+        TexturePixelFormat::PsmT8 | TexturePixelFormat::PsmDxt3 | TexturePixelFormat::PsmDxt5 => 8,
+        TexturePixelFormat::Psm5650
+        | TexturePixelFormat::Psm5551
+        | TexturePixelFormat::Psm4444
+        | TexturePixelFormat::PsmT16 => 16,
+        TexturePixelFormat::Psm8888 | TexturePixelFormat::PsmT32 => 32,
+    }
+}
+
+/// Check if the pixel format is block compressed
+pub const fn is_compressed(format: TexturePixelFormat) -> bool {
+    matches!(
+        format,
+        TexturePixelFormat::PsmDxt1 | TexturePixelFormat::PsmDxt3 | TexturePixelFormat::PsmDxt5
+    )
+}
+
+/// Check if the pixel format uses a color lookup table
+pub const fn is_indexed(format: TexturePixelFormat) -> bool {
+    matches!(
+        format,
+        TexturePixelFormat::PsmT4
+            | TexturePixelFormat::PsmT8
+            | TexturePixelFormat::PsmT16
+            | TexturePixelFormat::PsmT32
+    )
+}
+
+pub(crate) fn level_byte_size(format: TexturePixelFormat, buffer_width: u32, height: u32) -> usize {
+    if is_compressed(format) {
+        // 4x4 pixel blocks
+        let blocks = buffer_width.div_ceil(4) * height.div_ceil(4);
+        (blocks * 16 * bits_per_pixel(format) / 8) as usize
+    } else {
+        (buffer_width * height * bits_per_pixel(format)).div_ceil(8) as usize
+    }
+}
+
+/// Get the layout of `level_count` mip levels, each half the size of the previous one (but
+/// no smaller than 1x1)
+pub fn layout_levels(
+    format: TexturePixelFormat,
+    width: u32,
+    height: u32,
+    level_count: usize,
+) -> Vec<TextureLevel> {
+    // Rows must be a multiple of 16 bytes
+    let align = if is_compressed(format) {
+        4
+    } else {
+        128 / bits_per_pixel(format)
+    };
+    let mut offset = 0;
+    (0..level_count)
+        .map(|idx| {
+            let width = (width >> idx).max(1);
+            let height = (height >> idx).max(1);
+            let level = TextureLevel {
+                width,
+                height,
+                buffer_width: width.next_multiple_of(align),
+                offset,
+            };
+            offset += level.byte_size(format).next_multiple_of(LEVEL_ALIGN);
+            level
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+//! Host-side mip level generation
+//!
+//! Only depends on `core` and `alloc`, so it's shared with the host tools.
+
+use alloc::vec::Vec;
+
+use super::{TextureLevel, bits_per_pixel, dxt, is_compressed, is_indexed};
+use crate::color::{Color32, Color4444, Color5551, Color5650};
+use crate::ge::sys::TexturePixelFormat;
+
+/// Filter used to downsample mip levels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MipmapFilter {
+    /// Average of each 2x2 pixel block
+    #[default]
+    Box,
+    /// 4x4 tent filter (`1 3 3 1` weights), produces smoother results than [`MipmapFilter::Box`]
+    ///
+    /// Axes which are already 1 pixel wide/tall are copied, like with the box filter.
+    Triangle,
+}
+
+/// Downsample `src` level into `dst` level, which must be half its size
+///
+/// Compressed formats are decoded, downsampled and compressed again.
+pub fn downsample(
+    format: TexturePixelFormat,
+    filter: MipmapFilter,
+    src: &[u8],
+    src_level: TextureLevel,
+    dst: &mut [u8],
+    dst_level: TextureLevel,
+) {
+    if is_indexed(format) {
+        downsample_nearest(format, src, src_level, dst, dst_level);
+        return;
+    }
+
+    let src_pixels = decode_level(format, src, src_level);
+    let fetch = |x: i32, y: i32| {
+        let x = x.clamp(0, src_level.width as i32 - 1) as u32;
+        let y = y.clamp(0, src_level.height as i32 - 1) as u32;
+        src_pixels[(y * src_level.buffer_width + x) as usize]
+    };
+
+    // Scale factor per axis (1 if the source is already 1 pixel wide/tall)
+    let sx = (src_level.width / dst_level.width) as i32;
+    let sy = (src_level.height / dst_level.height) as i32;
+    // The outer taps are dropped on axes which aren't downsampled
+    let tent = |scale: i32| {
+        let outer = if scale == 1 { 0 } else { 1 };
+        [(-1, outer), (0, 3), (scale - 1, 3), (scale, outer)]
+    };
+    let (tent_x, tent_y) = (tent(sx), tent(sy));
+
+    let mut dst_pixels = alloc::vec![[0; 4]; (dst_level.buffer_width * dst_level.height) as usize];
+    for y in 0..dst_level.height {
+        for x in 0..dst_level.width {
+            let (x0, y0) = (x as i32 * sx, y as i32 * sy);
+            dst_pixels[(y * dst_level.buffer_width + x) as usize] = match filter {
+                MipmapFilter::Box => {
+                    weighted(&[(0, 1), (sx - 1, 1)], &[(0, 1), (sy - 1, 1)], |dx, dy| {
+                        fetch(x0 + dx, y0 + dy)
+                    })
+                }
+                MipmapFilter::Triangle => {
+                    weighted(&tent_x, &tent_y, |dx, dy| fetch(x0 + dx, y0 + dy))
+                }
+            };
+        }
+    }
+    encode_level(format, &dst_pixels, dst, dst_level);
+}
+
+/// Decode all pixels of a level as RGBA, rows are `buffer_width` pixels long
+fn decode_level(format: TexturePixelFormat, data: &[u8], level: TextureLevel) -> Vec<[u8; 4]> {
+    let len = (level.buffer_width * level.height) as usize;
+    if !is_compressed(format) {
+        return (0..len).map(|idx| decode(format, data, idx)).collect();
+    }
+    let mut pixels = alloc::vec![[0; 4]; len];
+    for_each_block(format, level, |idx, x, y| {
+        let block = dxt::decode_block(format, &data[idx * dxt::block_size(format)..]);
+        for (offset, pixel) in block.into_iter().enumerate() {
+            let (x, y) = (x + offset as u32 % 4, y + offset as u32 / 4);
+            if y < level.height {
+                pixels[(y * level.buffer_width + x) as usize] = pixel;
+            }
+        }
+    });
+    pixels
+}
+
+/// Encode RGBA pixels of a level, rows are `buffer_width` pixels long
+fn encode_level(
+    format: TexturePixelFormat,
+    pixels: &[[u8; 4]],
+    data: &mut [u8],
+    level: TextureLevel,
+) {
+    if !is_compressed(format) {
+        for y in 0..level.height {
+            for x in 0..level.width {
+                let idx = (y * level.buffer_width + x) as usize;
+                encode(format, data, idx, pixels[idx]);
+            }
+        }
+        return;
+    }
+    for_each_block(format, level, |idx, x, y| {
+        // Blocks of levels smaller than 4x4 repeat the edge pixels
+        let block = core::array::from_fn(|offset| {
+            let x = (x + offset as u32 % 4).min(level.width - 1);
+            let y = (y + offset as u32 / 4).min(level.height - 1);
+            pixels[(y * level.buffer_width + x) as usize]
+        });
+        let size = dxt::block_size(format);
+        dxt::encode_block(format, &block, &mut data[idx * size..(idx + 1) * size]);
+    });
+}
+
+/// Call `f` with the index and top left pixel of each 4x4 block of a compressed level
+fn for_each_block(
+    format: TexturePixelFormat,
+    level: TextureLevel,
+    mut f: impl FnMut(usize, u32, u32),
+) {
+    debug_assert!(is_compressed(format));
+    let columns = level.buffer_width / 4;
+    for row in 0..level.height.div_ceil(4) {
+        for column in 0..columns {
+            f((row * columns + column) as usize, column * 4, row * 4);
+        }
+    }
+}
+
+/// Compute a weighted average of pixels using separable per-axis `(offset, weight)` taps
+fn weighted(
+    taps_x: &[(i32, u32)],
+    taps_y: &[(i32, u32)],
+    fetch: impl Fn(i32, i32) -> [u8; 4],
+) -> [u8; 4] {
+    let mut sum = [0u32; 4];
+    let mut total = 0;
+    for &(dy, wy) in taps_y {
+        for &(dx, wx) in taps_x {
+            let pixel = fetch(dx, dy);
+            let weight = wx * wy;
+            for (acc, channel) in sum.iter_mut().zip(pixel) {
+                *acc += channel as u32 * weight;
+            }
+            total += weight;
+        }
+    }
+    sum.map(|acc| ((acc + total / 2) / total) as u8)
+}
+
+/// Nearest-neighbour downsampling, operating on raw texel values
+fn downsample_nearest(
+    format: TexturePixelFormat,
+    src: &[u8],
+    src_level: TextureLevel,
+    dst: &mut [u8],
+    dst_level: TextureLevel,
+) {
+    let sx = src_level.width / dst_level.width;
+    let sy = src_level.height / dst_level.height;
+    let bpp = bits_per_pixel(format);
+    for y in 0..dst_level.height {
+        for x in 0..dst_level.width {
+            let src_idx = (y * sy * src_level.buffer_width + x * sx) as usize;
+            let dst_idx = (y * dst_level.buffer_width + x) as usize;
+            if bpp == 4 {
+                let value = (src[src_idx / 2] >> ((src_idx % 2) * 4)) & 0xf;
+                let shift = (dst_idx % 2) * 4;
+                dst[dst_idx / 2] = (dst[dst_idx / 2] & !(0xf << shift)) | (value << shift);
+            } else {
+                let size = (bpp / 8) as usize;
+                dst[(dst_idx * size)..((dst_idx + 1) * size)]
+                    .copy_from_slice(&src[(src_idx * size)..((src_idx + 1) * size)]);
+            }
+        }
+    }
+}
+
+/// Read pixel at `idx` as RGBA
+fn decode(format: TexturePixelFormat, data: &[u8], idx: usize) -> [u8; 4] {
+    let read16 = || u16::from_le_bytes([data[idx * 2], data[idx * 2 + 1]]);
+    let color = match format {
+        TexturePixelFormat::Psm8888 => {
+            let mut pixel = [0; 4];
+            pixel.copy_from_slice(&data[(idx * 4)..(idx * 4 + 4)]);
+            return pixel;
+        }
+        TexturePixelFormat::Psm5650 => Color5650::from_bits(read16()).to_color32(),
+        TexturePixelFormat::Psm5551 => Color5551::from_bits(read16()).to_color32(),
+        TexturePixelFormat::Psm4444 => Color4444::from_bits(read16()).to_color32(),
+        _ => unreachable!(),
+    };
+    color.to_array()
+}
+
+/// Write RGBA pixel at `idx`
+fn encode(format: TexturePixelFormat, data: &mut [u8], idx: usize, pixel: [u8; 4]) {
+    let [r, g, b, a] = pixel;
+    let color = Color32::new(r, g, b, a);
+    let value = match format {
+        TexturePixelFormat::Psm8888 => {
+            data[(idx * 4)..(idx * 4 + 4)].copy_from_slice(&pixel);
+            return;
+        }
+        TexturePixelFormat::Psm5650 => Color5650::from_color32(color).to_bits(),
+        TexturePixelFormat::Psm5551 => Color5551::from_color32(color).to_bits(),
+        TexturePixelFormat::Psm4444 => Color4444::from_color32(color).to_bits(),
+        _ => unreachable!(),
+    };
+    data[(idx * 2)..(idx * 2 + 2)].copy_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,173 @@
+//! Block transfers between images in RAM and VRAM
+//!
+//! The GE copies rectangles of 16 or 32-bit pixels by DMA, without involving the CPU.
+//! Transfers run in display list order, so [`Frame::copy_image`](crate::Frame::copy_image)
+//! can copy between render targets and textures in the middle of a frame. Uploads outside
+//! of a frame, like [`PspGfx::upload_texture`](crate::PspGfx::upload_texture), wait for the
+//! GE to finish. Linear copies are split into rectangles as well, `sceDmacMemcpy` isn't
+//! available in the `psp` crate.
+//!
+//! The GE reads RAM directly, so the data cache is written back for sources in RAM and
+//! invalidated for destinations in RAM. The rest of this module only computes transfers,
+//! so it's shared with the host tools.
+
+use crate::ge::{CommandSink, Encoder};
+use crate::rect::{Point, Rect};
+use core::ops::Range;
+
+/// Maximum width/height of a transfer (and of the position inside of an image)
+pub const MAX_TRANSFER_SIZE: u32 = 1024;
+
+/// Maximum row stride of an image in pixels
+pub const MAX_BUFFER_WIDTH: u32 = 2040;
+
+/// Row length in pixels of linear copies
+const LINEAR_WIDTH: u32 = 512;
+
+/// Image in RAM or VRAM which can be used in block transfers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransferImage {
+    /// Address of the top left pixel, 16 byte aligned
+    pub address: u32,
+    /// Row stride in pixels, a multiple of 8
+    pub buffer_width: u32,
+    pub width: u32,
+    pub height: u32,
+    /// `true` for 32-bit pixels, `false` for 16-bit pixels
+    pub bits32: bool,
+}
+
+impl TransferImage {
+    pub const fn new(
+        address: u32,
+        buffer_width: u32,
+        width: u32,
+        height: u32,
+        bits32: bool,
+    ) -> Self {
+        assert!(
+            address.is_multiple_of(16),
+            "image address must be 16 byte aligned"
+        );
+        assert!(
+            buffer_width.is_multiple_of(8) && buffer_width <= MAX_BUFFER_WIDTH,
+            "buffer width must be a multiple of 8 up to 2040"
+        );
+        assert!(
+            width <= buffer_width && width <= MAX_TRANSFER_SIZE && height <= MAX_TRANSFER_SIZE,
+            "image size is out of range"
+        );
+        Self {
+            address,
+            buffer_width,
+            width,
+            height,
+            bits32,
+        }
+    }
+
+    pub const fn bytes_per_pixel(&self) -> u32 {
+        if self.bits32 { 4 } else { 2 }
+    }
+
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    /// Get the address range covered by the rows of a rectangle (must be inside of the image)
+    pub const fn byte_range(&self, rect: Rect) -> Range<u32> {
+        let bpp = self.bytes_per_pixel();
+        let first = rect.y as u32 * self.buffer_width + rect.x as u32;
+        let last = (rect.bottom() as u32 - 1) * self.buffer_width + rect.right() as u32;
+        self.address + first * bpp..self.address + last * bpp
+    }
+
+    /// Check if the image is in VRAM (which isn't cached)
+    pub const fn is_vram(&self) -> bool {
+        (self.address & 0x0fe00000) == 0x04000000
+    }
+}
+
+/// Clip a copy of `src_rect` to `dst_pos` to the bounds of both images
+///
+/// Returns the clipped source rectangle and its destination position, `None` if nothing
+/// is left to copy.
+pub fn clip(
+    src: &TransferImage,
+    src_rect: Rect,
+    dst: &TransferImage,
+    dst_pos: Point,
+) -> Option<(Rect, Point)> {
+    let dx = dst_pos.x - src_rect.x;
+    let dy = dst_pos.y - src_rect.y;
+    let src_rect = src_rect.intersection(&src.bounds())?;
+    let src_rect = src_rect
+        .offset(dx, dy)
+        .intersection(&dst.bounds())?
+        .offset(-dx, -dy);
+    Some((src_rect, src_rect.position().offset(dx, dy)))
+}
+
+/// Split a linear copy of `len` bytes into rectangles of 32-bit pixels
+///
+/// Yields `(byte offset, width, height)`, rows are [`LINEAR_WIDTH`] pixels long. `len`
+/// must be a multiple of 4.
+pub fn linear_blocks(len: u32) -> impl Iterator<Item = (u32, u32, u32)> {
+    assert!(
+        len.is_multiple_of(4),
+        "linear copies must be a multiple of 4 bytes"
+    );
+    let row_len = LINEAR_WIDTH * 4;
+    let rows = len / row_len;
+    let full = (0..rows)
+        .step_by(MAX_TRANSFER_SIZE as usize)
+        .map(move |row| {
+            let height = (rows - row).min(MAX_TRANSFER_SIZE);
+            (row * row_len, LINEAR_WIDTH, height)
+        });
+    let rest = len % row_len;
+    full.chain((rest != 0).then_some((rows * row_len, rest / 4, 1)))
+}
+
+/// Encode a copy of `src_rect` to `dst_pos`, both images must use the same pixel size
+///
+/// The copy must be inside of both images and not empty, as returned by [`clip`]. The
+/// texture cache is flushed, so textures written by the copy can be used by following
+/// draws.
+pub fn encode_copy<S: CommandSink>(
+    ge: &mut Encoder<S>,
+    src: &TransferImage,
+    src_rect: Rect,
+    dst: &TransferImage,
+    dst_pos: Point,
+) {
+    assert_eq!(
+        src.bits32, dst.bits32,
+        "images must use the same pixel size"
+    );
+    ge.transfer_source(
+        src.address,
+        src.buffer_width,
+        src_rect.x as u32,
+        src_rect.y as u32,
+    )
+    .transfer_destination(
+        dst.address,
+        dst.buffer_width,
+        dst_pos.x as u32,
+        dst_pos.y as u32,
+    )
+    .transfer_start(src_rect.w as u32, src_rect.h as u32, src.bits32)
+    .tex_sync()
+    .tex_flush();
+}
+
+/// Encode a linear copy of `len` bytes, see [`linear_blocks`]
+pub fn encode_linear_copy<S: CommandSink>(ge: &mut Encoder<S>, src: u32, dst: u32, len: u32) {
+    for (offset, width, height) in linear_blocks(len) {
+        ge.transfer_source(src + offset, LINEAR_WIDTH, 0, 0)
+            .transfer_destination(dst + offset, LINEAR_WIDTH, 0, 0)
+            .transfer_start(width, height, true);
+    }
+    ge.tex_sync().tex_flush();
+}
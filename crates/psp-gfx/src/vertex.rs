@@ -66,23 +66,23 @@ macro_rules! define_vertex_layout {
             };
             const DEFAULT: Self = Self {
                 $(
-                    weight: { stringify!($weight); 0 },
+                    weight: $crate::define_vertex_layout!(@zero $weight),
                 )?
                 $(
-                    u: { stringify!($texture); 0 },
-                    v: 0,
+                    u: $crate::define_vertex_layout!(@zero $texture),
+                    v: $crate::define_vertex_layout!(@zero $texture),
                 )?
                 $(
                     color: $crate::define_vertex_layout!(@color_default $color),
                 )?
                 $(
-                    normal_x: { stringify!($normal); 0 },
-                    normal_y: 0,
-                    normal_z: 0,
+                    normal_x: $crate::define_vertex_layout!(@zero $normal),
+                    normal_y: $crate::define_vertex_layout!(@zero $normal),
+                    normal_z: $crate::define_vertex_layout!(@zero $normal),
                 )?
-                x: 0,
-                y: 0,
-                z: 0,
+                x: $crate::define_vertex_layout!(@zero $vertex),
+                y: $crate::define_vertex_layout!(@zero $vertex),
+                z: $crate::define_vertex_layout!(@zero $vertex),
                 _padding: [0; Self::PADDING],
             };
 
@@ -181,6 +181,45 @@ macro_rules! define_vertex_layout {
             }
         }
 
+        // SAFETY: all fields are integers, floats or colors, which are valid for any bit
+        // pattern (including the padding)
+        unsafe impl $crate::bytemuck::Zeroable for $name {}
+        unsafe impl $crate::bytemuck::AnyBitPattern for $name {}
+
+        impl $crate::clip::ClipVertex for $name {
+            fn position(&self) -> [f32; 3] {
+                [
+                    $crate::define_vertex_layout!(@position $vertex, self.x),
+                    $crate::define_vertex_layout!(@position $vertex, self.y),
+                    $crate::define_vertex_layout!(@position $vertex, self.z),
+                ]
+            }
+
+            fn lerp(&self, other: &Self, t: f32) -> Self {
+                Self {
+                    $(
+                        weight: $crate::define_vertex_layout!(@lerp $weight, self.weight, other.weight, t),
+                    )?
+                    $(
+                        u: $crate::define_vertex_layout!(@lerp $texture, self.u, other.u, t),
+                        v: $crate::define_vertex_layout!(@lerp $texture, self.v, other.v, t),
+                    )?
+                    $(
+                        color: $crate::define_vertex_layout!(@lerp $color, self.color, other.color, t),
+                    )?
+                    $(
+                        normal_x: $crate::define_vertex_layout!(@lerp $normal, self.normal_x, other.normal_x, t),
+                        normal_y: $crate::define_vertex_layout!(@lerp $normal, self.normal_y, other.normal_y, t),
+                        normal_z: $crate::define_vertex_layout!(@lerp $normal, self.normal_z, other.normal_z, t),
+                    )?
+                    x: $crate::define_vertex_layout!(@lerp $vertex, self.x, other.x, t),
+                    y: $crate::define_vertex_layout!(@lerp $vertex, self.y, other.y, t),
+                    z: $crate::define_vertex_layout!(@lerp $vertex, self.z, other.z, t),
+                    _padding: [0; Self::PADDING],
+                }
+            }
+        }
+
         impl $crate::vertex::Vertex for $name {
             fn vtype() -> ::psp::sys::VertexType {
                 ::psp::sys::VertexType::empty()
@@ -218,26 +257,26 @@ macro_rules! define_vertex_layout {
     };
 
     (@color COLOR_5650) => {
-        u16
+        $crate::color::Color5650
     };
     (@color COLOR_5551) => {
-        u16
+        $crate::color::Color5551
     };
     (@color COLOR_4444) => {
-        u16
+        $crate::color::Color4444
     };
     (@color COLOR_8888) => {
         $crate::color::Color32
     };
 
     (@color_default COLOR_5650) => {
-        0
+        $crate::color::Color5650::from_bits(0)
     };
     (@color_default COLOR_5551) => {
-        0
+        $crate::color::Color5551::from_bits(0)
     };
     (@color_default COLOR_4444) => {
-        0
+        $crate::color::Color4444::from_bits(0)
     };
     (@color_default COLOR_8888) => {
         $crate::color::Color32::TRANSPARENT
@@ -274,10 +313,100 @@ macro_rules! define_vertex_layout {
         f32
     };
 
+    (@zero TEXTURE_32BITF) => {
+        0.
+    };
+    (@zero NORMAL_32BITF) => {
+        0.
+    };
+    (@zero VERTEX_32BITF) => {
+        0.
+    };
+    (@zero WEIGHT_32BITF) => {
+        0.
+    };
+    (@zero $kind:ident) => {
+        0
+    };
+
     (@index INDEX_8BIT) => {
         u8
     };
     (@index INDEX_16BIT) => {
         u16
     };
+
+    // Positions and normals are signed, texture coordinates and weights are unsigned
+    (@position VERTEX_8BIT, $x:expr) => {
+        $x as i8 as f32 / 128.
+    };
+    (@position VERTEX_16BIT, $x:expr) => {
+        $x as i16 as f32 / 32768.
+    };
+    (@position VERTEX_32BITF, $x:expr) => {
+        $x
+    };
+
+    (@lerp VERTEX_8BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_i8($a, $b, $t)
+    };
+    (@lerp VERTEX_16BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_i16($a, $b, $t)
+    };
+    (@lerp NORMAL_8BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_i8($a, $b, $t)
+    };
+    (@lerp NORMAL_16BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_i16($a, $b, $t)
+    };
+    (@lerp TEXTURE_8BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_u8($a, $b, $t)
+    };
+    (@lerp TEXTURE_16BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_u16($a, $b, $t)
+    };
+    (@lerp WEIGHT_8BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_u8($a, $b, $t)
+    };
+    (@lerp WEIGHT_16BIT, $a:expr, $b:expr, $t:expr) => {
+        $crate::vertex::lerp_u16($a, $b, $t)
+    };
+    (@lerp COLOR_8888, $a:expr, $b:expr, $t:expr) => {
+        $a.lerp($b, $t)
+    };
+    (@lerp COLOR_5650, $a:expr, $b:expr, $t:expr) => {
+        $crate::color::Color5650::from_color32($a.to_color32().lerp($b.to_color32(), $t))
+    };
+    (@lerp COLOR_5551, $a:expr, $b:expr, $t:expr) => {
+        $crate::color::Color5551::from_color32($a.to_color32().lerp($b.to_color32(), $t))
+    };
+    (@lerp COLOR_4444, $a:expr, $b:expr, $t:expr) => {
+        $crate::color::Color4444::from_color32($a.to_color32().lerp($b.to_color32(), $t))
+    };
+    // 32-bit float components
+    (@lerp $kind:ident, $a:expr, $b:expr, $t:expr) => {
+        $a + ($b - $a) * $t
+    };
+}
+
+#[doc(hidden)]
+pub fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
+    libm::roundf(a as f32 + (b as f32 - a as f32) * t) as u8
+}
+
+#[doc(hidden)]
+pub fn lerp_u16(a: u16, b: u16, t: f32) -> u16 {
+    libm::roundf(a as f32 + (b as f32 - a as f32) * t) as u16
+}
+
+#[doc(hidden)]
+pub fn lerp_i8(a: u8, b: u8, t: f32) -> u8 {
+    let (a, b) = (a as i8 as f32, b as i8 as f32);
+    libm::roundf(a + (b - a) * t) as i8 as u8
+}
+
+#[doc(hidden)]
+pub fn lerp_i16(a: u16, b: u16, t: f32) -> u16 {
+    let (a, b) = (a as i16 as f32, b as i16 as f32);
+    libm::roundf(a + (b - a) * t) as i16 as u16
 }
@@ -0,0 +1,144 @@
+//! Viewports and split-screen layouts
+//!
+//! The GE maps normalized device coordinates into a 4096x4096 drawing area, and the
+//! drawing offset selects which part of it lands in the framebuffer. A [`Viewport`] is
+//! centered in the drawing area, so its [`GuardBand`] is the same size on all sides, and
+//! the offset moves it to its rectangle on screen. Drawing is scissored to the rectangle.
+
+use alloc::vec::Vec;
+use psp::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+use crate::{Frame, clip::GuardBand, rect::Rect};
+
+/// Center of the 4096x4096 drawing area
+pub const DRAWING_AREA_CENTER: i32 = 2048;
+
+/// Rectangle of the screen that 3D rendering is mapped to, set with [`Frame::set_viewport`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub rect: Rect,
+}
+
+impl Viewport {
+    pub const FULL_SCREEN: Self =
+        Self::new(Rect::new(0, 0, SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32));
+
+    pub const fn new(rect: Rect) -> Self {
+        Self { rect }
+    }
+
+    /// Get width divided by height, used for the projection matrix
+    pub const fn aspect_ratio(&self) -> f32 {
+        self.rect.w as f32 / self.rect.h as f32
+    }
+
+    /// Get the drawing offset which moves the viewport to its rectangle on screen
+    pub const fn offset(&self) -> (u32, u32) {
+        let center = self.rect.center();
+        (
+            (DRAWING_AREA_CENTER - center.x) as u32,
+            (DRAWING_AREA_CENTER - center.y) as u32,
+        )
+    }
+
+    /// Get the center of the viewport in the drawing area
+    ///
+    /// Odd sizes are centered between pixels, so the edges line up with the rectangle.
+    pub const fn center(&self) -> (f32, f32) {
+        (
+            DRAWING_AREA_CENTER as f32 + (self.rect.w % 2) as f32 / 2.,
+            DRAWING_AREA_CENTER as f32 + (self.rect.h % 2) as f32 / 2.,
+        )
+    }
+
+    /// Get the guard band used to clip triangles drawn in the viewport
+    pub const fn guard_band(&self) -> GuardBand {
+        GuardBand::for_viewport(self.rect.w as u32, self.rect.h as u32)
+    }
+
+    /// Split into a left and a right half
+    pub fn split_vertical(&self) -> (Self, Self) {
+        let (left, right) = self.rect.split_left(self.rect.w / 2);
+        (Self::new(left), Self::new(right))
+    }
+
+    /// Split into a top and a bottom half
+    pub fn split_horizontal(&self) -> (Self, Self) {
+        let (top, bottom) = self.rect.split_top(self.rect.h / 2);
+        (Self::new(top), Self::new(bottom))
+    }
+
+    /// Split into a grid of `columns * rows` viewports, row by row
+    ///
+    /// Remaining pixels go to the last column and row.
+    pub fn grid(&self, columns: u32, rows: u32) -> Vec<Self> {
+        assert!(columns > 0 && rows > 0, "grid must have at least one cell");
+        let mut cells = Vec::with_capacity((columns * rows) as usize);
+        let mut rest = self.rect;
+        for row in 0..rows as i32 {
+            let (mut line, below) = rest.split_top(self.rect.h / rows as i32);
+            if row == rows as i32 - 1 {
+                line = rest;
+            }
+            rest = below;
+            for column in 0..columns as i32 {
+                let (mut cell, right) = line.split_left(self.rect.w / columns as i32);
+                if column == columns as i32 - 1 {
+                    cell = line;
+                }
+                line = right;
+                cells.push(Self::new(cell));
+            }
+        }
+        cells
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::FULL_SCREEN
+    }
+}
+
+/// Get the viewports of a split-screen layout for 1 to 4 players
+///
+/// Two players are side by side, which keeps the aspect ratio closest to square on the
+/// wide screen. Three and four players use a 2x2 grid (the fourth cell is unused for
+/// three players).
+pub fn split_screen(players: usize) -> Vec<Viewport> {
+    let screen = Viewport::FULL_SCREEN;
+    match players {
+        1 => alloc::vec![screen],
+        2 => {
+            let (left, right) = screen.split_vertical();
+            alloc::vec![left, right]
+        }
+        3 | 4 => {
+            let mut cells = screen.grid(2, 2);
+            cells.truncate(players);
+            cells
+        }
+        _ => panic!("split screen supports 1 to 4 players"),
+    }
+}
+
+impl<'gfx> Frame<'gfx> {
+    /// Map 3D rendering to a viewport and scissor drawing to it
+    ///
+    /// Stays active for following frames, like other render state.
+    pub fn set_viewport(&self, viewport: &Viewport) {
+        let (x, y) = viewport.offset();
+        self.ge().offset(x, y).viewport(
+            viewport.center(),
+            viewport.rect.w as f32,
+            viewport.rect.h as f32,
+        );
+        self.set_scissor(viewport.rect);
+        self.gfx.viewport.set(*viewport);
+    }
+
+    /// Get the current viewport
+    pub fn viewport(&self) -> Viewport {
+        self.gfx.viewport.get()
+    }
+}
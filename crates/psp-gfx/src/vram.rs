@@ -0,0 +1,113 @@
+//! VRAM allocation
+//!
+//! [`VramHeap`] is a first-fit allocator over a region of offsets, it doesn't touch
+//! memory itself. Freed blocks are merged with their free neighbours. Pinned blocks are
+//! never moved, as their addresses are referenced by GE state and display lists.
+//! Relocatable blocks are moved by [`PspGfx::defragment_vram`](crate::PspGfx::defragment_vram)
+//! to merge the free space between them.
+//!
+//! [`PspGfx`](crate::PspGfx) owns the heap of the whole VRAM, allocate from it with
+//! [`PspGfx::alloc_vram`](crate::PspGfx::alloc_vram), blocks are freed when dropped.
+
+use crate::cache::Uncached;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use psp::sys::{self, TexturePixelFormat};
+
+mod heap;
+
+pub use heap::{Location, VramHeap, VramMove, VramStats};
+
+/// Alignment of textures and render targets
+pub const TEXTURE_ALIGN: u32 = 16;
+
+/// Get the size in bytes of an image
+pub const fn image_size(width: u32, height: u32, format: TexturePixelFormat) -> u32 {
+    let pixels = width * height;
+    match format {
+        TexturePixelFormat::PsmT4 | TexturePixelFormat::PsmDxt1 => pixels / 2,
+        TexturePixelFormat::PsmT8 | TexturePixelFormat::PsmDxt3 | TexturePixelFormat::PsmDxt5 => {
+            pixels
+        }
+        TexturePixelFormat::Psm8888 | TexturePixelFormat::PsmT32 => pixels * 4,
+        _ => pixels * 2,
+    }
+}
+
+/// Block of VRAM, freed when dropped
+///
+/// Relocatable blocks can change their offset in
+/// [`PspGfx::defragment_vram`](crate::PspGfx::defragment_vram), their offset and pointers
+/// have to be read again afterwards.
+pub struct VramBlock {
+    heap: Rc<RefCell<VramHeap>>,
+    location: Location,
+    size: u32,
+    relocatable: bool,
+}
+
+impl VramBlock {
+    pub(crate) fn alloc(heap: &Rc<RefCell<VramHeap>>, size: u32, align: u32) -> Option<Self> {
+        let offset = heap.borrow_mut().alloc(size, align)?;
+        Some(Self {
+            heap: heap.clone(),
+            location: Rc::new(offset.into()),
+            size,
+            relocatable: false,
+        })
+    }
+
+    pub(crate) fn alloc_relocatable(
+        heap: &Rc<RefCell<VramHeap>>,
+        size: u32,
+        align: u32,
+    ) -> Option<Self> {
+        let location = heap.borrow_mut().alloc_relocatable(size, align)?;
+        Some(Self {
+            heap: heap.clone(),
+            location,
+            size,
+            relocatable: true,
+        })
+    }
+
+    /// Offset from the start of VRAM
+    pub fn offset(&self) -> u32 {
+        self.location.get()
+    }
+
+    /// Check if the block can be moved by
+    /// [`PspGfx::defragment_vram`](crate::PspGfx::defragment_vram)
+    pub fn is_relocatable(&self) -> bool {
+        self.relocatable
+    }
+
+    pub fn len(&self) -> u32 {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Pointer relative to the start of VRAM, as used by the `sceGu*Buffer` functions
+    pub fn as_mut_ptr_from_zero(&self) -> *mut u8 {
+        self.offset() as usize as *mut u8
+    }
+
+    /// Pointer to the block in VRAM
+    pub fn as_mut_ptr_direct_to_vram(&self) -> *mut u8 {
+        unsafe { sys::sceGeEdramGetAddr().add(self.offset() as usize) }
+    }
+
+    /// Uncached pointer to the block, for CPU writes which the GE has to see
+    pub fn uncached(&self) -> Uncached<u8> {
+        unsafe { Uncached::new(self.as_mut_ptr_direct_to_vram(), self.size as usize) }
+    }
+}
+
+impl Drop for VramBlock {
+    fn drop(&mut self) {
+        self.heap.borrow_mut().free(self.offset(), self.size);
+    }
+}
@@ -0,0 +1,211 @@
+//! First-fit allocator of VRAM offsets
+//!
+//! The heap only manages offsets, it doesn't touch memory itself. Allocations are either
+//! pinned or relocatable: [`VramHeap::compact`] moves relocatable allocations to the
+//! start of the heap and reports the copies the caller has to make. Relocatable
+//! allocations share their offset with the heap as a [`Location`], which is updated when
+//! they are moved.
+
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::Cell;
+
+/// Offset of a relocatable allocation, updated by [`VramHeap::compact`]
+pub type Location = Rc<Cell<u32>>;
+
+/// First-fit allocator of offsets in `0..size`
+#[derive(Debug)]
+pub struct VramHeap {
+    size: u32,
+    /// Free ranges (start, end), sorted and never adjacent
+    free: Vec<(u32, u32)>,
+    /// Live allocations, sorted by offset
+    blocks: Vec<Allocation>,
+}
+
+#[derive(Debug)]
+struct Allocation {
+    location: Location,
+    size: u32,
+    align: u32,
+    relocatable: bool,
+}
+
+/// Usage statistics of a [`VramHeap`], in bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VramStats {
+    pub total: u32,
+    pub used: u32,
+    pub free: u32,
+    /// Size of the largest allocation that can succeed (without alignment)
+    pub largest_free: u32,
+    /// Number of live allocations
+    pub allocations: u32,
+}
+
+/// Copy of `size` bytes from offset `from` to `to`, made by [`VramHeap::compact`]
+///
+/// Allocations only move towards the start of the heap and the moves are ordered by
+/// offset, so they can be made one after another with an overlapping copy (`memmove`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VramMove {
+    pub from: u32,
+    pub to: u32,
+    pub size: u32,
+}
+
+impl VramHeap {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            free: alloc::vec![(0, size)],
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Allocate `size` bytes aligned to `align` (a power of two), returns the offset
+    ///
+    /// The allocation is pinned, it's never moved by [`VramHeap::compact`].
+    pub fn alloc(&mut self, size: u32, align: u32) -> Option<u32> {
+        self.alloc_block(size, align, false)
+            .map(|location| location.get())
+    }
+
+    /// Allocate `size` bytes aligned to `align` which [`VramHeap::compact`] may move
+    pub fn alloc_relocatable(&mut self, size: u32, align: u32) -> Option<Location> {
+        self.alloc_block(size, align, true)
+    }
+
+    fn alloc_block(&mut self, size: u32, align: u32, relocatable: bool) -> Option<Location> {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+        let size = size.max(1);
+        let (index, start) = self
+            .free
+            .iter()
+            .enumerate()
+            .find_map(|(index, &(start, end))| {
+                let aligned = start.checked_next_multiple_of(align)?;
+                (aligned.checked_add(size)? <= end).then_some((index, aligned))
+            })?;
+
+        let (free_start, free_end) = self.free[index];
+        let end = start + size;
+        match (free_start < start, end < free_end) {
+            (false, false) => {
+                self.free.remove(index);
+            }
+            (true, false) => self.free[index] = (free_start, start),
+            (false, true) => self.free[index] = (end, free_end),
+            (true, true) => {
+                self.free[index] = (free_start, start);
+                self.free.insert(index + 1, (end, free_end));
+            }
+        }
+        let location = Rc::new(Cell::new(start));
+        let block = self
+            .blocks
+            .partition_point(|block| block.location.get() < start);
+        self.blocks.insert(
+            block,
+            Allocation {
+                location: location.clone(),
+                size,
+                align,
+                relocatable,
+            },
+        );
+        Some(location)
+    }
+
+    /// Free a block returned by [`VramHeap::alloc`] with the same `size`
+    ///
+    /// Relocatable blocks are freed with the current offset of their [`Location`].
+    pub fn free(&mut self, offset: u32, size: u32) {
+        let start = offset;
+        let end = offset + size.max(1);
+        assert!(end <= self.size, "freed block is outside of the heap");
+        let block = self
+            .blocks
+            .binary_search_by_key(&start, |block| block.location.get())
+            .expect("block is already free");
+        assert_eq!(
+            self.blocks[block].size,
+            size.max(1),
+            "freed size doesn't match the allocation"
+        );
+        self.blocks.remove(block);
+
+        let index = self
+            .free
+            .partition_point(|&(free_start, _)| free_start < start);
+        let merge_prev = index > 0 && self.free[index - 1].1 == start;
+        let merge_next = self.free.get(index).is_some_and(|&(next, _)| next == end);
+        match (merge_prev, merge_next) {
+            (false, false) => self.free.insert(index, (start, end)),
+            (true, false) => self.free[index - 1].1 = end,
+            (false, true) => self.free[index].0 = start,
+            (true, true) => {
+                self.free[index - 1].1 = self.free[index].1;
+                self.free.remove(index);
+            }
+        }
+    }
+
+    /// Move relocatable allocations towards the start of the heap, closing the gaps
+    /// between allocations
+    ///
+    /// Pinned allocations stay in place, relocatable ones are placed at the lowest aligned
+    /// offset after the previous allocation. Their [`Location`]s are updated, the data has
+    /// to be copied with the returned moves, in order.
+    pub fn compact(&mut self) -> Vec<VramMove> {
+        let mut moves = Vec::new();
+        let mut cursor = 0u32;
+        for block in &self.blocks {
+            let offset = block.location.get();
+            if block.relocatable {
+                // Never past the current offset, which is aligned and ends before the
+                // next allocation
+                let to = cursor.next_multiple_of(block.align);
+                if to < offset {
+                    block.location.set(to);
+                    moves.push(VramMove {
+                        from: offset,
+                        to,
+                        size: block.size,
+                    });
+                }
+            }
+            cursor = block.location.get() + block.size;
+        }
+
+        self.free.clear();
+        let mut cursor = 0;
+        for block in &self.blocks {
+            let start = block.location.get();
+            if cursor < start {
+                self.free.push((cursor, start));
+            }
+            cursor = start + block.size;
+        }
+        if cursor < self.size {
+            self.free.push((cursor, self.size));
+        }
+        moves
+    }
+
+    pub fn stats(&self) -> VramStats {
+        let free = self.free.iter().map(|&(start, end)| end - start).sum();
+        let largest_free = self
+            .free
+            .iter()
+            .map(|&(start, end)| end - start)
+            .max()
+            .unwrap_or(0);
+        VramStats {
+            total: self.size,
+            used: self.size - free,
+            free,
+            largest_free,
+            allocations: self.blocks.len() as u32,
+        }
+    }
+}
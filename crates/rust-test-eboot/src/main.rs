@@ -9,7 +9,7 @@ use psp::{
     SCREEN_HEIGHT, SCREEN_WIDTH,
     sys::{GuPrimitive, TextureColorComponent, TextureEffect},
 };
-use psp_gfx::{PspGfx, color::Color32, define_vertex_layout, rect::Rect};
+use psp_gfx::{PspGfx, color::Color32, define_vertex_layout, gfx_ext::GfxExt, rect::Rect};
 
 const FLAG_COLORS: &[u32] = &[0xE40303, 0xFF8C00, 0xFFED00, 0x008026, 0x004CFF, 0x732982];
 const FLAG_STRIP_HEIGHT: u32 = SCREEN_HEIGHT / FLAG_COLORS.len() as u32;
@@ -39,24 +39,6 @@ fn psp_main() -> ! {
     psp::dprintln!("current clock speed {cpu}/{bus}MHz");
     psp::dprintln!("Hello PSP from rust! owo");
 
-    let mut vertices: [Vertex; FLAG_COLORS.len() * 2] = Default::default();
-    for (idx, color) in FLAG_COLORS.iter().copied().enumerate() {
-        let rect = Rect {
-            x: 0,
-            y: FLAG_STRIP_HEIGHT as i32 * idx as i32,
-            w: SCREEN_WIDTH as i32,
-            h: FLAG_STRIP_HEIGHT as i32,
-        };
-        let base_idx = idx << 1;
-        vertices[base_idx] =
-            Vertex::from_position2_color(rect.x as u16, rect.y as u16, Color32::from_rgb(color));
-        vertices[base_idx + 1] = Vertex::from_position2_color(
-            (rect.x + rect.w) as u16,
-            (rect.y + rect.h) as u16,
-            Color32::from_rgb(color),
-        );
-    }
-
     let mut gfx = PspGfx::init();
     loop {
         let frame = gfx.start_frame();
@@ -65,10 +47,18 @@ fn psp_main() -> ! {
         frame.set_texture_function(TextureEffect::Modulate, TextureColorComponent::Rgba);
         frame.set_shading_model(psp::sys::ShadingModel::Smooth);
 
-        let buf = frame.get_memory(&vertices);
-        frame.draw_array(GuPrimitive::Sprites, &buf);
+        for (idx, color) in FLAG_COLORS.iter().copied().enumerate() {
+            let rect = Rect {
+                x: 0,
+                y: FLAG_STRIP_HEIGHT as i32 * idx as i32,
+                w: SCREEN_WIDTH as i32,
+                h: FLAG_STRIP_HEIGHT as i32,
+            };
+            let color = Color32::from_rgb(color);
+            frame.gfx_gradient_rect_v(rect, color, color);
+        }
 
         let buf = frame.get_memory(TRIANGLE);
-        frame.draw_array(GuPrimitive::Triangles, &buf);
+        frame.draw_array(GuPrimitive::Triangles, buf);
     }
 }